@@ -0,0 +1,7 @@
+-- Holds the key derivation salt and a verifier for the passphrase used to
+-- encrypt secrets at rest. The table is empty when encryption is disabled.
+CREATE TABLE IF NOT EXISTS encryption (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    salt TEXT NOT NULL,
+    verifier TEXT NOT NULL
+);
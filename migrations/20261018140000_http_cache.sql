@@ -0,0 +1,14 @@
+-- Responses cached by `--cache=persistent`, keyed by the credentials that
+-- fetched them and the request URL, so one realm's client never sees
+-- responses fetched by another. Bodies are encrypted when encryption is
+-- enabled.
+CREATE TABLE IF NOT EXISTS http_cache (
+    scope TEXT NOT NULL,
+    url TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    headers TEXT NOT NULL,
+    body TEXT NOT NULL,
+    etag TEXT,
+    stored_at INTEGER NOT NULL,
+    PRIMARY KEY (scope, url)
+);
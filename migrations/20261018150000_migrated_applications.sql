@@ -0,0 +1,21 @@
+-- Maps applications migrated by fast-migrate to the SSO config created for
+-- them, so that reruns update the existing config instead of duplicating it.
+CREATE TABLE IF NOT EXISTS migrated_applications (
+    tenant_id TEXT NOT NULL,
+    realm_id TEXT NOT NULL,
+    source TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    sso_config_id TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (tenant_id, realm_id, source, source_id)
+);
+
+-- Identities and groups fast-migrate assigned to the SSO configs it manages,
+-- and identities it added to groups, keyed by the SSO config or group. Reruns
+-- only remove assignments and members recorded here, leaving ones made by hand.
+CREATE TABLE IF NOT EXISTS migrated_assignments (
+    parent_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    member_id TEXT NOT NULL,
+    PRIMARY KEY (parent_id, kind, member_id)
+);
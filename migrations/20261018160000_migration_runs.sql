@@ -0,0 +1,28 @@
+-- Each fast-migrate run along with every resource it created, so that a run
+-- can be rolled back without touching resources that existed before it.
+CREATE TABLE IF NOT EXISTS migration_runs (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    realm_id TEXT NOT NULL,
+    source TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    rolled_back_at INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS migration_run_resources (
+    run_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    resource_id TEXT NOT NULL,
+    parent_id TEXT,
+    FOREIGN KEY (run_id) REFERENCES migration_runs(id) ON DELETE CASCADE
+);
+
+-- Source applications offered for migration by each run, including those
+-- left unselected, so that drift only reports applications added to the
+-- source since.
+CREATE TABLE IF NOT EXISTS migration_run_applications (
+    run_id TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    PRIMARY KEY (run_id, source_id),
+    FOREIGN KEY (run_id) REFERENCES migration_runs(id) ON DELETE CASCADE
+);
@@ -0,0 +1,12 @@
+-- Maps source groups mirrored by fast-migrate to the Beyond Identity group
+-- created for them, so that reruns sync the existing group instead of
+-- duplicating it.
+CREATE TABLE IF NOT EXISTS migrated_groups (
+    tenant_id TEXT NOT NULL,
+    realm_id TEXT NOT NULL,
+    source TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    group_id TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (tenant_id, realm_id, source, source_id)
+);
@@ -1,15 +1,14 @@
 use std::env;
 
 use crate::beyond_identity::api::common::api_client::ApiClient;
-use crate::common::command::ambassador_impl_Executable;
+use crate::cli::Cli;
 use crate::common::database::models::AiProvider;
 use crate::common::{command::Executable, error::BiError};
-use crate::Cli;
+use crate::common::{global, http_client, secret};
 
 use async_trait::async_trait;
 use clap::{Args, Subcommand};
 use clap_markdown::MarkdownOptions;
-use reqwest::Client;
 use serde_json::json;
 
 // ====================================
@@ -17,13 +16,23 @@ use serde_json::json;
 // ====================================
 
 /// Commands for interacting with the AI helper tool to assist with CLI operations.
-#[derive(Subcommand, ambassador::Delegate)]
-#[delegate(Executable)]
+#[derive(Subcommand)]
 pub enum AiCommands {
     /// Ask the AI helper tool for assistance in generating CLI commands.
     Ask(Ask),
 }
 
+// Delegated by hand, since ambassador generates an unused helper trait for
+// enums with a single variant
+#[async_trait]
+impl Executable for AiCommands {
+    async fn execute(&self) -> Result<(), BiError> {
+        match self {
+            Self::Ask(ask) => ask.execute().await,
+        }
+    }
+}
+
 // ====================================
 // Ai Ask
 // ====================================
@@ -39,9 +48,9 @@ impl Executable for Ask {
     async fn execute(&self) -> Result<(), BiError> {
         let prompt = prompt(&self.input);
 
-        let api_client = ApiClient::new(None, None).await;
+        let api_client = ApiClient::new(None, None).await?;
 
-        let default_ai_provider = match api_client.db.get_default_ai_provider().await? {
+        let default_ai_provider = match api_client.db()?.get_default_ai_provider().await? {
             Some(x) => x,
             None => {
                 return Err(BiError::StringError(
@@ -50,13 +59,12 @@ impl Executable for Ask {
             }
         };
 
-        Ok(println!(
-            "{}",
-            match default_ai_provider {
-                AiProvider::Openai => openai(&api_client, &prompt).await?,
-                AiProvider::Anthropic => anthropic(&api_client, &prompt).await?,
-            }
-        ))
+        let answer = match default_ai_provider {
+            AiProvider::Openai => openai(&api_client, &prompt).await?,
+            AiProvider::Anthropic => anthropic(&api_client, &prompt).await?,
+        };
+        println!("{}", answer);
+        Ok(())
     }
 }
 
@@ -110,8 +118,8 @@ fn prompt(input: &str) -> String {
 }
 
 async fn openai(api_client: &ApiClient, prompt: &str) -> Result<String, BiError> {
-    let api_key = match api_client.db.get_openai_config().await? {
-        Some(x) => x.api_key,
+    let api_key = match api_client.db()?.get_openai_config().await? {
+        Some(x) => secret::resolve(&x.api_key)?,
         None => {
             return Err(BiError::StringError(
                 "No api_key set for Openai".to_string(),
@@ -119,7 +127,7 @@ async fn openai(api_client: &ApiClient, prompt: &str) -> Result<String, BiError>
         }
     };
 
-    let response = Client::new()
+    let response = http_client::new(&global::options().http_options())?
         .post("https://api.openai.com/v1/chat/completions")
         .header("Content-Type", "application/json")
         .header("Authorization", format!("Bearer {}", api_key))
@@ -149,8 +157,8 @@ async fn openai(api_client: &ApiClient, prompt: &str) -> Result<String, BiError>
 }
 
 pub async fn anthropic(api_client: &ApiClient, prompt: &str) -> Result<String, BiError> {
-    let api_key = match api_client.db.get_anthropic_config().await? {
-        Some(x) => x.api_key,
+    let api_key = match api_client.db()?.get_anthropic_config().await? {
+        Some(x) => secret::resolve(&x.api_key)?,
         None => {
             return Err(BiError::StringError(
                 "No api_key set for Anthropic".to_string(),
@@ -158,7 +166,7 @@ pub async fn anthropic(api_client: &ApiClient, prompt: &str) -> Result<String, B
         }
     };
 
-    let response = Client::new()
+    let response = http_client::new(&global::options().http_options())?
         .post("https://api.anthropic.com/v1/messages")
         .header("Content-Type", "application/json")
         .header("x-api-key", api_key)
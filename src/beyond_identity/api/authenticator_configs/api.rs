@@ -7,6 +7,7 @@ use super::types::{
 use crate::beyond_identity::api::common::service::AuthenticatorConfigsService;
 use crate::common::error::BiError;
 
+use async_trait::async_trait;
 use convert_case::{Case, Casing};
 use http::Method;
 
@@ -14,6 +15,7 @@ use http::Method;
 // Authenticator Configs API
 // ====================================
 
+#[async_trait]
 pub trait AuthenticatorConfigsApi {
     async fn create_authenticator_config(
         &self,
@@ -41,6 +43,7 @@ pub trait AuthenticatorConfigsApi {
 // Authenticator Configs API Implementation
 // ====================================
 
+#[async_trait]
 impl AuthenticatorConfigsApi for AuthenticatorConfigsService {
     async fn create_authenticator_config(
         &self,
@@ -40,8 +40,8 @@ impl Executable for CreateAuthenticatorConfigRequest {
         output(
             AuthenticatorConfigsService::new()
                 .build()
-                .await
-                .create_authenticator_config(&self),
+                .await?
+                .create_authenticator_config(self),
         )
         .await
     }
@@ -64,7 +64,7 @@ impl Executable for List {
         output(
             AuthenticatorConfigsService::new()
                 .build()
-                .await
+                .await?
                 .list_authenticator_configs(self.limit),
         )
         .await
@@ -88,7 +88,7 @@ impl Executable for Get {
         output(
             AuthenticatorConfigsService::new()
                 .build()
-                .await
+                .await?
                 .get_authenticator_config(&self.id),
         )
         .await
@@ -105,8 +105,8 @@ impl Executable for PatchAuthenticatorConfigRequest {
         output(
             AuthenticatorConfigsService::new()
                 .build()
-                .await
-                .patch_authenticator_config(&self),
+                .await?
+                .patch_authenticator_config(self),
         )
         .await
     }
@@ -129,7 +129,7 @@ impl Executable for Delete {
         output(
             AuthenticatorConfigsService::new()
                 .build()
-                .await
+                .await?
                 .delete_authenticator_config(&self.id),
         )
         .await
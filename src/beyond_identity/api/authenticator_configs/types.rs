@@ -1,3 +1,4 @@
+#[cfg(feature = "cli")]
 use clap::{Args, Subcommand, ValueEnum};
 use field_types::FieldName;
 use serde::{Deserialize, Serialize};
@@ -79,10 +80,11 @@ pub struct HostedWebAuthenticatorConfig {
 }
 
 /// Configuration options for the platform authenticator.
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PlatformAuthenticatorConfig {
     /// Trusted origins are URLs that will be allowed to make requests from a browser to the Beyond Identity API.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trusted_origins: Option<Vec<String>>,
 }
@@ -169,21 +171,24 @@ pub struct OptionalPlatformAuthenticatorConfig {
     pub trusted_origins: Option<Vec<String>>,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateAuthenticatorConfigRequest {
-    #[clap(flatten)]
+    #[cfg_attr(feature = "cli", clap(flatten))]
     pub authenticator_config: CreateAuthenticatorConfig,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateAuthenticatorConfig {
     /// Configuration details for the authenticator.
-    #[clap(subcommand)]
+    #[cfg_attr(feature = "cli", clap(subcommand))]
     pub config: CreateAuthenticatorConfigDetails,
 }
 
 /// Enum representing the details of the authenticator configuration.
-#[derive(Subcommand, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Subcommand))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum CreateAuthenticatorConfigDetails {
     /// Embedded SDK authenticator configuration.
@@ -197,62 +202,66 @@ pub enum CreateAuthenticatorConfigDetails {
 }
 
 /// Configuration options for the embedded SDK authenticator.
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateEmbeddedAuthenticatorConfig {
     /// A human-readable name for the authenticator configuration.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: Option<String>,
 
     /// URL to invoke during the authentication flow.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub invoke_url: String,
 
     /// The method used to invoke the `invoke_url` in the embedded authenticator config type.
-    #[clap(long, value_enum)]
+    #[cfg_attr(feature = "cli", clap(long, value_enum))]
     pub invocation_type: InvocationType,
 
     /// Set of authentication methods that are available to the authenticator.
-    #[clap(long, use_value_delimiter = true, num_args(1..), required = true)]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(1..), required = true))]
     pub authentication_methods: Vec<AuthenticationMethod>,
 
     /// Trusted origins are URLs that will be allowed to make requests from a browser to the Beyond Identity API.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trusted_origins: Option<Vec<String>>,
 }
 
 /// Configuration options for the hosted web experience authenticator.
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateHostedWebAuthenticatorConfig {
     /// A human-readable name for the authenticator configuration.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: Option<String>,
 
     /// Set of authentication methods that are available to the authenticator.
-    #[clap(long, use_value_delimiter = true, num_args(1..), required = true)]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(1..), required = true))]
     pub authentication_methods: Vec<AuthenticationMethod>,
 
     /// Trusted origins are URLs that will be allowed to make requests from a browser to the Beyond Identity API.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trusted_origins: Option<Vec<String>>,
 }
 
 /// Configuration options for the platform authenticator.
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreatePlatformAuthenticatorConfig {
     /// A human-readable name for the authenticator configuration.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: Option<String>,
 
     /// Trusted origins are URLs that will be allowed to make requests from a browser to the Beyond Identity API.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trusted_origins: Option<Vec<String>>,
 }
 
 /// Enum representing the possible types of authentication methods.
-#[derive(Clone, Debug, Serialize, Deserialize, ValueEnum)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum AuthenticationMethod {
     WebauthnPasskey,
@@ -261,28 +270,32 @@ pub enum AuthenticationMethod {
 }
 
 /// Enum representing the invocation type.
-#[derive(Clone, Debug, Serialize, Deserialize, ValueEnum)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[serde(rename_all = "lowercase")]
 pub enum InvocationType {
     Automatic,
     Manual,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchAuthenticatorConfigRequest {
-    #[clap(flatten)]
+    #[cfg_attr(feature = "cli", clap(flatten))]
     pub authenticator_config: PatchAuthenticatorConfig,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchAuthenticatorConfig {
     /// Configuration details for the authenticator.
-    #[clap(subcommand)]
+    #[cfg_attr(feature = "cli", clap(subcommand))]
     pub config: PatchAuthenticatorConfigDetails,
 }
 
 /// Enum representing the details of the authenticator configuration.
-#[derive(Subcommand, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Subcommand))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum PatchAuthenticatorConfigDetails {
     /// Embedded SDK authenticator configuration.
@@ -296,75 +309,78 @@ pub enum PatchAuthenticatorConfigDetails {
 }
 
 /// Configuration options for the embedded SDK authenticator.
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchEmbeddedAuthenticatorConfig {
     /// A unique identifier for the authenticator config.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub id: String,
 
     /// A human-readable name for the authenticator configuration.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
 
     /// URL to invoke during the authentication flow.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub invoke_url: Option<String>,
 
     /// The method used to invoke the `invoke_url` in the embedded authenticator config type.
-    #[clap(long, value_enum)]
+    #[cfg_attr(feature = "cli", clap(long, value_enum))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub invocation_type: Option<InvocationType>,
 
     /// Set of authentication methods that are available to the authenticator.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authentication_methods: Option<Vec<AuthenticationMethod>>,
 
     /// Trusted origins are URLs that will be allowed to make requests from a browser to the Beyond Identity API.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trusted_origins: Option<Vec<String>>,
 }
 
 /// Configuration options for the hosted web experience authenticator.
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchHostedWebAuthenticatorConfig {
     /// A unique identifier for the authenticator config.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub id: String,
 
     /// A human-readable name for the authenticator configuration.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
 
     /// Set of authentication methods that are available to the authenticator.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authentication_methods: Option<Vec<AuthenticationMethod>>,
 
     /// Trusted origins are URLs that will be allowed to make requests from a browser to the Beyond Identity API.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trusted_origins: Option<Vec<String>>,
 }
 
 /// Configuration options for the platform authenticator.
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchPlatformAuthenticatorConfig {
     /// A unique identifier for the authenticator config.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub id: String,
 
     /// A human-readable name for the authenticator configuration.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
 
     /// Trusted origins are URLs that will be allowed to make requests from a browser to the Beyond Identity API.
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trusted_origins: Option<Vec<String>>,
 }
@@ -1,72 +1,102 @@
-use std::usize;
-
+use super::credentials::{CredentialStore, Credentials};
 use super::middleware::authorization::AuthorizationMiddleware;
+#[cfg(feature = "cli")]
+use super::middleware::cache::CacheMiddleware;
 use super::middleware::logging::LoggingMiddleware;
 use super::middleware::rate_limit::RespectRateLimitMiddleware;
+use super::middleware::with_transport;
 
 use crate::common::database::models::Realm;
 use crate::common::database::models::Tenant;
-use crate::common::{database::Database, error::BiError};
+#[cfg(feature = "cli")]
+use crate::common::{database::Database, global};
+use crate::common::{
+    error::BiError,
+    http_client::{self, HttpOptions},
+};
 
 use http::Method;
-use reqwest::Client;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use serde::de::{self, Deserializer, MapAccess};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::fmt;
 use url::Url;
 
+#[derive(Clone)]
 pub struct ApiClient {
     pub client: ClientWithMiddleware,
-    pub db: Database,
-    tenant: Option<Tenant>,
-    realm: Option<Realm>,
+    store: CredentialStore,
 }
 
 impl ApiClient {
-    pub async fn new(tenant: Option<Tenant>, realm: Option<Realm>) -> Self {
-        let db = Database::initialize().await.unwrap();
+    // Create a client backed by the local configuration, acting on the given
+    // tenant and realm or the configured defaults.
+    #[cfg(feature = "cli")]
+    pub async fn new(tenant: Option<Tenant>, realm: Option<Realm>) -> Result<Self, BiError> {
+        let db = Database::initialize().await?;
+        Self::with_store(
+            CredentialStore::Database { db, tenant, realm },
+            &global::options().http_options(),
+        )
+    }
+
+    // Create a client for a single realm that keeps its credentials and
+    // tokens in memory instead of the local configuration database.
+    pub async fn from_credentials(credentials: Credentials) -> Result<Self, BiError> {
+        Self::from_credentials_with_options(credentials, &HttpOptions::default()).await
+    }
+
+    // Same as `from_credentials`, sending requests with the given transport
+    // settings.
+    pub async fn from_credentials_with_options(
+        credentials: Credentials,
+        options: &HttpOptions,
+    ) -> Result<Self, BiError> {
+        Self::with_store(CredentialStore::memory(&credentials), options)
+    }
 
-        let http_client = Client::new();
+    fn with_store(store: CredentialStore, options: &HttpOptions) -> Result<Self, BiError> {
+        let http_client = http_client::new(options)?;
 
-        let rate_limit_middleware = ClientBuilder::new(http_client.clone())
-            .with(RespectRateLimitMiddleware)
-            .build();
+        let rate_limit_middleware = with_transport(
+            ClientBuilder::new(http_client.clone()).with(RespectRateLimitMiddleware),
+        )
+        .build();
 
-        let auth_middleware = AuthorizationMiddleware::new(
-            db.clone(),
-            rate_limit_middleware,
-            tenant.clone(),
-            realm.clone(),
-        );
+        let auth_middleware = AuthorizationMiddleware::new(store.clone(), rate_limit_middleware);
 
-        let client = ClientBuilder::new(http_client)
+        let builder = ClientBuilder::new(http_client)
             .with(auth_middleware)
             .with(LoggingMiddleware)
-            .with(RespectRateLimitMiddleware)
-            .build();
+            .with(RespectRateLimitMiddleware);
+        // Only responses from the Beyond Identity API are cached, per credentials
+        #[cfg(feature = "cli")]
+        let builder = builder.with(CacheMiddleware::new(store.clone()));
+        let client = with_transport(builder).build();
 
-        Self {
-            client,
-            db,
-            tenant,
-            realm,
+        Ok(Self { client, store })
+    }
+
+    // The configuration database behind this client. Clients created from
+    // in-memory credentials have none.
+    #[cfg(feature = "cli")]
+    pub fn db(&self) -> Result<&Database, BiError> {
+        match &self.store {
+            CredentialStore::Database { db, .. } => Ok(db),
+            CredentialStore::Memory { .. } => Err(BiError::StringError(
+                "This client is not backed by a local configuration".to_string(),
+            )),
         }
     }
 
+    // The tenant and realm this client sends requests to
+    pub async fn tenant_and_realm(&self) -> Result<(Tenant, Realm), BiError> {
+        self.store.tenant_and_realm().await
+    }
+
     // Initializes the URLBuilder
     pub async fn builder(&self) -> Result<URLBuilder, BiError> {
-        // Get tenant and realm, using defaults if not provided
-        let (tenant, realm) = match (self.tenant.clone(), self.realm.clone()) {
-            (Some(t), Some(r)) => (t.clone(), r.clone()),
-            _ => self
-                .db
-                .get_default_tenant_and_realm()
-                .await?
-                .map(|(t, r)| (t, r))
-                .ok_or_else(|| BiError::StringError("No default tenant/realm set".to_string()))?,
-        };
-
+        let (tenant, realm) = self.tenant_and_realm().await?;
         Ok(URLBuilder::build(tenant, realm))
     }
 
@@ -154,7 +184,7 @@ impl ApiClient {
 
             total_size = response.total_size;
 
-            if response.items.len() > 0 {
+            if !response.items.is_empty() {
                 remaining_limit = remaining_limit.saturating_sub(response.items.len());
                 final_results.extend(response.items);
             }
@@ -314,6 +344,28 @@ impl URLBuilder {
         self
     }
 
+    /// Adds a raw path, which may include a query string, to the URL.
+    pub fn add_raw_path(mut self, path: &str) -> Self {
+        let (path, query) = match path.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (path, None),
+        };
+
+        self.url_mut()
+            .path_segments_mut()
+            .expect("Cannot be base")
+            .extend(path.split('/').filter(|s| !s.is_empty()));
+
+        if let Some(query) = query {
+            let pairs: Vec<(String, String)> = url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect();
+            self.url_mut().query_pairs_mut().extend_pairs(pairs);
+        }
+
+        self
+    }
+
     /// Appends a colon suffix to the last path segment.
     pub fn add_custom_method(mut self, suffix: &str) -> Self {
         // Get the current path segments as a vector of strings.
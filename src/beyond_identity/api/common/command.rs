@@ -3,6 +3,7 @@ use crate::beyond_identity::api::credential_binding_jobs::command::CredentialBin
 use crate::beyond_identity::api::credentials::command::CredentialCommands;
 use crate::beyond_identity::api::groups::command::GroupCommands;
 use crate::beyond_identity::api::identities::command::IdentityCommands;
+use crate::beyond_identity::api::raw::command::Raw;
 use crate::beyond_identity::api::realms::command::RealmCommands;
 use crate::beyond_identity::api::tenants::command::TenantCommands;
 use crate::common::command::ambassador_impl_Executable;
@@ -42,4 +43,7 @@ pub enum BeyondIdentityApiCommands {
     /// Authenticator Configs
     #[clap(subcommand)]
     AuthenticatorConfigs(AuthenticatorConfigCommands),
+
+    /// Send an authenticated request to any API endpoint
+    Raw(Raw),
 }
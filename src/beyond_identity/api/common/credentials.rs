@@ -0,0 +1,147 @@
+use crate::common::database::models::{Realm, Tenant, Token};
+#[cfg(feature = "cli")]
+use crate::common::database::Database;
+use crate::common::error::BiError;
+#[cfg(feature = "cli")]
+use crate::common::secret;
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+// ====================================
+// Credentials
+// ====================================
+
+/// Everything needed to call the API for a single realm without any locally
+/// stored configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub tenant_id: String,
+    pub realm_id: String,
+    pub application_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub api_base_url: String,
+    pub auth_base_url: String,
+}
+
+impl Credentials {
+    pub fn tenant(&self) -> Tenant {
+        Tenant {
+            id: self.tenant_id.clone(),
+        }
+    }
+
+    pub fn realm(&self) -> Realm {
+        let auth_base_url = self.auth_base_url.trim_end_matches('/').to_string();
+        Realm {
+            id: self.realm_id.clone(),
+            tenant_id: self.tenant_id.clone(),
+            application_id: self.application_id.clone(),
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            open_id_configuration_url: format!(
+                "{}/v1/tenants/{}/realms/{}/applications/{}/.well-known/openid-configuration",
+                auth_base_url, self.tenant_id, self.realm_id, self.application_id
+            ),
+            auth_base_url,
+            api_base_url: self.api_base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+// ====================================
+// Credential Store
+// ====================================
+
+// Where a client finds the tenant and realm it acts on and caches the access
+// tokens it fetches for them.
+#[derive(Clone)]
+pub(crate) enum CredentialStore {
+    // The local configuration, falling back to the default tenant and realm
+    #[cfg(feature = "cli")]
+    Database {
+        db: Database,
+        tenant: Option<Tenant>,
+        realm: Option<Realm>,
+    },
+    // A single realm with its token kept in memory
+    Memory {
+        tenant: Tenant,
+        realm: Realm,
+        token: Arc<Mutex<Option<Token>>>,
+    },
+}
+
+impl CredentialStore {
+    pub(crate) fn memory(credentials: &Credentials) -> Self {
+        Self::Memory {
+            tenant: credentials.tenant(),
+            realm: credentials.realm(),
+            token: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub(crate) async fn tenant_and_realm(&self) -> Result<(Tenant, Realm), BiError> {
+        match self {
+            #[cfg(feature = "cli")]
+            Self::Database {
+                tenant: Some(tenant),
+                realm: Some(realm),
+                ..
+            } => Ok((tenant.clone(), realm.clone())),
+            #[cfg(feature = "cli")]
+            Self::Database { db, .. } => db
+                .get_default_tenant_and_realm()
+                .await?
+                .ok_or_else(|| BiError::StringError("No default tenant/realm set".to_string())),
+            Self::Memory { tenant, realm, .. } => Ok((tenant.clone(), realm.clone())),
+        }
+    }
+
+    // The realm's client secret, resolving references to secrets stored
+    // outside of the local configuration
+    pub(crate) fn client_secret(&self, realm: &Realm) -> Result<String, BiError> {
+        match self {
+            #[cfg(feature = "cli")]
+            Self::Database { .. } => secret::resolve(&realm.client_secret),
+            Self::Memory { .. } => Ok(realm.client_secret.clone()),
+        }
+    }
+
+    pub(crate) async fn get_token(&self) -> Result<Option<Token>, BiError> {
+        match self {
+            #[cfg(feature = "cli")]
+            Self::Database { db, .. } => {
+                let (tenant, realm) = self.tenant_and_realm().await?;
+                db.get_token(&tenant.id, &realm.id).await
+            }
+            Self::Memory { token, .. } => Ok(token.lock().unwrap().clone()),
+        }
+    }
+
+    pub(crate) async fn set_token(&self, token: Token) -> Result<(), BiError> {
+        match self {
+            #[cfg(feature = "cli")]
+            Self::Database { db, .. } => db.set_token(token).await,
+            Self::Memory { token: stored, .. } => {
+                *stored.lock().unwrap() = Some(token);
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) async fn delete_token(&self) -> Result<(), BiError> {
+        match self {
+            #[cfg(feature = "cli")]
+            Self::Database { db, .. } => {
+                let (tenant, realm) = self.tenant_and_realm().await?;
+                db.delete_token(&tenant.id, &realm.id).await
+            }
+            Self::Memory { token, .. } => {
+                *token.lock().unwrap() = None;
+                Ok(())
+            }
+        }
+    }
+}
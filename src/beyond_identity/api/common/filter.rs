@@ -5,6 +5,9 @@ use scim_filter::parser::filter;
 pub struct Filter(pub String);
 
 impl Filter {
+    // Query parameter list endpoints take the filter in
+    pub const QUERY_PARAM: &'static str = "filter";
+
     pub fn new(maybe_filter: Option<String>) -> Result<Option<Self>, BiError> {
         let f = match maybe_filter {
             Some(filter) if !filter.trim().is_empty() => filter,
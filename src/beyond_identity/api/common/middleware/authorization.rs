@@ -1,9 +1,11 @@
 use crate::beyond_identity::api::common::api_client::URLBuilder;
+use crate::beyond_identity::api::common::credentials::CredentialStore;
 use crate::common::database::models::Realm;
 use crate::common::database::models::Tenant;
 use crate::common::database::models::Token;
-use crate::common::database::Database;
 use crate::common::error::BiError;
+#[cfg(feature = "cli")]
+use crate::common::{global, redact, stats};
 
 use http::Extensions;
 use http::StatusCode;
@@ -16,25 +18,13 @@ use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct AuthorizationMiddleware {
-    db: Database,
+    store: CredentialStore,
     client: ClientWithMiddleware,
-    tenant: Option<Tenant>,
-    realm: Option<Realm>,
 }
 
 impl AuthorizationMiddleware {
-    pub fn new(
-        db: Database,
-        client: ClientWithMiddleware,
-        tenant: Option<Tenant>,
-        realm: Option<Realm>,
-    ) -> Self {
-        Self {
-            db,
-            client,
-            tenant,
-            realm,
-        }
+    pub(crate) fn new(store: CredentialStore, client: ClientWithMiddleware) -> Self {
+        Self { store, client }
     }
 }
 
@@ -46,7 +36,7 @@ impl Middleware for AuthorizationMiddleware {
         extensions: &mut Extensions,
         next: Next<'_>,
     ) -> MiddlewareResult<Response> {
-        let fetched_token = token(&self.db, &self.client, &self.tenant, &self.realm)
+        let fetched_token = token(&self.store, &self.client)
             .await
             .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
 
@@ -68,19 +58,13 @@ impl Middleware for AuthorizationMiddleware {
             log::debug!("Received 403 Forbidden, attempting to refresh token and retry request.");
 
             // Invalidate the current token
-            if let (Some(tenant), Some(realm)) = (&self.tenant, &self.realm) {
-                self.db
-                    .delete_token(&tenant.id, &realm.id)
-                    .await
-                    .map_err(|e| {
-                        reqwest_middleware::Error::Middleware(
-                            BiError::StringError(e.to_string()).into(),
-                        )
-                    })?;
-            }
+            self.store
+                .delete_token()
+                .await
+                .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
 
             // Fetch a new token
-            let new_token = token(&self.db, &self.client, &self.tenant, &self.realm)
+            let new_token = token(&self.store, &self.client)
                 .await
                 .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
 
@@ -89,6 +73,8 @@ impl Middleware for AuthorizationMiddleware {
                 format!("Bearer {}", new_token).parse().unwrap(),
             );
 
+            #[cfg(feature = "cli")]
+            stats::record_retry();
             response = next.run(req_for_retry, extensions).await?;
 
             if response.status() == StatusCode::FORBIDDEN {
@@ -108,23 +94,14 @@ struct ApiTokenResponse {
     expires_in: u64,
 }
 
-async fn token(
-    db: &Database,
-    client: &Client,
-    tenant: &Option<Tenant>,
-    realm: &Option<Realm>,
-) -> Result<String, BiError> {
-    // Get tenant and realm, using defaults if not provided
-    let (tenant, realm) = match (tenant, realm) {
-        (Some(t), Some(r)) => (t.clone(), r.clone()),
-        _ => db
-            .get_default_tenant_and_realm()
-            .await?
-            .map(|(t, r)| (t, r))
-            .ok_or_else(|| BiError::StringError("No default tenant/realm set".to_string()))?,
-    };
-
-    if let Some(token) = db.get_token(&tenant.id, &realm.id).await? {
+async fn token(store: &CredentialStore, client: &Client) -> Result<String, BiError> {
+    // Recorded tokens are redacted, so there is nothing to fetch when replaying
+    #[cfg(feature = "cli")]
+    if global::options().replay.is_some() {
+        return Ok(redact::REDACTED.to_string());
+    }
+
+    if let Some(token) = store.get_token().await? {
         let current_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -145,6 +122,24 @@ async fn token(
     log::debug!("No valid token found. Fetching a new one.");
 
     // If no valid token, fetch a new one
+    let (tenant, realm) = store.tenant_and_realm().await?;
+    let client_secret = store.client_secret(&realm)?;
+    let token = fetch_token(client, &tenant, &realm, &client_secret).await?;
+    #[cfg(feature = "cli")]
+    stats::record_token_refresh();
+
+    store.set_token(token.clone()).await?;
+
+    Ok(token.access_token)
+}
+
+// Exchange the realm's client credentials for an access token
+pub async fn fetch_token(
+    client: &Client,
+    tenant: &Tenant,
+    realm: &Realm,
+    client_secret: &str,
+) -> Result<Token, BiError> {
     let url = URLBuilder::build(tenant.clone(), realm.clone())
         .auth()
         .add_tenant()
@@ -155,7 +150,7 @@ async fn token(
     let response = client
         .post(&url)
         .header("Content-Type", "application/x-www-form-urlencoded")
-        .basic_auth(realm.client_id, Some(realm.client_secret))
+        .basic_auth(&realm.client_id, Some(client_secret))
         .form(&[("grant_type", "client_credentials")])
         .send()
         .await?;
@@ -189,15 +184,11 @@ async fn token(
         expires_at
     );
 
-    let token = Token {
+    Ok(Token {
         access_token: token_response.access_token,
         expires_at: expires_at as i64,
-        tenant_id: tenant.id,
-        realm_id: realm.id,
-        application_id: realm.application_id,
-    };
-
-    db.set_token(token.clone()).await?;
-
-    Ok(token.access_token)
+        tenant_id: tenant.id.clone(),
+        realm_id: realm.id.clone(),
+        application_id: realm.application_id.clone(),
+    })
 }
@@ -0,0 +1,287 @@
+use crate::beyond_identity::api::common::credentials::CredentialStore;
+use crate::common::database::models::CachedResponse;
+use crate::common::database::Database;
+use crate::common::error::BiError;
+use crate::common::global::{self, CacheMode};
+use crate::common::stats;
+
+use http::{header, Extensions, Method, StatusCode};
+use log::debug;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Error, Middleware, Next, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+
+// Caches Beyond Identity API GET responses when `--cache` is given. Responses
+// are cached per tenant, realm and client so they are never served to other
+// credentials. Fresh responses are served without a request, stale ones are
+// revalidated with `If-None-Match`, and any successful mutating request drops
+// the cached responses for the resource it modifies, listings of its
+// collection, and listings of related resources in the same realm.
+pub struct CacheMiddleware {
+    store: CredentialStore,
+}
+
+impl CacheMiddleware {
+    pub(crate) fn new(store: CredentialStore) -> Self {
+        Self { store }
+    }
+
+    async fn scope(&self) -> std::result::Result<String, BiError> {
+        let (tenant, realm) = self.store.tenant_and_realm().await?;
+        Ok(format!("{}/{}/{}", tenant.id, realm.id, realm.client_id))
+    }
+}
+
+type MemoryCache = HashMap<(String, String), CachedResponse>;
+
+static MEMORY: OnceLock<Mutex<MemoryCache>> = OnceLock::new();
+static DATABASE: OnceCell<Database> = OnceCell::const_new();
+
+#[async_trait::async_trait]
+impl Middleware for CacheMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let mode = match global::options().cache {
+            Some(mode) => mode,
+            None => return next.run(req, extensions).await,
+        };
+        let url = req.url().to_string();
+
+        if req.method() != Method::GET {
+            let is_mutation = req.method() != Method::HEAD && req.method() != Method::OPTIONS;
+            let result = next.run(req, extensions).await;
+            let succeeded = matches!(&result, Ok(response) if response.status().is_success());
+            if is_mutation && succeeded {
+                invalidate(mode, &url).await.map_err(middleware_error)?;
+            }
+            return result;
+        }
+
+        let scope = self.scope().await.map_err(middleware_error)?;
+        let cached = get(mode, &scope, &url).await.map_err(middleware_error)?;
+        if let Some(cached) = &cached {
+            if now() - cached.stored_at < global::options().cache_ttl as i64 {
+                debug!("Serving cached response for GET {}", url);
+                stats::record_cache_hit();
+                return to_response(cached);
+            }
+            if let Some(etag) = &cached.etag {
+                if let Ok(value) = etag.parse() {
+                    req.headers_mut().insert(header::IF_NONE_MATCH, value);
+                }
+            }
+        }
+
+        let response = next.run(req, extensions).await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(mut cached) = cached {
+                debug!("Cached response for GET {} is still valid", url);
+                stats::record_cache_hit();
+                cached.stored_at = now();
+                set(mode, &cached).await.map_err(middleware_error)?;
+                return to_response(&cached);
+            }
+        }
+
+        if response.status() != StatusCode::OK || is_no_store(&response) {
+            return Ok(response);
+        }
+
+        let status = response.status();
+        let headers: BTreeMap<String, String> = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let (response, body) = super::buffer(response).await?;
+
+        let cached = CachedResponse {
+            scope,
+            url,
+            status: status.as_u16() as i64,
+            etag: headers.get(header::ETAG.as_str()).cloned(),
+            headers: serde_json::to_string(&headers).map_err(|e| Error::Middleware(e.into()))?,
+            body: String::from_utf8_lossy(&body).into_owned(),
+            stored_at: now(),
+        };
+        set(mode, &cached).await.map_err(middleware_error)?;
+
+        Ok(response)
+    }
+}
+
+async fn database() -> std::result::Result<&'static Database, BiError> {
+    DATABASE.get_or_try_init(Database::initialize).await
+}
+
+fn memory() -> std::sync::MutexGuard<'static, MemoryCache> {
+    MEMORY
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+async fn get(
+    mode: CacheMode,
+    scope: &str,
+    url: &str,
+) -> std::result::Result<Option<CachedResponse>, BiError> {
+    match mode {
+        CacheMode::Memory => Ok(memory().get(&(scope.to_string(), url.to_string())).cloned()),
+        CacheMode::Persistent => database().await?.get_cached_response(scope, url).await,
+    }
+}
+
+async fn set(mode: CacheMode, cached: &CachedResponse) -> std::result::Result<(), BiError> {
+    match mode {
+        CacheMode::Memory => {
+            memory().insert((cached.scope.clone(), cached.url.clone()), cached.clone());
+            Ok(())
+        }
+        CacheMode::Persistent => database().await?.set_cached_response(cached).await,
+    }
+}
+
+// Drop cached responses for the resource at `url`, anything beneath it, and
+// listings of its parent collection. A custom method such as
+// `/groups/{id}:addMembers` modifies `/groups/{id}`. Mutations can also change
+// listings reached through other resources, such as the groups listed by
+// `/identities/{id}:listGroups`, so custom listing methods anywhere in the same
+// realm are dropped too. Responses are dropped for every credential scope.
+async fn invalidate(mode: CacheMode, url: &str) -> std::result::Result<(), BiError> {
+    let (prefix, is_affected) = affected_by(url);
+
+    match mode {
+        CacheMode::Memory => memory().retain(|(_, cached_url), _| !is_affected(cached_url)),
+        CacheMode::Persistent => {
+            let db = database().await?;
+            for cached_url in db.get_cached_response_urls(&prefix).await? {
+                if is_affected(&cached_url) {
+                    db.delete_cached_response(&cached_url).await?;
+                }
+            }
+        }
+    }
+    debug!("Invalidated cached responses affected by {}", url);
+
+    Ok(())
+}
+
+// The prefix shared by every cached URL a mutation of `url` can affect, and a
+// predicate telling whether a cached URL is affected.
+fn affected_by(url: &str) -> (String, impl Fn(&str) -> bool) {
+    let base = url.split(['?', '#']).next().unwrap_or(url);
+    let resource = match base.rsplit_once('/') {
+        Some((parent, last)) => match last.split_once(':') {
+            Some((id, _)) => format!("{}/{}", parent, id),
+            None => base.to_string(),
+        },
+        None => base.to_string(),
+    };
+    let collection = resource
+        .rsplit_once('/')
+        .map(|(parent, _)| parent.to_string())
+        .unwrap_or_else(|| resource.clone());
+    let realm = realm_prefix(&resource).unwrap_or_else(|| collection.clone());
+    let prefix = realm.clone();
+
+    let is_affected = move |cached_url: &str| {
+        let cached_base = cached_url.split('?').next().unwrap_or(cached_url);
+        let is_related_listing = cached_base.starts_with(realm.as_str())
+            && cached_base
+                .rsplit('/')
+                .next()
+                .and_then(|last| last.split_once(':'))
+                .is_some_and(|(_, method)| method.starts_with("list"));
+        cached_base == collection
+            || cached_base == resource
+            || cached_base
+                .strip_prefix(resource.as_str())
+                .is_some_and(|rest| rest.starts_with('/') || rest.starts_with(':'))
+            || is_related_listing
+    };
+    (prefix, is_affected)
+}
+
+// The URL of the realm a resource belongs to, such as
+// `https://api.byndid.com/v1/tenants/{id}/realms/{id}`
+fn realm_prefix(resource: &str) -> Option<String> {
+    let (head, rest) = resource.split_once("/realms/")?;
+    let id = rest.split('/').next()?;
+    Some(format!("{}/realms/{}", head, id))
+}
+
+fn is_no_store(response: &Response) -> bool {
+    response
+        .headers()
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.to_ascii_lowercase().contains("no-store"))
+}
+
+fn to_response(cached: &CachedResponse) -> Result<Response> {
+    let headers: BTreeMap<String, String> =
+        serde_json::from_str(&cached.headers).map_err(|e| Error::Middleware(e.into()))?;
+
+    let mut builder = http::Response::builder().status(cached.status as u16);
+    for (name, value) in &headers {
+        // The body is stored decoded, so drop headers describing the encoding on the wire
+        if name.eq_ignore_ascii_case("content-length")
+            || name.eq_ignore_ascii_case("transfer-encoding")
+            || name.eq_ignore_ascii_case("content-encoding")
+        {
+            continue;
+        }
+        builder = builder.header(name, value);
+    }
+    Ok(Response::from(
+        builder
+            .body(cached.body.clone().into_bytes())
+            .map_err(|e| Error::Middleware(e.into()))?,
+    ))
+}
+
+fn middleware_error(e: BiError) -> Error {
+    Error::Middleware(e.into())
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REALM: &str = "https://api.example.com/v1/tenants/t1/realms/r1";
+
+    #[test]
+    fn mutations_drop_the_resource_and_related_listings() {
+        let (prefix, is_affected) = affected_by(&format!("{}/groups/g1:addMembers", REALM));
+        assert_eq!(prefix, REALM);
+
+        assert!(is_affected(&format!("{}/groups", REALM)));
+        assert!(is_affected(&format!("{}/groups?page_size=10", REALM)));
+        assert!(is_affected(&format!("{}/groups/g1", REALM)));
+        assert!(is_affected(&format!("{}/groups/g1:listMembers", REALM)));
+        assert!(is_affected(&format!("{}/identities/i1:listGroups", REALM)));
+
+        assert!(!is_affected(&format!("{}/groups/g2", REALM)));
+        assert!(!is_affected(&format!("{}/identities/i1", REALM)));
+        assert!(!is_affected(
+            "https://api.example.com/v1/tenants/t1/realms/r2/identities/i1:listGroups"
+        ));
+    }
+}
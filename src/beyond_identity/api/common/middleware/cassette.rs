@@ -0,0 +1,243 @@
+use crate::common::global;
+use crate::common::redact;
+
+use http::Extensions;
+use log::debug;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Error, Middleware, Next, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+// Records every request and response to the cassette given by `--record`, or
+// serves responses from the cassette given by `--replay` instead of the
+// network. Secrets are redacted before anything is written. This must be the
+// last middleware added to a client so it sees requests exactly as they would
+// be sent.
+pub struct CassetteMiddleware;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+struct Recorder {
+    path: PathBuf,
+    cassette: Cassette,
+}
+
+struct Player {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+// Cassettes are shared by every client in the process so that all traffic
+// ends up in a single file.
+static RECORDER: OnceLock<Mutex<Recorder>> = OnceLock::new();
+static PLAYER: OnceLock<std::result::Result<Mutex<Player>, String>> = OnceLock::new();
+
+#[async_trait::async_trait]
+impl Middleware for CassetteMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let options = global::options();
+
+        if let Some(path) = &options.replay {
+            return replay(path, &record_request(&req));
+        }
+
+        let path = match &options.record {
+            Some(path) => path,
+            None => return next.run(req, extensions).await,
+        };
+
+        let request = record_request(&req);
+        let response = next.run(req, extensions).await?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let (response, body) = super::buffer(response).await?;
+
+        let interaction = Interaction {
+            request,
+            response: RecordedResponse {
+                status: status.as_u16(),
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| {
+                        (
+                            name.to_string(),
+                            redact::header(name.as_str(), value.to_str().unwrap_or_default()),
+                        )
+                    })
+                    .collect(),
+                body: redact::body(&String::from_utf8_lossy(&body)),
+            },
+        };
+        record(path, interaction).map_err(|e| Error::Middleware(anyhow::anyhow!(e)))?;
+
+        Ok(response)
+    }
+}
+
+fn record_request(req: &Request) -> RecordedRequest {
+    RecordedRequest {
+        method: req.method().to_string(),
+        url: redact::url(req.url()),
+        headers: req
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    redact::header(name.as_str(), value.to_str().unwrap_or_default()),
+                )
+            })
+            .collect(),
+        body: req
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| redact::body(&String::from_utf8_lossy(bytes)))
+            .unwrap_or_default(),
+    }
+}
+
+fn record(path: &Path, interaction: Interaction) -> std::result::Result<(), String> {
+    let recorder = RECORDER.get_or_init(|| {
+        Mutex::new(Recorder {
+            path: path.to_path_buf(),
+            cassette: Cassette::default(),
+        })
+    });
+    let mut recorder = recorder.lock().map_err(|e| e.to_string())?;
+    recorder.cassette.interactions.push(interaction);
+
+    // Rewrite the whole cassette so it is complete even if the command fails
+    let contents = serde_json::to_string_pretty(&recorder.cassette).map_err(|e| e.to_string())?;
+    std::fs::write(&recorder.path, contents).map_err(|e| {
+        format!(
+            "Failed to write cassette {}: {}",
+            recorder.path.display(),
+            e
+        )
+    })
+}
+
+// Serve the first unused interaction matching the request's method, URL and
+// body, falling back to one matching only method and URL. Once every match
+// has been used the last one is served again.
+fn replay(path: &Path, request: &RecordedRequest) -> Result<Response> {
+    let player = PLAYER
+        .get_or_init(|| load(path).map(Mutex::new))
+        .as_ref()
+        .map_err(|e| Error::Middleware(anyhow::anyhow!(e.clone())))?;
+    let mut player = player
+        .lock()
+        .map_err(|e| Error::Middleware(anyhow::anyhow!(e.to_string())))?;
+
+    let same_endpoint =
+        |i: &Interaction| i.request.method == request.method && i.request.url == request.url;
+    let candidates: Vec<usize> = (0..player.interactions.len())
+        .filter(|&i| same_endpoint(&player.interactions[i]))
+        .collect();
+
+    let index = candidates
+        .iter()
+        .copied()
+        .find(|&i| !player.used[i] && player.interactions[i].request.body == request.body)
+        .or_else(|| candidates.iter().copied().find(|&i| !player.used[i]))
+        .or_else(|| candidates.last().copied())
+        .ok_or_else(|| {
+            Error::Middleware(anyhow::anyhow!(
+                "No recorded response for {} {} in cassette {}",
+                request.method,
+                request.url,
+                path.display()
+            ))
+        })?;
+    player.used[index] = true;
+
+    let recorded = &player.interactions[index].response;
+    debug!(
+        "Replaying recorded response for {} {}: {}",
+        request.method, request.url, recorded.status
+    );
+
+    let mut builder = http::Response::builder().status(recorded.status);
+    for (name, value) in &recorded.headers {
+        // The body is stored decoded, so drop headers describing the encoding on the wire
+        if name.eq_ignore_ascii_case("content-length")
+            || name.eq_ignore_ascii_case("transfer-encoding")
+            || name.eq_ignore_ascii_case("content-encoding")
+        {
+            continue;
+        }
+        builder = builder.header(name, value);
+    }
+    Ok(Response::from(
+        builder
+            .body(recorded.body.clone().into_bytes())
+            .map_err(|e| Error::Middleware(e.into()))?,
+    ))
+}
+
+fn load(path: &Path) -> std::result::Result<Player, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read cassette {}: {}", path.display(), e))?;
+    let cassette: Cassette = serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid cassette {}: {}", path.display(), e))?;
+    Ok(Player {
+        used: vec![false; cassette.interactions.len()],
+        interactions: cassette.interactions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_requests_are_redacted() {
+        let req = reqwest::Client::new()
+            .post("https://auth.example.com/token?api_key=hunter2&x=1")
+            .header("Authorization", "Basic c2VjcmV0")
+            .header("Accept", "application/json")
+            .body("grant_type=client_credentials&client_secret=hunter2")
+            .build()
+            .unwrap();
+
+        let recorded = record_request(&req);
+        assert_eq!(recorded.method, "POST");
+        assert!(!recorded.url.contains("hunter2"));
+        assert_eq!(recorded.headers["authorization"], redact::REDACTED);
+        assert_eq!(recorded.headers["accept"], "application/json");
+        assert!(!recorded.body.contains("hunter2"));
+        assert!(recorded.body.contains("grant_type=client_credentials"));
+    }
+}
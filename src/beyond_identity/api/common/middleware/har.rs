@@ -0,0 +1,322 @@
+use crate::common::global;
+use crate::common::redact;
+
+use chrono::{DateTime, Utc};
+use http::{Extensions, HeaderMap, Version};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Error, Middleware, Next, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+// Writes every request and response, with timings, to the HAR file given by
+// `--trace-http`. Secrets are redacted before anything is written.
+pub struct HarMiddleware;
+
+#[derive(Debug, Serialize)]
+struct Har {
+    log: Log,
+}
+
+#[derive(Debug, Serialize)]
+struct Log {
+    version: String,
+    creator: Creator,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize)]
+struct Creator {
+    name: String,
+    version: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    started_date_time: DateTime<Utc>,
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: Cache,
+    timings: Timings,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<NameValue>,
+    headers: Vec<NameValue>,
+    query_string: Vec<NameValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<PostData>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<NameValue>,
+    headers: Vec<NameValue>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PostData {
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: i64,
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Cache {}
+
+#[derive(Debug, Serialize)]
+struct Timings {
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+struct Tracer {
+    path: PathBuf,
+    har: Har,
+}
+
+// Shared by every client in the process so that all traffic ends up in a
+// single file.
+static TRACER: OnceLock<Mutex<Tracer>> = OnceLock::new();
+
+#[async_trait::async_trait]
+impl Middleware for HarMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let path = match &global::options().trace_http {
+            Some(path) => path,
+            None => return next.run(req, extensions).await,
+        };
+
+        let started_date_time = Utc::now();
+        let start = Instant::now();
+        let request = har_request(&req);
+
+        let result = next.run(req, extensions).await;
+        let wait = start.elapsed();
+
+        let (result, response, receive) = match result {
+            Ok(response) => {
+                let status = response.status();
+                let version = response.version();
+                let headers = response.headers().clone();
+                let (response, body) = super::buffer(response).await?;
+                let receive = start.elapsed() - wait;
+                let har_response = har_response(status, version, &headers, &body);
+                (Ok(response), har_response, receive)
+            }
+            Err(err) => {
+                let har_response = HarResponse {
+                    status: 0,
+                    status_text: err.to_string(),
+                    http_version: String::new(),
+                    cookies: Vec::new(),
+                    headers: Vec::new(),
+                    content: Content {
+                        size: 0,
+                        mime_type: String::new(),
+                        text: String::new(),
+                    },
+                    redirect_url: String::new(),
+                    headers_size: -1,
+                    body_size: -1,
+                };
+                (Err(err), har_response, Duration::ZERO)
+            }
+        };
+
+        let entry = Entry {
+            started_date_time,
+            time: millis(wait + receive),
+            request,
+            response,
+            cache: Cache {},
+            timings: Timings {
+                send: 0.0,
+                wait: millis(wait),
+                receive: millis(receive),
+            },
+        };
+        trace(path, entry).map_err(|e| Error::Middleware(anyhow::anyhow!(e)))?;
+
+        result
+    }
+}
+
+fn har_request(req: &Request) -> HarRequest {
+    let body = req.body().and_then(|body| body.as_bytes());
+    let url = redact::url(req.url());
+    let query_string = url::Url::parse(&url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| NameValue {
+                    name: name.into_owned(),
+                    value: value.into_owned(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    HarRequest {
+        method: req.method().to_string(),
+        url,
+        http_version: version(req.version()),
+        cookies: Vec::new(),
+        headers: headers(req.headers()),
+        query_string,
+        post_data: body.map(|bytes| PostData {
+            mime_type: content_type(req.headers()),
+            text: redact::body(&String::from_utf8_lossy(bytes)),
+        }),
+        headers_size: -1,
+        body_size: body.map(|bytes| bytes.len() as i64).unwrap_or(0),
+    }
+}
+
+fn har_response(
+    status: http::StatusCode,
+    http_version: Version,
+    response_headers: &HeaderMap,
+    body: &[u8],
+) -> HarResponse {
+    let redirect_url = response_headers
+        .get(http::header::LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    HarResponse {
+        status: status.as_u16(),
+        status_text: status.canonical_reason().unwrap_or_default().to_string(),
+        http_version: version(http_version),
+        cookies: Vec::new(),
+        headers: headers(response_headers),
+        content: Content {
+            size: body.len() as i64,
+            mime_type: content_type(response_headers),
+            text: redact::body(&String::from_utf8_lossy(body)),
+        },
+        redirect_url,
+        headers_size: -1,
+        body_size: body.len() as i64,
+    }
+}
+
+fn headers(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.to_string(),
+            value: redact::header(name.as_str(), value.to_str().unwrap_or_default()),
+        })
+        .collect()
+}
+
+fn content_type(headers: &HeaderMap) -> String {
+    headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn version(version: Version) -> String {
+    format!("{:?}", version)
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn trace(path: &Path, entry: Entry) -> std::result::Result<(), String> {
+    let tracer = TRACER.get_or_init(|| {
+        Mutex::new(Tracer {
+            path: path.to_path_buf(),
+            har: Har {
+                log: Log {
+                    version: "1.2".to_string(),
+                    creator: Creator {
+                        name: env!("CARGO_PKG_NAME").to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                    },
+                    entries: Vec::new(),
+                },
+            },
+        })
+    });
+    let mut tracer = tracer.lock().map_err(|e| e.to_string())?;
+    tracer.har.log.entries.push(entry);
+
+    // Rewrite the whole archive so it is complete even if the command fails
+    let contents = serde_json::to_string_pretty(&tracer.har).map_err(|e| e.to_string())?;
+    std::fs::write(&tracer.path, contents)
+        .map_err(|e| format!("Failed to write HAR file {}: {}", tracer.path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traced_requests_are_redacted() {
+        let req = reqwest::Client::new()
+            .post("https://api.example.com/v1/token?access_token=hunter2&page_size=5")
+            .header("Authorization", "Bearer hunter2")
+            .header("Content-Type", "application/json")
+            .body(r#"{"client_id":"id","client_secret":"hunter2"}"#)
+            .build()
+            .unwrap();
+
+        let har = har_request(&req);
+        assert!(!serde_json::to_string(&har).unwrap().contains("hunter2"));
+        assert!(har
+            .headers
+            .iter()
+            .any(|header| header.name == "authorization" && header.value == redact::REDACTED));
+        assert!(har
+            .query_string
+            .iter()
+            .any(|pair| pair.name == "page_size" && pair.value == "5"));
+        let post_data = har.post_data.unwrap();
+        assert_eq!(post_data.mime_type, "application/json");
+        assert!(post_data.text.contains(r#""client_id":"id""#));
+    }
+}
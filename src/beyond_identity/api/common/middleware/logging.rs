@@ -1,55 +1,67 @@
+use crate::common::redact;
+
 use http::Extensions;
-use log::{debug, error};
-use reqwest::{Body, Request, Response};
+use log::{debug, error, log_enabled, Level};
+use reqwest::{Request, Response};
 use reqwest_middleware::{Middleware, Next, Result};
+use std::time::Instant;
 
+// Logs every request and response at debug level. Credentials and any fields
+// given by `--redact-field` are redacted.
 pub struct LoggingMiddleware;
 
 #[async_trait::async_trait]
 impl Middleware for LoggingMiddleware {
     async fn handle(
         &self,
-        mut req: Request,
+        req: Request,
         extensions: &mut Extensions,
         next: Next<'_>,
     ) -> Result<Response> {
-        // Extract the body as bytes if present
-        let body_bytes = if let Some(body) = req.body_mut().take() {
-            match body.as_bytes() {
-                Some(bytes) => bytes.to_vec(),
-                None => {
-                    error!("Failed to read request body");
-                    Vec::new() // Default to an empty body in case of error
-                }
-            }
-        } else {
-            Vec::new() // Handle case where body is None
-        };
+        let method = req.method().clone();
+        let url = redact::url(req.url());
 
         // Log the HTTP method, URL, and body
         debug!(
-            "Sending request: method = {:?}, url = {}, body = {:?}",
-            req.method(),
-            req.url(),
-            String::from_utf8_lossy(&body_bytes),
+            "Sending request: method = {}, url = {}, body = {:?}",
+            method,
+            url,
+            req.body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| redact::body(&String::from_utf8_lossy(bytes)))
+                .unwrap_or_default(),
         );
 
-        // Reconstruct the body and set it back to the request
-        *req.body_mut() = Some(Body::from(body_bytes));
-
         // Proceed with the request
+        let start = Instant::now();
         let result = next.run(req, extensions).await;
 
-        // Log the response status
-        match &result {
-            Ok(response) => {
-                debug!("Received response: status = {}", response.status());
+        // Log the response status, timing, and body
+        match result {
+            Ok(response) if log_enabled!(Level::Debug) => {
+                let status = response.status();
+                let (response, body) = super::buffer(response).await?;
+                debug!(
+                    "Received response: method = {}, url = {}, status = {}, time = {}ms, body = {:?}",
+                    method,
+                    url,
+                    status,
+                    start.elapsed().as_millis(),
+                    redact::body(&String::from_utf8_lossy(&body)),
+                );
+                Ok(response)
             }
+            Ok(response) => Ok(response),
             Err(err) => {
-                error!("Request error: {:?}", err);
+                error!(
+                    "Request error: method = {}, url = {}, time = {}ms, error = {:?}",
+                    method,
+                    url,
+                    start.elapsed().as_millis(),
+                    err
+                );
+                Err(err)
             }
         }
-
-        result
     }
 }
@@ -1,3 +1,52 @@
 pub mod authorization;
+#[cfg(feature = "cli")]
+pub mod cache;
+#[cfg(feature = "cli")]
+pub mod cassette;
+#[cfg(feature = "cli")]
+pub mod har;
 pub mod logging;
 pub mod rate_limit;
+#[cfg(feature = "cli")]
+pub mod stats;
+
+#[cfg(feature = "cli")]
+use cassette::CassetteMiddleware;
+#[cfg(feature = "cli")]
+use har::HarMiddleware;
+#[cfg(feature = "cli")]
+use stats::StatsMiddleware;
+
+use reqwest::Response;
+use reqwest_middleware::{ClientBuilder, Error, Result};
+
+// Add the middleware that sits between a client and the network. These must be
+// added after every other middleware so they see requests exactly as sent.
+// Tracing, stats and cassettes are only offered by the CLI. Caching is not
+// added here as it only applies to Beyond Identity API clients.
+pub fn with_transport(builder: ClientBuilder) -> ClientBuilder {
+    #[cfg(feature = "cli")]
+    let builder = builder
+        .with(HarMiddleware)
+        .with(StatsMiddleware)
+        .with(CassetteMiddleware);
+    builder
+}
+
+// Read the whole body of a response, returning it along with an equivalent
+// response that can still be handed back to the caller.
+pub(crate) async fn buffer(response: Response) -> Result<(Response, Vec<u8>)> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?.to_vec();
+
+    let mut builder = http::Response::builder().status(status).version(version);
+    for (name, value) in headers.iter() {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(body.clone())
+        .map_err(|e| Error::Middleware(e.into()))?;
+    Ok((Response::from(response), body))
+}
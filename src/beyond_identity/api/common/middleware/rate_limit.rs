@@ -1,13 +1,19 @@
+use super::with_transport;
+
+use crate::common::error::BiError;
+use crate::common::http_client::{self, HttpOptions};
+#[cfg(feature = "cli")]
+use crate::common::stats;
+
 use log::debug;
-use reqwest::Client;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Error, Middleware, Next};
 
 pub struct RespectRateLimitMiddleware;
 
 impl RespectRateLimitMiddleware {
-    pub fn new_client() -> ClientWithMiddleware {
-        let client = Client::new();
-        ClientBuilder::new(client).with(Self).build()
+    pub fn new_client(options: &HttpOptions) -> Result<ClientWithMiddleware, BiError> {
+        let client = http_client::new(options)?;
+        Ok(with_transport(ClientBuilder::new(client).with(Self)).build())
     }
 }
 
@@ -44,20 +50,28 @@ impl Middleware for RespectRateLimitMiddleware {
                         "Received RETRY_AFTER header. Retrying after {} seconds",
                         delay_secs
                     );
-                    tokio::time::sleep(std::time::Duration::from_secs(delay_secs)).await;
+                    let delay = std::time::Duration::from_secs(delay_secs);
+                    tokio::time::sleep(delay).await;
+                    #[cfg(feature = "cli")]
+                    stats::record_rate_limit_wait(delay);
                 } else if retries < max_retries {
                     let backoff_delay = 2u64.pow(retries).min(60);
                     debug!(
                         "Did not receive RETRY_AFTER header. Retrying after {} seconds",
                         backoff_delay
                     );
-                    tokio::time::sleep(std::time::Duration::from_secs(backoff_delay)).await;
+                    let delay = std::time::Duration::from_secs(backoff_delay);
+                    tokio::time::sleep(delay).await;
+                    #[cfg(feature = "cli")]
+                    stats::record_rate_limit_wait(delay);
                     retries += 1;
                 } else {
                     return Err(Error::Middleware(anyhow::anyhow!(
                         "Max retries reached without a RETRY_AFTER header."
                     )));
                 }
+                #[cfg(feature = "cli")]
+                stats::record_retry();
             } else {
                 return Ok(response);
             }
@@ -0,0 +1,32 @@
+use crate::common::stats;
+
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+use std::time::Instant;
+
+// Records the latency and status of every request for `--stats`.
+pub struct StatsMiddleware;
+
+#[async_trait::async_trait]
+impl Middleware for StatsMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let method = req.method().to_string();
+        let url = req.url().clone();
+
+        let start = Instant::now();
+        let result = next.run(req, extensions).await;
+        let status = result
+            .as_ref()
+            .ok()
+            .map(|response| response.status().as_u16());
+        stats::record_request(&method, &url, status, start.elapsed());
+
+        result
+    }
+}
@@ -1,5 +1,7 @@
 pub mod api_client;
+#[cfg(feature = "cli")]
 pub mod command;
+pub mod credentials;
 pub mod filter;
 pub mod middleware;
 pub mod serialize;
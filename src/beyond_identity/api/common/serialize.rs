@@ -25,7 +25,7 @@ where
                         serde_json::to_string_pretty(&parsed_json).map_err(BiError::from)?
                     )
                 }
-                Err(_) => println!("{}", format!("Error (HTTP {}): {}", status, body)),
+                Err(_) => println!("Error (HTTP {}): {}", status, body),
             }
             Ok(())
         }
@@ -1,7 +1,9 @@
 use super::api_client::ApiClient;
 
-use crate::common::database::models::Realm;
-use crate::common::database::models::Tenant;
+#[cfg(feature = "cli")]
+use crate::common::database::models::{Realm, Tenant};
+#[cfg(feature = "cli")]
+use crate::common::error::BiError;
 
 use paste::paste;
 
@@ -12,12 +14,17 @@ macro_rules! create_service_with_builder {
                 pub api_client: ApiClient,
             }
 
+            #[cfg(feature = "cli")]
             pub struct [<$service_name Builder>] {
                 tenant: Option<Tenant>,
                 realm: Option<Realm>,
             }
 
+            #[cfg(feature = "cli")]
             impl $service_name {
+                // Services are built for a tenant and realm, which default to
+                // the configured ones
+                #[allow(clippy::new_ret_no_self)]
                 pub fn new() -> [<$service_name Builder>] {
                     [<$service_name Builder>] {
                         tenant: None,
@@ -26,6 +33,13 @@ macro_rules! create_service_with_builder {
                 }
             }
 
+            impl From<ApiClient> for $service_name {
+                fn from(api_client: ApiClient) -> Self {
+                    Self { api_client }
+                }
+            }
+
+            #[cfg(feature = "cli")]
             impl [<$service_name Builder>] {
                 #[allow(dead_code)]
                 pub fn tenant(mut self, tenant: Tenant) -> [<$service_name Builder>] {
@@ -39,10 +53,10 @@ macro_rules! create_service_with_builder {
                     self
                 }
 
-                pub async fn build(self) -> $service_name {
-                    $service_name {
-                        api_client: ApiClient::new(self.tenant, self.realm).await,
-                    }
+                pub async fn build(self) -> Result<$service_name, BiError> {
+                    Ok($service_name {
+                        api_client: ApiClient::new(self.tenant, self.realm).await?,
+                    })
                 }
             }
         }
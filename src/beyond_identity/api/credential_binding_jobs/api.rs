@@ -7,6 +7,7 @@ use crate::beyond_identity::api::common::service::CredentialBindingJobsService;
 use crate::beyond_identity::api::identities::types::IdentitiesFieldName;
 use crate::common::error::BiError;
 
+use async_trait::async_trait;
 use convert_case::{Case, Casing};
 use http::Method;
 
@@ -14,6 +15,7 @@ use http::Method;
 // Credential Binding Jobs API
 // ====================================
 
+#[async_trait]
 pub trait CredentialBindingJobsApi {
     async fn create_credential_binding_job(
         &self,
@@ -36,6 +38,7 @@ pub trait CredentialBindingJobsApi {
 // Credential Binding Jobs API Implementation
 // ====================================
 
+#[async_trait]
 impl CredentialBindingJobsApi for CredentialBindingJobsService {
     async fn create_credential_binding_job(
         &self,
@@ -45,7 +45,7 @@ impl Executable for Create {
         output(
             CredentialBindingJobsService::new()
                 .build()
-                .await
+                .await?
                 .create_credential_binding_job(&self.identity_id, &self.request),
         )
         .await
@@ -74,7 +74,7 @@ impl Executable for List {
         output(
             CredentialBindingJobsService::new()
                 .build()
-                .await
+                .await?
                 .list_credential_binding_jobs(&self.identity_id, self.limit),
         )
         .await
@@ -102,7 +102,7 @@ impl Executable for Get {
         output(
             CredentialBindingJobsService::new()
                 .build()
-                .await
+                .await?
                 .get_credential_binding_job(&self.id, &self.identity_id),
         )
         .await
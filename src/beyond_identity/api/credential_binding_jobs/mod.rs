@@ -1,3 +1,4 @@
 pub mod api;
+#[cfg(feature = "cli")]
 pub mod command;
 pub mod types;
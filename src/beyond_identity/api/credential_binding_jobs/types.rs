@@ -1,3 +1,4 @@
+#[cfg(feature = "cli")]
 use clap::{ArgGroup, Args, ValueEnum};
 use field_types::FieldName;
 use serde::{Deserialize, Serialize};
@@ -21,7 +22,8 @@ pub struct CredentialBindingJobEnvelope {
 }
 
 /// The method by which a credential binding link is delivered to the target authenticator or identity.
-#[derive(Clone, Debug, Serialize, Deserialize, ValueEnum)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DeliveryMethod {
     /// Indicates that a credential binding link will be returned to the caller upon creation of the credential binding job
@@ -31,7 +33,8 @@ pub enum DeliveryMethod {
 }
 
 /// The current state of the credential binding job.
-#[derive(Clone, Debug, Serialize, Deserialize, ValueEnum)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum State {
     /// Indicates that the credential binding request has been successfully delivered to its target authenticator.
@@ -44,20 +47,22 @@ pub enum State {
     Complete,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateCredentialBindingJobRequest {
-    #[clap(flatten)]
+    #[cfg_attr(feature = "cli", clap(flatten))]
     job: CreateCredentialBindingJob,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
-#[clap(group = ArgGroup::new("authenticator_config_group").required(true).args(&["authenticator_config", "authenticator_config_id"]).multiple(false))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
+#[cfg_attr(feature = "cli", clap(group = ArgGroup::new("authenticator_config_group").required(true).args(&["authenticator_config", "authenticator_config_id"]).multiple(false)))]
 pub struct CreateCredentialBindingJob {
     /// (required) The method by which a credential binding link is delivered to the target authenticator or identity.
-    #[clap(long, value_enum)]
+    #[cfg_attr(feature = "cli", clap(long, value_enum))]
     pub delivery_method: DeliveryMethod,
     /// (optional) The URI to which the caller will be redirected after successfully binding a credential to an identity.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub post_binding_redirect_uri: Option<String>,
     /// The full authenticator configuration (optional if `authenticator_config_id` is provided).
@@ -83,11 +88,11 @@ pub struct CreateCredentialBindingJob {
     ///     "trusted_origins": ["https://trusted-origin.com"]
     ///   }
     /// }
-    #[clap(long, group = "authenticator_config_group", value_parser = clap::builder::ValueParser::new(|s: &str| serde_json::from_str::<CredentialBindingJobInlineAuthenticatorConfig>(s).map_err(|e| e.to_string())))]
+    #[cfg_attr(feature = "cli", clap(long, group = "authenticator_config_group", value_parser = clap::builder::ValueParser::new(|s: &str| serde_json::from_str::<CredentialBindingJobInlineAuthenticatorConfig>(s).map_err(|e| e.to_string()))))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authenticator_config: Option<CredentialBindingJobInlineAuthenticatorConfig>,
     /// The ID of the authenticator configuration (optional if `authenticator_config` is provided).
-    #[clap(long, group = "authenticator_config_group")]
+    #[cfg_attr(feature = "cli", clap(long, group = "authenticator_config_group"))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authenticator_config_id: Option<String>,
 }
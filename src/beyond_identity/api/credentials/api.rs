@@ -1,4 +1,3 @@
-use super::command::ListFieldName;
 use super::types::{Credential, Credentials, CredentialsFieldName};
 
 use crate::beyond_identity::api::common::filter::Filter;
@@ -6,6 +5,7 @@ use crate::beyond_identity::api::common::service::CredentialsService;
 use crate::beyond_identity::api::identities::types::IdentitiesFieldName;
 use crate::common::error::BiError;
 
+use async_trait::async_trait;
 use function_name::named;
 use http::Method;
 
@@ -13,6 +13,7 @@ use http::Method;
 // Credentials API
 // ====================================
 
+#[async_trait]
 pub trait CredentialsApi {
     async fn list_credentials(
         &self,
@@ -36,6 +37,7 @@ pub trait CredentialsApi {
 // Credentials API Implementation
 // ====================================
 
+#[async_trait]
 impl CredentialsApi for CredentialsService {
     async fn list_credentials(
         &self,
@@ -55,10 +57,7 @@ impl CredentialsApi for CredentialsService {
                 identity_id,
                 CredentialsFieldName::Credentials.name(),
             ])
-            .add_query_param(
-                &ListFieldName::Filter.name(),
-                filter.as_ref().map(|f| f.0.as_ref()),
-            )
+            .add_query_param(Filter::QUERY_PARAM, filter.as_ref().map(|f| f.0.as_ref()))
             .to_string()?;
 
         let (credentials, total_size) = self
@@ -121,7 +120,7 @@ impl CredentialsApi for CredentialsService {
                         CredentialsFieldName::Credentials.name(),
                         credential_id,
                     ])
-                    .add_custom_method(&function_name!().split('_').next().unwrap())
+                    .add_custom_method(function_name!().split('_').next().unwrap())
                     .to_string()?,
                 None::<&()>,
             )
@@ -59,7 +59,7 @@ pub struct List {
 #[async_trait]
 impl Executable for List {
     async fn execute(&self) -> Result<(), BiError> {
-        output(CredentialsService::new().build().await.list_credentials(
+        output(CredentialsService::new().build().await?.list_credentials(
             &self.identity_id,
             Filter::new(self.filter.clone())?,
             self.limit,
@@ -89,7 +89,7 @@ impl Executable for Get {
         output(
             CredentialsService::new()
                 .build()
-                .await
+                .await?
                 .get_credential(&self.id, &self.identity_id),
         )
         .await
@@ -117,7 +117,7 @@ impl Executable for Revoke {
         output(
             CredentialsService::new()
                 .build()
-                .await
+                .await?
                 .revoke_credential(&self.id, &self.identity_id),
         )
         .await
@@ -1,4 +1,3 @@
-use super::command::ListFieldName;
 use super::types::{
     AddMembersRequest, CreateGroupRequest, DeleteMembersRequest, PatchGroupRequest,
 };
@@ -12,6 +11,7 @@ use crate::beyond_identity::api::roles::types::Roles;
 use crate::beyond_identity::api::roles::types::{Role, RoleFieldName};
 use crate::common::error::BiError;
 
+use async_trait::async_trait;
 use convert_case::{Case, Casing};
 use function_name::named;
 use http::Method;
@@ -20,6 +20,7 @@ use http::Method;
 // Groups API
 // ====================================
 
+#[async_trait]
 pub trait GroupsApi {
     async fn create_group(&self, request: &CreateGroupRequest) -> Result<Group, BiError>;
     async fn list_groups(
@@ -57,6 +58,7 @@ pub trait GroupsApi {
 // Groups API Implementation
 // ====================================
 
+#[async_trait]
 impl GroupsApi for GroupsService {
     async fn create_group(&self, request: &CreateGroupRequest) -> Result<Group, BiError> {
         self.api_client
@@ -89,10 +91,7 @@ impl GroupsApi for GroupsService {
             .add_tenant()
             .add_realm()
             .add_path(vec![GroupsFieldName::Groups.name()])
-            .add_query_param(
-                &ListFieldName::Filter.name(),
-                filter.as_ref().map(|f| f.0.as_ref()),
-            )
+            .add_query_param(Filter::QUERY_PARAM, filter.as_ref().map(|f| f.0.as_ref()))
             .to_string()?;
 
         let (groups, total_size) = self
@@ -250,7 +249,7 @@ impl GroupsApi for GroupsService {
             .add_path(vec![GroupsFieldName::Groups.name(), group_id])
             .add_custom_method(&function_name!().to_case(Case::Camel))
             .add_query_param(
-                &RoleFieldName::ResourceServerId.name(),
+                RoleFieldName::ResourceServerId.name(),
                 Some(resource_server_id),
             )
             .to_string()?;
@@ -49,7 +49,7 @@ pub enum GroupCommands {
 #[async_trait]
 impl Executable for CreateGroupRequest {
     async fn execute(&self) -> Result<(), BiError> {
-        output(GroupsService::new().build().await.create_group(self)).await
+        output(GroupsService::new().build().await?.create_group(self)).await
     }
 }
 
@@ -85,7 +85,7 @@ impl Executable for List {
         output(
             GroupsService::new()
                 .build()
-                .await
+                .await?
                 .list_groups(Filter::new(self.filter.clone())?, self.limit),
         )
         .await
@@ -106,7 +106,7 @@ pub struct Get {
 #[async_trait]
 impl Executable for Get {
     async fn execute(&self) -> Result<(), BiError> {
-        output(GroupsService::new().build().await.get_group(&self.id)).await
+        output(GroupsService::new().build().await?.get_group(&self.id)).await
     }
 }
 
@@ -117,7 +117,7 @@ impl Executable for Get {
 #[async_trait]
 impl Executable for PatchGroupRequest {
     async fn execute(&self) -> Result<(), BiError> {
-        output(GroupsService::new().build().await.patch_group(self)).await
+        output(GroupsService::new().build().await?.patch_group(self)).await
     }
 }
 
@@ -135,7 +135,7 @@ pub struct Delete {
 #[async_trait]
 impl Executable for Delete {
     async fn execute(&self) -> Result<(), BiError> {
-        output(GroupsService::new().build().await.delete_group(&self.id)).await
+        output(GroupsService::new().build().await?.delete_group(&self.id)).await
     }
 }
 
@@ -158,7 +158,7 @@ impl Executable for AddMembers {
         output(
             GroupsService::new()
                 .build()
-                .await
+                .await?
                 .add_members(&self.id, &self.request),
         )
         .await
@@ -185,7 +185,7 @@ impl Executable for DeleteMembers {
         output(
             GroupsService::new()
                 .build()
-                .await
+                .await?
                 .delete_members(&self.id, &self.request),
         )
         .await
@@ -213,7 +213,7 @@ impl Executable for ListMembers {
         output(
             GroupsService::new()
                 .build()
-                .await
+                .await?
                 .list_members(&self.id, self.limit),
         )
         .await
@@ -242,7 +242,7 @@ pub struct ListRoles {
 #[async_trait]
 impl Executable for ListRoles {
     async fn execute(&self) -> Result<(), BiError> {
-        output(GroupsService::new().build().await.list_roles(
+        output(GroupsService::new().build().await?.list_roles(
             &self.id,
             &self.resource_server_id,
             self.limit,
@@ -1,3 +1,4 @@
+#[cfg(feature = "cli")]
 use clap::Args;
 use field_types::FieldName;
 use serde::{Deserialize, Serialize};
@@ -17,68 +18,75 @@ pub struct GroupEnvelope {
     pub group: Group,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct Group {
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub id: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub realm_id: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub tenant_id: String,
     /// (required) The display name of the group.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: String,
     /// (required) A free-form text field to describe a group.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub description: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub create_time: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub update_time: String,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateGroupRequest {
-    #[clap(flatten)]
+    #[cfg_attr(feature = "cli", clap(flatten))]
     pub group: CreateGroup,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateGroup {
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: String,
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub description: String,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchGroupRequest {
-    #[clap(flatten)]
+    #[cfg_attr(feature = "cli", clap(flatten))]
     pub group: PatchGroup,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchGroup {
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub description: Option<String>,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct AddMembersRequest {
     /// A list of identity IDs to add as members to the group
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     pub identity_ids: Vec<String>,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct DeleteMembersRequest {
     /// A list of identity IDs to delete from the group
-    #[clap(long, use_value_delimiter = true, num_args(0..))]
+    #[cfg_attr(feature = "cli", clap(long, use_value_delimiter = true, num_args(0..)))]
     pub identity_ids: Vec<String>,
 }
@@ -1,4 +1,3 @@
-use super::command::ListFieldName;
 use super::types::{
     CreateIdentityRequest, Identities, IdentitiesFieldName, Identity, PatchIdentityRequest,
 };
@@ -9,6 +8,7 @@ use crate::beyond_identity::api::groups::types::{Group, Groups};
 use crate::beyond_identity::api::roles::types::{Role, RoleFieldName, Roles};
 use crate::common::error::BiError;
 
+use async_trait::async_trait;
 use convert_case::{Case, Casing};
 use function_name::named;
 use http::Method;
@@ -17,6 +17,7 @@ use http::Method;
 // Identities API
 // ====================================
 
+#[async_trait]
 pub trait IdentitiesApi {
     async fn create_identity(&self, request: &CreateIdentityRequest) -> Result<Identity, BiError>;
     async fn delete_identity(&self, identity_id: &str) -> Result<serde_json::Value, BiError>;
@@ -41,6 +42,7 @@ pub trait IdentitiesApi {
 // Identities API Implementation
 // ====================================
 
+#[async_trait]
 impl IdentitiesApi for IdentitiesService {
     async fn create_identity(&self, request: &CreateIdentityRequest) -> Result<Identity, BiError> {
         self.api_client
@@ -109,10 +111,7 @@ impl IdentitiesApi for IdentitiesService {
             .add_tenant()
             .add_realm()
             .add_path(vec![IdentitiesFieldName::Identities.name()])
-            .add_query_param(
-                &ListFieldName::Filter.name(),
-                filter.as_ref().map(|f| f.0.as_ref()),
-            )
+            .add_query_param(Filter::QUERY_PARAM, filter.as_ref().map(|f| f.0.as_ref()))
             .to_string()?;
 
         let (identities, total_size) = self
@@ -168,7 +167,7 @@ impl IdentitiesApi for IdentitiesService {
             .add_path(vec![IdentitiesFieldName::Identities.name(), identity_id])
             .add_custom_method(&function_name!().to_case(Case::Camel))
             .add_query_param(
-                &RoleFieldName::ResourceServerId.name(),
+                RoleFieldName::ResourceServerId.name(),
                 Some(resource_server_id),
             )
             .to_string()?;
@@ -42,7 +42,13 @@ pub enum IdentityCommands {
 #[async_trait]
 impl Executable for CreateIdentityRequest {
     async fn execute(&self) -> Result<(), BiError> {
-        output(IdentitiesService::new().build().await.create_identity(self)).await
+        output(
+            IdentitiesService::new()
+                .build()
+                .await?
+                .create_identity(self),
+        )
+        .await
     }
 }
 
@@ -84,7 +90,7 @@ impl Executable for List {
         output(
             IdentitiesService::new()
                 .build()
-                .await
+                .await?
                 .list_identities(Filter::new(self.filter.clone())?, self.limit),
         )
         .await
@@ -108,7 +114,7 @@ impl Executable for Get {
         output(
             IdentitiesService::new()
                 .build()
-                .await
+                .await?
                 .get_identity(&self.id),
         )
         .await
@@ -122,7 +128,7 @@ impl Executable for Get {
 #[async_trait]
 impl Executable for PatchIdentityRequest {
     async fn execute(&self) -> Result<(), BiError> {
-        output(IdentitiesService::new().build().await.patch_identity(self)).await
+        output(IdentitiesService::new().build().await?.patch_identity(self)).await
     }
 }
 
@@ -143,7 +149,7 @@ impl Executable for Delete {
         output(
             IdentitiesService::new()
                 .build()
-                .await
+                .await?
                 .delete_identity(&self.id),
         )
         .await
@@ -171,7 +177,7 @@ impl Executable for ListGroups {
         output(
             IdentitiesService::new()
                 .build()
-                .await
+                .await?
                 .list_groups(&self.id, self.limit),
         )
         .await
@@ -200,7 +206,7 @@ pub struct ListRoles {
 #[async_trait]
 impl Executable for ListRoles {
     async fn execute(&self) -> Result<(), BiError> {
-        output(IdentitiesService::new().build().await.list_roles(
+        output(IdentitiesService::new().build().await?.list_roles(
             &self.id,
             &self.resource_server_id,
             self.limit,
@@ -4,6 +4,8 @@ pub mod credential_binding_jobs;
 pub mod credentials;
 pub mod groups;
 pub mod identities;
+#[cfg(feature = "cli")]
+pub mod raw;
 pub mod realms;
 pub mod roles;
 pub mod tenants;
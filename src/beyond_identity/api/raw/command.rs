@@ -0,0 +1,129 @@
+use crate::beyond_identity::api::common::api_client::ApiClient;
+use crate::beyond_identity::api::common::serialize::output;
+use crate::common::command::Executable;
+use crate::common::error::BiError;
+
+use async_trait::async_trait;
+use clap::Args;
+use http::Method;
+use serde_json::Value;
+use std::io::Read;
+use url::Url;
+
+// ====================================
+// Raw Request
+// ====================================
+
+#[derive(Args, Debug, Clone)]
+pub struct Raw {
+    /// HTTP method to use, e.g. GET, POST, PATCH or DELETE
+    method: String,
+
+    /// Path of the endpoint, optionally including a query string. Paths are relative to the
+    /// default tenant/realm (e.g. `/identities?page_size=5`) unless they start with `/v1/`, in
+    /// which case they are relative to the API base URL.
+    path: String,
+
+    /// JSON request body. Use `@file.json` to read the body from a file or `@-` to read it from
+    /// standard input.
+    #[clap(long, short = 'd')]
+    data: Option<String>,
+
+    /// Follow `next_page_token` and return the items from every page
+    #[clap(long)]
+    paginate: bool,
+
+    /// Limits the number of items returned when paginating
+    #[clap(long, short = 'n', requires = "paginate")]
+    limit: Option<usize>,
+}
+
+#[async_trait]
+impl Executable for Raw {
+    async fn execute(&self) -> Result<(), BiError> {
+        let method = Method::from_bytes(self.method.to_uppercase().as_bytes())
+            .map_err(|_| BiError::StringError(format!("Invalid HTTP method: {}", self.method)))?;
+        let body = self.body()?;
+
+        let api_client = ApiClient::new(None, None).await?;
+        let builder = api_client.builder().await?.api();
+        let url = if self.path.trim_start_matches('/').starts_with("v1/") {
+            builder.add_raw_path(&self.path)
+        } else {
+            builder.add_tenant().add_realm().add_raw_path(&self.path)
+        }
+        .to_string()?;
+
+        if self.paginate {
+            // Pagination adds its own `page_size`, so take any given one out of the path
+            let (url, page_size) = take_page_size(&url)?;
+            output(async {
+                api_client
+                    .send_request_paginated::<_, Value>(
+                        method,
+                        &url,
+                        body.as_ref(),
+                        self.limit,
+                        page_size,
+                    )
+                    .await
+                    .map(|(items, _)| items)
+            })
+            .await
+        } else {
+            output(api_client.send_request::<_, Value>(method, &url, body.as_ref())).await
+        }
+    }
+}
+
+impl Raw {
+    fn body(&self) -> Result<Option<Value>, BiError> {
+        self.data.as_deref().map(read_data).transpose()
+    }
+}
+
+// Remove the `page_size` query parameter from a URL, returning its value
+fn take_page_size(url: &str) -> Result<(String, Option<usize>), BiError> {
+    let mut url = Url::parse(url).map_err(BiError::InvalidUrl)?;
+    let mut page_size = None;
+    let mut pairs = Vec::new();
+    for (key, value) in url.query_pairs() {
+        if key == "page_size" {
+            page_size = Some(
+                value
+                    .parse()
+                    .map_err(|_| BiError::StringError(format!("Invalid page_size: {}", value)))?,
+            );
+        } else {
+            pairs.push((key.into_owned(), value.into_owned()));
+        }
+    }
+
+    if page_size.is_some() {
+        if pairs.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+    }
+    Ok((url.to_string(), page_size))
+}
+
+// Parse a JSON request body given inline, as `@file.json` or as `@-` for
+// standard input.
+pub fn read_data(data: &str) -> Result<Value, BiError> {
+    let contents = match data.strip_prefix('@') {
+        Some("-") => {
+            let mut contents = String::new();
+            std::io::stdin()
+                .read_to_string(&mut contents)
+                .map_err(|e| BiError::StringError(e.to_string()))?;
+            contents
+        }
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| BiError::StringError(format!("Failed to read {}: {}", path, e)))?,
+        None => data.to_string(),
+    };
+
+    Ok(serde_json::from_str(&contents)?)
+}
@@ -1,16 +1,17 @@
-use super::command::ListFieldName;
 use super::types::{CreateRealmRequest, PatchRealmRequest, Realm, Realms, RealmsFieldName};
 
 use crate::beyond_identity::api::common::filter::Filter;
 use crate::beyond_identity::api::common::service::RealmsService;
 use crate::common::error::BiError;
 
+use async_trait::async_trait;
 use http::Method;
 
 // ====================================
 // Realms API
 // ====================================
 
+#[async_trait]
 pub trait RealmsApi {
     async fn create_realm(&self, request: &CreateRealmRequest) -> Result<Realm, BiError>;
     async fn list_realms(
@@ -27,6 +28,7 @@ pub trait RealmsApi {
 // Realms API Implementation
 // ====================================
 
+#[async_trait]
 impl RealmsApi for RealmsService {
     async fn create_realm(&self, request: &CreateRealmRequest) -> Result<Realm, BiError> {
         self.api_client
@@ -57,10 +59,7 @@ impl RealmsApi for RealmsService {
             .api()
             .add_tenant()
             .add_path(vec![RealmsFieldName::Realms.name()])
-            .add_query_param(
-                &ListFieldName::Filter.name(),
-                filter.as_ref().map(|f| f.0.as_ref()),
-            )
+            .add_query_param(Filter::QUERY_PARAM, filter.as_ref().map(|f| f.0.as_ref()))
             .to_string()?;
 
         let (realms, total_size) = self
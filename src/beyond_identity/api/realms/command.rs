@@ -43,7 +43,7 @@ pub enum RealmCommands {
 #[async_trait]
 impl Executable for CreateRealmRequest {
     async fn execute(&self) -> Result<(), BiError> {
-        output(RealmsService::new().build().await.create_realm(self)).await
+        output(RealmsService::new().build().await?.create_realm(self)).await
     }
 }
 
@@ -79,7 +79,7 @@ impl Executable for List {
         output(
             RealmsService::new()
                 .build()
-                .await
+                .await?
                 .list_realms(Filter::new(self.filter.clone())?, self.limit),
         )
         .await
@@ -100,7 +100,7 @@ pub struct Get {
 #[async_trait]
 impl Executable for Get {
     async fn execute(&self) -> Result<(), BiError> {
-        output(RealmsService::new().build().await.get_realm(&self.id)).await
+        output(RealmsService::new().build().await?.get_realm(&self.id)).await
     }
 }
 
@@ -111,7 +111,7 @@ impl Executable for Get {
 #[async_trait]
 impl Executable for PatchRealmRequest {
     async fn execute(&self) -> Result<(), BiError> {
-        output(RealmsService::new().build().await.patch_realm(self)).await
+        output(RealmsService::new().build().await?.patch_realm(self)).await
     }
 }
 
@@ -129,6 +129,6 @@ pub struct Delete {
 #[async_trait]
 impl Executable for Delete {
     async fn execute(&self) -> Result<(), BiError> {
-        output(RealmsService::new().build().await.delete_realm(&self.id)).await
+        output(RealmsService::new().build().await?.delete_realm(&self.id)).await
     }
 }
@@ -1,3 +1,4 @@
+#[cfg(feature = "cli")]
 use clap::{Args, ValueEnum};
 use field_types::FieldName;
 use serde::{Deserialize, Serialize};
@@ -12,58 +13,64 @@ pub struct Realms {
     pub total_size: usize,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct Realm {
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub id: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub tenant_id: String,
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: String,
-    #[clap(long, value_enum)]
+    #[cfg_attr(feature = "cli", clap(long, value_enum))]
     pub classification: Option<Classification>,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub create_time: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub update_time: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, ValueEnum)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 pub enum Classification {
     #[serde(rename = "Secure Customer")]
-    #[clap(name = "secure_customer")]
+    #[cfg_attr(feature = "cli", clap(name = "secure_customer"))]
     SecureCustomer,
     #[serde(rename = "Secure Workforce")]
-    #[clap(name = "secure_workforce")]
+    #[cfg_attr(feature = "cli", clap(name = "secure_workforce"))]
     SecureWorkforce,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateRealmRequest {
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub classification: Classification,
-    #[clap(flatten)]
+    #[cfg_attr(feature = "cli", clap(flatten))]
     pub realm: CreateRealm,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct CreateRealm {
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: String,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchRealmRequest {
-    #[clap(flatten)]
+    #[cfg_attr(feature = "cli", clap(flatten))]
     pub realm: PatchRealm,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchRealm {
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub id: String,
     /// (optional) The display name of the realm.
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: Option<String>,
 }
@@ -1,3 +1,4 @@
+#[cfg(feature = "cli")]
 use clap::Args;
 use field_types::FieldName;
 use serde::{Deserialize, Serialize};
@@ -17,25 +18,26 @@ pub struct RoleEnvelope {
     pub role: Role,
 }
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize, FieldName)]
+#[derive(Clone, Debug, Serialize, Deserialize, FieldName)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct Role {
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub id: String,
     /// (required) A unique identifier for a resource server.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub resource_server_id: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub realm_id: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub tenant_id: String,
     /// (required) The display name of the role.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: String,
     /// (required) A free-form text field to describe a role.
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub description: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub create_time: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub update_time: String,
 }
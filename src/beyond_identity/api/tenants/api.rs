@@ -3,12 +3,14 @@ use super::types::{PatchTenantRequest, Tenant};
 use crate::beyond_identity::api::common::service::TenantsService;
 use crate::common::error::BiError;
 
+use async_trait::async_trait;
 use http::Method;
 
 // ====================================
 // Tenants API
 // ====================================
 
+#[async_trait]
 pub trait TenantsApi {
     async fn get_tenant(&self) -> Result<Tenant, BiError>;
     async fn patch_tenant(&self, patch_request: &PatchTenantRequest) -> Result<Tenant, BiError>;
@@ -18,6 +20,7 @@ pub trait TenantsApi {
 // Tenants API Implementation
 // ====================================
 
+#[async_trait]
 impl TenantsApi for TenantsService {
     async fn get_tenant(&self) -> Result<Tenant, BiError> {
         self.api_client
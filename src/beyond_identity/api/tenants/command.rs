@@ -33,7 +33,7 @@ pub struct Get;
 #[async_trait]
 impl Executable for Get {
     async fn execute(&self) -> Result<(), BiError> {
-        output(TenantsService::new().build().await.get_tenant()).await
+        output(TenantsService::new().build().await?.get_tenant()).await
     }
 }
 
@@ -54,7 +54,7 @@ impl Executable for Patch {
         output(
             TenantsService::new()
                 .build()
-                .await
+                .await?
                 .patch_tenant(&PatchTenantRequest {
                     tenant: PatchTenant {
                         display_name: Some(self.display_name.to_string()),
@@ -1,3 +1,4 @@
+#[cfg(feature = "cli")]
 use clap::Args;
 use serde::{Deserialize, Serialize};
 
@@ -5,22 +6,24 @@ use serde::{Deserialize, Serialize};
 // Tenant Types
 // ====================================
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct Tenant {
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub id: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub display_name: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub create_time: String,
-    #[clap(skip)]
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub update_time: String,
 }
 
-#[derive(Args, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "cli", derive(Args))]
 pub struct PatchTenant {
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[clap(long)]
+    #[cfg_attr(feature = "cli", clap(long))]
     pub display_name: Option<String>,
 }
 
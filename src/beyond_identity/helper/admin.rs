@@ -11,7 +11,7 @@ pub async fn create_admin_account(
     api_client: &ApiClient,
     email: String,
 ) -> Result<Identity, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -95,7 +95,7 @@ pub async fn create_admin_account(
 pub async fn get_identities_without_role(api_client: &ApiClient) -> Result<Vec<Identity>, BiError> {
     let identities = IdentitiesService::new()
         .build()
-        .await
+        .await?
         .list_identities(None, None)
         .await?
         .identities;
@@ -79,7 +79,7 @@ pub struct ReviewUnenrolled;
 #[async_trait]
 impl Executable for CreateAdminAccount {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
+        let api_client = ApiClient::new(None, None).await?;
         let identity = create_admin_account(&api_client, self.email.to_string())
             .await
             .expect("Failed to create admin account");
@@ -91,7 +91,7 @@ impl Executable for CreateAdminAccount {
 #[async_trait]
 impl Executable for SendEnrollmentEmail {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
+        let api_client = ApiClient::new(None, None).await?;
         let mut identities: Vec<Identity> = Vec::new();
 
         if self.all {
@@ -109,7 +109,7 @@ impl Executable for SendEnrollmentEmail {
         if self.groups {
             let groups = GroupsService::new()
                 .build()
-                .await
+                .await?
                 .list_groups(None, None)
                 .await?
                 .groups;
@@ -128,7 +128,7 @@ impl Executable for SendEnrollmentEmail {
             } else {
                 identities = GroupsService::new()
                     .build()
-                    .await
+                    .await?
                     .list_members(&group.id, None)
                     .await?
                     .identities;
@@ -173,7 +173,7 @@ impl Executable for SendEnrollmentEmail {
 #[async_trait]
 impl Executable for DeleteAllIdentities {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
+        let api_client = ApiClient::new(None, None).await?;
         if self.force {
             if self.all {
                 delete_all_identities(&api_client)
@@ -215,7 +215,7 @@ impl Executable for DeleteAllIdentities {
                 .expect("Failed to fetch unenrolled identities");
         }
 
-        if identities.len() == 0 {
+        if identities.is_empty() {
             println!("No identities found.");
             return Ok(());
         }
@@ -225,7 +225,7 @@ impl Executable for DeleteAllIdentities {
         for identity in &selected_identities {
             IdentitiesService::new()
                 .build()
-                .await
+                .await?
                 .delete_identity(&identity.id)
                 .await
                 .expect("Failed to delete identity");
@@ -238,7 +238,7 @@ impl Executable for DeleteAllIdentities {
 #[async_trait]
 impl Executable for ReviewUnenrolled {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
+        let api_client = ApiClient::new(None, None).await?;
         let unenrolled_identities = get_unenrolled_identities(&api_client)
             .await
             .expect("Failed to fetch unenrolled identities");
@@ -49,7 +49,7 @@ pub struct IdentityResponse {
 }
 
 pub async fn get_all_identities(api_client: &ApiClient) -> Result<Vec<Identity>, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -107,7 +107,7 @@ pub async fn get_credentials_for_identity(
     api_client: &ApiClient,
     identity_id: &str,
 ) -> Result<Vec<Credential>, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -162,7 +162,7 @@ pub async fn get_credentials_for_identity(
 }
 
 pub async fn get_unenrolled_identities(api_client: &ApiClient) -> Result<Vec<Identity>, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -198,7 +198,7 @@ pub async fn get_idp_application_for_sso_config(
     api_client: &ApiClient,
     sso_config_id: String,
 ) -> Result<SsoConfigIdpResponse, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -388,7 +388,7 @@ pub async fn send_enrollment_email(
     identity: &Identity,
     payload: Value,
 ) -> Result<EnrollmentJobResponse, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -8,7 +8,7 @@ pub async fn get_unenrolled_identities_from_group(
     api_client: &ApiClient,
     group_id: &str,
 ) -> Result<Vec<Identity>, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -9,7 +9,7 @@ use crate::beyond_identity::api::identities::types::Identity;
 use crate::common::error::BiError;
 
 pub async fn delete_all_identities(api_client: &ApiClient) -> Result<(), BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -42,7 +42,7 @@ pub async fn delete_all_identities(api_client: &ApiClient) -> Result<(), BiError
         for identity in &page_identities {
             IdentitiesService::new()
                 .build()
-                .await
+                .await?
                 .delete_identity(&identity.id)
                 .await
                 .expect("Failed to delete identity");
@@ -66,7 +66,7 @@ pub async fn delete_all_identities(api_client: &ApiClient) -> Result<(), BiError
 }
 
 pub async fn delete_unenrolled_identities(api_client: &ApiClient) -> Result<(), BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -107,7 +107,7 @@ pub async fn delete_unenrolled_identities(api_client: &ApiClient) -> Result<(),
             if enrolled.is_empty() {
                 IdentitiesService::new()
                     .build()
-                    .await
+                    .await?
                     .delete_identity(&identity.id)
                     .await
                     .expect("Failed to delete identity");
@@ -132,7 +132,7 @@ pub async fn delete_unenrolled_identities(api_client: &ApiClient) -> Result<(),
 }
 
 pub async fn delete_norole_identities(api_client: &ApiClient) -> Result<(), BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -178,7 +178,7 @@ pub async fn delete_norole_identities(api_client: &ApiClient) -> Result<(), BiEr
             if !has_role {
                 IdentitiesService::new()
                     .build()
-                    .await
+                    .await?
                     .delete_identity(&identity.id)
                     .await
                     .expect("Failed to delete identity");
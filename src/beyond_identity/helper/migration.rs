@@ -0,0 +1,2249 @@
+use crate::beyond_identity::api::common::api_client::ApiClient;
+use crate::beyond_identity::api::common::service::{GroupsService, IdentitiesService};
+use crate::beyond_identity::api::groups::api::GroupsApi;
+use crate::beyond_identity::api::groups::types::{
+    AddMembersRequest, CreateGroup, CreateGroupRequest, DeleteMembersRequest, Group, PatchGroup,
+    PatchGroupRequest,
+};
+use crate::beyond_identity::api::identities::api::IdentitiesApi;
+use crate::beyond_identity::api::identities::types::{
+    CreateIdentity, CreateIdentityRequest, Identity, Traits, Type,
+};
+use crate::beyond_identity::helper::sso_configs::{self, SsoConfigPayload};
+use crate::common::database::models::{
+    MigratedApplication, MigratedGroup, MigrationResourceKind, MigrationRun, MigrationRunResource,
+};
+use crate::common::error::BiError;
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use http::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+// ====================================
+// Migration Plan
+// ====================================
+
+/// Everything a fast-migrate run will create, written by `--plan` so it can be
+/// reviewed and later executed unchanged with `--apply`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationPlan {
+    /// Identity provider the applications are migrated from, e.g. `okta`.
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+    pub tenant_id: String,
+    pub realm_id: String,
+    pub applications: Vec<PlannedApplication>,
+    /// Source groups mirrored as Beyond Identity groups. Empty unless groups
+    /// were requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<PlannedGroup>,
+    /// Identities to create for unmatched source users before anything is
+    /// assigned. Empty unless missing identities were requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identities: Vec<PlannedIdentity>,
+    /// Applications that could not be federated and are migrated as bookmark
+    /// tiles instead. Empty unless federation was requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub untranslated: Vec<UntranslatedApplication>,
+    /// Source IDs of every application offered for selection, including those
+    /// left out, so that drift does not report them as added.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub offered: Vec<String>,
+}
+
+/// An SSO config to create in Beyond Identity for a source application. This
+/// is a bookmark tile unless the application is federated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedApplication {
+    pub source_id: String,
+    pub display_name: String,
+    pub login_link: String,
+    pub icon: Option<String>,
+    /// How Beyond Identity signs users in to the application directly, in
+    /// place of the source identity provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub federation: Option<Federation>,
+    /// Source users matched to an existing Beyond Identity identity. These are
+    /// assigned to the tile.
+    pub assignments: Vec<PlannedAssignment>,
+    /// Source users without a matching identity. These are only assigned if an
+    /// identity is planned for them.
+    pub unmatched: Vec<SourceUser>,
+    /// Source IDs of the mirrored groups assigned to the tile.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+}
+
+/// Settings for a generic OIDC SSO config equivalent to a source application.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "protocol", rename_all = "snake_case")]
+pub enum Federation {
+    Oidc {
+        redirect_uris: Vec<String>,
+        post_logout_redirect_uris: Vec<String>,
+        public_client: bool,
+        /// URL of the relying party that starts a sign in, used as the tile link.
+        login_uri: String,
+    },
+}
+
+/// A source application that could not be federated, along with why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UntranslatedApplication {
+    pub source_id: String,
+    pub display_name: String,
+    pub sign_on_mode: String,
+    pub reason: String,
+}
+
+/// A Beyond Identity group to create for a source group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedGroup {
+    pub source_id: String,
+    pub display_name: String,
+    pub description: String,
+    /// Source members matched to an existing Beyond Identity identity. These
+    /// are added to the group.
+    pub members: Vec<PlannedAssignment>,
+    /// Source members without a matching identity. These are only added if an
+    /// identity is planned for them.
+    pub unmatched: Vec<SourceUser>,
+}
+
+/// A Beyond Identity identity to create from the profile of a source user. Its
+/// external ID is set to the source user ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedIdentity {
+    pub source_user_id: String,
+    pub display_name: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedAssignment {
+    pub identity_id: String,
+    pub source_user_id: String,
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_by: Option<MatchStrategy>,
+}
+
+/// How a source user is matched to a Beyond Identity identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatchStrategy {
+    /// Email address equals the primary email address of the identity.
+    Email,
+    /// Email address equals the primary email address of the identity, ignoring case.
+    EmailCi,
+    /// Username equals the username of the identity, ignoring case.
+    Username,
+    /// Source user ID equals the external ID of the identity.
+    ExternalId,
+    /// Value of a source profile attribute equals the external ID of the identity.
+    CustomAttribute,
+}
+
+impl MatchStrategy {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::EmailCi => "email-ci",
+            Self::Username => "username",
+            Self::ExternalId => "external-id",
+            Self::CustomAttribute => "custom-attribute",
+        }
+    }
+
+    fn matches(&self, user: &SourceUser, identity: &Identity) -> bool {
+        let traits = &identity.traits;
+        let (source, target) = match self {
+            Self::Email | Self::EmailCi => (
+                user.email.as_deref(),
+                traits.primary_email_address.as_deref(),
+            ),
+            Self::Username => (user.username.as_deref(), Some(traits.username.as_str())),
+            Self::ExternalId => (Some(user.id.as_str()), traits.external_id.as_deref()),
+            Self::CustomAttribute => (user.attribute.as_deref(), traits.external_id.as_deref()),
+        };
+        match (source, target) {
+            (Some(source), Some(target)) => match self {
+                Self::EmailCi | Self::Username => source.eq_ignore_ascii_case(target),
+                _ => source == target,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceUser {
+    pub id: String,
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+    /// Value of the source profile attribute used for `custom-attribute`
+    /// matching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<String>,
+}
+
+/// A source application along with the users and groups assigned to it.
+pub struct SourceApplication {
+    pub id: String,
+    pub name: String,
+    pub login_link: String,
+    pub icon: Option<String>,
+    pub users: Vec<SourceUser>,
+    /// IDs of the source groups assigned to the application.
+    pub groups: Vec<String>,
+    /// Settings for federating the application, if requested and supported.
+    pub federation: Option<Federation>,
+    /// Sign-on mode and reason the application could not be federated, if
+    /// federation was requested.
+    pub untranslated: Option<(String, String)>,
+}
+
+/// A source group along with its members.
+pub struct SourceGroup {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub users: Vec<SourceUser>,
+}
+
+impl MigrationPlan {
+    // Plan an SSO config for each application and a group for each source
+    // group, matching users to Beyond Identity identities with the first
+    // strategy that finds exactly one identity.
+    pub async fn build(
+        api_client: &ApiClient,
+        source: &str,
+        offered: Vec<String>,
+        applications: Vec<SourceApplication>,
+        groups: Vec<SourceGroup>,
+        strategies: &[MatchStrategy],
+    ) -> Result<Self, BiError> {
+        let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
+            Some((t, r)) => (t, r),
+            None => {
+                return Err(BiError::StringError(
+                    "No default tenant/realm set".to_string(),
+                ))
+            }
+        };
+
+        let identities = IdentitiesService::from(api_client.clone())
+            .list_identities(None, None)
+            .await?
+            .identities;
+
+        let untranslated = applications
+            .iter()
+            .filter_map(|app| {
+                let (sign_on_mode, reason) = app.untranslated.clone()?;
+                Some(UntranslatedApplication {
+                    source_id: app.id.clone(),
+                    display_name: app.name.clone(),
+                    sign_on_mode,
+                    reason,
+                })
+            })
+            .collect();
+        let applications = applications
+            .into_iter()
+            .map(|app| plan_application(app, &identities, strategies))
+            .collect();
+        let groups = groups
+            .into_iter()
+            .map(|group| plan_group(group, &identities, strategies))
+            .collect();
+
+        Ok(Self {
+            source: source.to_string(),
+            created_at: Utc::now(),
+            tenant_id: tenant.id,
+            realm_id: realm.id,
+            applications,
+            groups,
+            identities: Vec::new(),
+            untranslated,
+            offered,
+        })
+    }
+
+    // Plan an identity for every unmatched user with a username, so that they
+    // are assigned once it is created. Each user is planned once no matter how
+    // many applications or groups they are unmatched in.
+    pub fn plan_missing_identities(&mut self) {
+        let unmatched = self
+            .applications
+            .iter()
+            .flat_map(|app| &app.unmatched)
+            .chain(self.groups.iter().flat_map(|group| &group.unmatched));
+
+        for user in unmatched {
+            if self
+                .identities
+                .iter()
+                .any(|identity| identity.source_user_id == user.id)
+            {
+                continue;
+            }
+            let Some(username) = user.username.clone() else {
+                println!(
+                    "Skipping identity for user {}: no username in profile",
+                    user.id
+                );
+                continue;
+            };
+            let full_name = [user.given_name.as_deref(), user.family_name.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let display_name = if full_name.is_empty() {
+                username.clone()
+            } else {
+                full_name
+            };
+
+            self.identities.push(PlannedIdentity {
+                source_user_id: user.id.clone(),
+                display_name,
+                username,
+                email: user.email.clone(),
+                given_name: user.given_name.clone(),
+                family_name: user.family_name.clone(),
+            });
+        }
+    }
+
+    pub fn load(path: &Path, source: &str) -> Result<Self, BiError> {
+        let plan: Self = read_json(path, "plan")?;
+
+        if plan.source != source {
+            return Err(BiError::StringError(format!(
+                "{} is a plan for migrating from {}, not {}",
+                path.display(),
+                plan.source,
+                source
+            )));
+        }
+
+        Ok(plan)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BiError> {
+        write_json(path, self)
+    }
+
+    pub fn summary(&self) -> String {
+        let assignments: usize = self.applications.iter().map(|a| a.assignments.len()).sum();
+        let unmatched: usize = self.applications.iter().map(|a| a.unmatched.len()).sum();
+        let groups = if self.groups.is_empty() {
+            String::new()
+        } else {
+            let members: usize = self.groups.iter().map(|g| g.members.len()).sum();
+            format!("{} groups with {} members, ", self.groups.len(), members)
+        };
+        let identities = if self.identities.is_empty() {
+            String::new()
+        } else {
+            format!(", {} identities to create", self.identities.len())
+        };
+        let federated = self
+            .applications
+            .iter()
+            .filter(|app| app.federation.is_some())
+            .count();
+        let federated = if federated == 0 && self.untranslated.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " ({} federated, {} untranslated)",
+                federated,
+                self.untranslated.len()
+            )
+        };
+        format!(
+            "{} applications{}, {}{} user assignments, {} unmatched users{}",
+            self.applications.len(),
+            federated,
+            groups,
+            assignments,
+            unmatched,
+            identities
+        )
+    }
+
+    // Write every unmatched source user to a CSV file for manual
+    // reconciliation, noting whether an identity is planned for them.
+    pub fn write_unmatched_report(&self, path: &Path) -> Result<(), BiError> {
+        let mut contents =
+            String::from("kind,name,source_id,source_user_id,email,username,identity_planned\n");
+        let unmatched = self
+            .applications
+            .iter()
+            .map(|app| {
+                (
+                    "application",
+                    &app.display_name,
+                    &app.source_id,
+                    &app.unmatched,
+                )
+            })
+            .chain(self.groups.iter().map(|group| {
+                (
+                    "group",
+                    &group.display_name,
+                    &group.source_id,
+                    &group.unmatched,
+                )
+            }));
+        let mut count = 0;
+        for (kind, name, source_id, users) in unmatched {
+            for user in users {
+                let planned = self
+                    .identities
+                    .iter()
+                    .any(|identity| identity.source_user_id == user.id);
+                let row = [
+                    kind,
+                    name,
+                    source_id,
+                    &user.id,
+                    user.email.as_deref().unwrap_or_default(),
+                    user.username.as_deref().unwrap_or_default(),
+                    if planned { "true" } else { "false" },
+                ];
+                let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+                contents.push_str(&row.join(","));
+                contents.push('\n');
+                count += 1;
+            }
+        }
+
+        std::fs::write(path, contents).map_err(|e| {
+            BiError::StringError(format!("Failed to write {}: {}", path.display(), e))
+        })?;
+        println!("Wrote {} unmatched users to {}", count, path.display());
+        Ok(())
+    }
+
+    // Report the applications that could not be federated. These still rely
+    // on the source identity provider to sign users in.
+    pub fn report_untranslated(&self) {
+        if self.untranslated.is_empty() {
+            return;
+        }
+        println!(
+            "{} applications could not be federated and are kept as bookmark tiles:",
+            self.untranslated.len()
+        );
+        for app in &self.untranslated {
+            println!(
+                "  {} ({}, {}): {}",
+                app.display_name, app.source_id, app.sign_on_mode, app.reason
+            );
+        }
+    }
+
+    // Create the planned tiles and assignments exactly as written. The plan
+    // must target the current default tenant and realm. Applications migrated
+    // by an earlier run have their existing tile updated and their assignments
+    // synced to the plan instead. Everything created is recorded against a new
+    // run so it can be rolled back.
+    pub async fn apply(&self, api_client: &ApiClient) -> Result<(), BiError> {
+        let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
+            Some((t, r)) => (t, r),
+            None => {
+                return Err(BiError::StringError(
+                    "No default tenant/realm set".to_string(),
+                ))
+            }
+        };
+        if tenant.id != self.tenant_id || realm.id != self.realm_id {
+            return Err(BiError::StringError(format!(
+                "Plan targets tenant {} realm {}, but the default is tenant {} realm {}",
+                self.tenant_id, self.realm_id, tenant.id, realm.id
+            )));
+        }
+
+        let run = start_run(api_client, &self.tenant_id, &self.realm_id, &self.source).await?;
+        let offered: Vec<String> = self
+            .offered
+            .iter()
+            .chain(self.applications.iter().map(|app| &app.source_id))
+            .cloned()
+            .collect();
+        api_client
+            .db()?
+            .add_migration_run_applications(&run.id, &offered)
+            .await?;
+
+        // Identities come first so they can be added to groups and assigned
+        let identity_ids = self.apply_identities(api_client, &run.id).await?;
+
+        // Groups come next so they can be assigned to the SSO configs
+        let mut group_ids = HashMap::new();
+        for planned in &self.groups {
+            match self
+                .apply_group(api_client, &run.id, planned, &identity_ids)
+                .await
+            {
+                Ok(applied) => {
+                    if applied.created {
+                        println!(
+                            "Group created for {} with {} members",
+                            planned.display_name, applied.added
+                        );
+                    } else {
+                        println!(
+                            "Group updated for {} ({} members added, {} removed)",
+                            planned.display_name, applied.added, applied.removed
+                        );
+                    }
+                    group_ids.insert(planned.source_id.clone(), applied.group.id);
+                }
+                Err(err) => println!(
+                    "Failed to mirror group {} to a group, assigning its members directly: {}",
+                    planned.display_name, err
+                ),
+            }
+        }
+
+        let mut federated = Vec::new();
+        for app in &self.applications {
+            match self
+                .apply_application(api_client, &run.id, app, &identity_ids, &group_ids)
+                .await
+            {
+                Ok(applied) => {
+                    if applied.created {
+                        println!(
+                            "SSO config created for {} with {} assignments: {}",
+                            app.display_name,
+                            applied.added,
+                            serde_json::to_string_pretty(&applied.sso_config).unwrap()
+                        );
+                    } else {
+                        println!(
+                            "SSO config updated for {} ({} assignments added, {} removed): {}",
+                            app.display_name,
+                            applied.added,
+                            applied.removed,
+                            serde_json::to_string_pretty(&applied.sso_config).unwrap()
+                        );
+                    }
+                    if app.federation.is_some() {
+                        federated.push((&app.display_name, applied.sso_config));
+                    }
+                }
+                Err(err) => {
+                    println!(
+                        "Failed to migrate {} to an SSO config: {}",
+                        app.display_name, err
+                    )
+                }
+            }
+        }
+
+        self.report_untranslated();
+        report_federated(&federated);
+        println!(
+            "Run ID: {}. Undo everything this run created with `bi {} fast-migrate rollback {}`.",
+            run.id, self.source, run.id
+        );
+
+        Ok(())
+    }
+
+    async fn apply_application(
+        &self,
+        api_client: &ApiClient,
+        run_id: &str,
+        app: &PlannedApplication,
+        identity_ids: &HashMap<String, String>,
+        group_ids: &HashMap<String, String>,
+    ) -> Result<AppliedApplication, BiError> {
+        let migrated = api_client
+            .db()?
+            .get_migrated_application(
+                &self.tenant_id,
+                &self.realm_id,
+                &self.source,
+                &app.source_id,
+            )
+            .await?;
+
+        // The SSO config may have been deleted since the last run
+        let existing = match &migrated {
+            Some(migrated) => {
+                sso_configs::get_sso_config(api_client, &migrated.sso_config_id).await?
+            }
+            None => None,
+        };
+
+        let (sso_config, created) = match existing {
+            Some(existing) => (
+                sso_configs::update_sso_config(
+                    api_client,
+                    &existing.id,
+                    app.display_name.clone(),
+                    &app.payload(),
+                )
+                .await?,
+                false,
+            ),
+            None => {
+                let sso_config = sso_configs::create_sso_config(
+                    api_client,
+                    app.display_name.clone(),
+                    &app.payload(),
+                )
+                .await?;
+                record(
+                    api_client,
+                    run_id,
+                    MigrationResourceKind::SsoConfig,
+                    &sso_config.id,
+                    None,
+                )
+                .await?;
+                (sso_config, true)
+            }
+        };
+
+        api_client
+            .db()?
+            .set_migrated_application(&MigratedApplication {
+                tenant_id: self.tenant_id.clone(),
+                realm_id: self.realm_id.clone(),
+                source: self.source.clone(),
+                source_id: app.source_id.clone(),
+                sso_config_id: sso_config.id.clone(),
+                updated_at: Utc::now().timestamp(),
+            })
+            .await?;
+
+        // Members of assigned groups that could not be mirrored are assigned
+        // directly instead, so they keep access to the tile
+        let unmirrored: Vec<&PlannedGroup> = self
+            .groups
+            .iter()
+            .filter(|group| {
+                app.groups.contains(&group.source_id) && !group_ids.contains_key(&group.source_id)
+            })
+            .collect();
+
+        // Sync assignments so the tile is assigned to every planned identity.
+        // Only identities an earlier run assigned are removed, so assignments
+        // made by hand in Beyond Identity survive reruns.
+        let mut planned = planned_identity_ids(&app.assignments, &app.unmatched, identity_ids);
+        for group in &unmirrored {
+            println!(
+                "Assigning the members of group {} to {} directly",
+                group.display_name, app.display_name
+            );
+            planned.extend(planned_identity_ids(
+                &group.members,
+                &group.unmatched,
+                identity_ids,
+            ));
+        }
+        // A user may be assigned both directly and through a group
+        planned.sort();
+        planned.dedup();
+        let (current, assigned) = if created {
+            (Vec::new(), Vec::new())
+        } else {
+            (
+                sso_configs::list_sso_config_identity_ids(api_client, &sso_config.id).await?,
+                api_client
+                    .db()?
+                    .get_migrated_assignments(
+                        &sso_config.id,
+                        MigrationResourceKind::SsoConfigIdentity,
+                    )
+                    .await?,
+            )
+        };
+        let to_add: Vec<String> = planned
+            .iter()
+            .filter(|id| !current.contains(id))
+            .cloned()
+            .collect();
+        let to_remove: Vec<String> = current
+            .iter()
+            .filter(|id| assigned.contains(id) && !planned.contains(id))
+            .cloned()
+            .collect();
+
+        if !to_add.is_empty() {
+            sso_configs::assign_identities_to_sso_config(api_client, &sso_config.id, &to_add)
+                .await?;
+            api_client
+                .db()?
+                .add_migrated_assignments(
+                    &sso_config.id,
+                    MigrationResourceKind::SsoConfigIdentity,
+                    &to_add,
+                )
+                .await?;
+            // Assignments to a created SSO config are removed along with it
+            if !created {
+                for identity_id in &to_add {
+                    record(
+                        api_client,
+                        run_id,
+                        MigrationResourceKind::SsoConfigIdentity,
+                        identity_id,
+                        Some(&sso_config.id),
+                    )
+                    .await?;
+                }
+            }
+        }
+        if !to_remove.is_empty() {
+            sso_configs::unassign_identities_from_sso_config(
+                api_client,
+                &sso_config.id,
+                &to_remove,
+            )
+            .await?;
+            api_client
+                .db()?
+                .delete_migrated_assignments(
+                    &sso_config.id,
+                    MigrationResourceKind::SsoConfigIdentity,
+                    &to_remove,
+                )
+                .await?;
+        }
+
+        // Sync groups only when the plan mirrors them, so that a plan without
+        // groups leaves group assignments alone. As with identities, only
+        // groups an earlier run assigned are removed, and none are while any
+        // of the application's groups could not be mirrored.
+        if !self.groups.is_empty() {
+            let planned: Vec<String> = app
+                .groups
+                .iter()
+                .filter_map(|source_id| group_ids.get(source_id).cloned())
+                .collect();
+            let (current, assigned) = if created {
+                (Vec::new(), Vec::new())
+            } else {
+                (
+                    sso_configs::list_sso_config_group_ids(api_client, &sso_config.id).await?,
+                    api_client
+                        .db()?
+                        .get_migrated_assignments(
+                            &sso_config.id,
+                            MigrationResourceKind::SsoConfigGroup,
+                        )
+                        .await?,
+                )
+            };
+            let to_add: Vec<String> = planned
+                .iter()
+                .filter(|id| !current.contains(id))
+                .cloned()
+                .collect();
+            let to_remove: Vec<String> = current
+                .iter()
+                .filter(|id| {
+                    unmirrored.is_empty() && assigned.contains(id) && !planned.contains(id)
+                })
+                .cloned()
+                .collect();
+
+            if !to_add.is_empty() {
+                sso_configs::assign_groups_to_sso_config(api_client, &sso_config.id, &to_add)
+                    .await?;
+                api_client
+                    .db()?
+                    .add_migrated_assignments(
+                        &sso_config.id,
+                        MigrationResourceKind::SsoConfigGroup,
+                        &to_add,
+                    )
+                    .await?;
+                if !created {
+                    for group_id in &to_add {
+                        record(
+                            api_client,
+                            run_id,
+                            MigrationResourceKind::SsoConfigGroup,
+                            group_id,
+                            Some(&sso_config.id),
+                        )
+                        .await?;
+                    }
+                }
+            }
+            if !to_remove.is_empty() {
+                sso_configs::unassign_groups_from_sso_config(
+                    api_client,
+                    &sso_config.id,
+                    &to_remove,
+                )
+                .await?;
+                api_client
+                    .db()?
+                    .delete_migrated_assignments(
+                        &sso_config.id,
+                        MigrationResourceKind::SsoConfigGroup,
+                        &to_remove,
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(AppliedApplication {
+            sso_config,
+            created,
+            added: to_add.len(),
+            removed: to_remove.len(),
+        })
+    }
+
+    // Create the planned identities, reporting each one, and map source user
+    // IDs to them. An identity already carrying the source user ID as its
+    // external ID, e.g. from an earlier apply of the same plan, is reused.
+    async fn apply_identities(
+        &self,
+        api_client: &ApiClient,
+        run_id: &str,
+    ) -> Result<HashMap<String, String>, BiError> {
+        let mut identity_ids = HashMap::new();
+        if self.identities.is_empty() {
+            return Ok(identity_ids);
+        }
+
+        let identities = IdentitiesService::from(api_client.clone());
+        let existing = identities.list_identities(None, None).await?.identities;
+
+        let mut created = 0;
+        for planned in &self.identities {
+            let reused = existing.iter().find(|identity| {
+                identity.traits.external_id.as_deref() == Some(planned.source_user_id.as_str())
+            });
+            if let Some(identity) = reused {
+                println!(
+                    "Identity already exists for {} ({}): {}",
+                    planned.username, planned.source_user_id, identity.id
+                );
+                identity_ids.insert(planned.source_user_id.clone(), identity.id.clone());
+                continue;
+            }
+
+            let request = CreateIdentityRequest {
+                identity: CreateIdentity {
+                    display_name: planned.display_name.clone(),
+                    traits: Traits {
+                        r#type: Type::TraitsV0,
+                        username: planned.username.clone(),
+                        primary_email_address: planned.email.clone(),
+                        external_id: Some(planned.source_user_id.clone()),
+                        family_name: planned.family_name.clone(),
+                        given_name: planned.given_name.clone(),
+                    },
+                },
+            };
+            match identities.create_identity(&request).await {
+                Ok(identity) => {
+                    record(
+                        api_client,
+                        run_id,
+                        MigrationResourceKind::Identity,
+                        &identity.id,
+                        None,
+                    )
+                    .await?;
+                    println!(
+                        "Identity created for {} ({}): {}",
+                        planned.username, planned.source_user_id, identity.id
+                    );
+                    identity_ids.insert(planned.source_user_id.clone(), identity.id);
+                    created += 1;
+                }
+                Err(err) => println!(
+                    "Failed to create identity for {} ({}): {}",
+                    planned.username, planned.source_user_id, err
+                ),
+            }
+        }
+        println!(
+            "Created {} of {} planned identities",
+            created,
+            self.identities.len()
+        );
+
+        Ok(identity_ids)
+    }
+
+    async fn apply_group(
+        &self,
+        api_client: &ApiClient,
+        run_id: &str,
+        planned: &PlannedGroup,
+        identity_ids: &HashMap<String, String>,
+    ) -> Result<AppliedGroup, BiError> {
+        let groups = GroupsService::from(api_client.clone());
+        let migrated = api_client
+            .db()?
+            .get_migrated_group(
+                &self.tenant_id,
+                &self.realm_id,
+                &self.source,
+                &planned.source_id,
+            )
+            .await?;
+
+        // The group may have been deleted since the last run
+        let existing = match &migrated {
+            Some(migrated) => get_group(&groups, &migrated.group_id).await?,
+            None => None,
+        };
+
+        let (group, created) = match existing {
+            Some(existing)
+                if existing.display_name == planned.display_name
+                    && existing.description == planned.description =>
+            {
+                (existing, false)
+            }
+            Some(existing) => (
+                groups
+                    .patch_group(&PatchGroupRequest {
+                        group: PatchGroup {
+                            id: existing.id,
+                            display_name: Some(planned.display_name.clone()),
+                            description: Some(planned.description.clone()),
+                        },
+                    })
+                    .await?,
+                false,
+            ),
+            None => {
+                let group = groups
+                    .create_group(&CreateGroupRequest {
+                        group: CreateGroup {
+                            display_name: planned.display_name.clone(),
+                            description: planned.description.clone(),
+                        },
+                    })
+                    .await?;
+                record(
+                    api_client,
+                    run_id,
+                    MigrationResourceKind::Group,
+                    &group.id,
+                    None,
+                )
+                .await?;
+                (group, true)
+            }
+        };
+
+        api_client
+            .db()?
+            .set_migrated_group(&MigratedGroup {
+                tenant_id: self.tenant_id.clone(),
+                realm_id: self.realm_id.clone(),
+                source: self.source.clone(),
+                source_id: planned.source_id.clone(),
+                group_id: group.id.clone(),
+                updated_at: Utc::now().timestamp(),
+            })
+            .await?;
+
+        // Sync members so the group contains every planned identity. Only
+        // members an earlier run added are removed, so members added by hand
+        // in Beyond Identity survive reruns.
+        let planned_ids = planned_identity_ids(&planned.members, &planned.unmatched, identity_ids);
+        let (current, added) = if created {
+            (Vec::new(), Vec::new())
+        } else {
+            (
+                groups
+                    .list_members(&group.id, None)
+                    .await?
+                    .identities
+                    .into_iter()
+                    .map(|identity| identity.id)
+                    .collect(),
+                api_client
+                    .db()?
+                    .get_migrated_assignments(&group.id, MigrationResourceKind::GroupMember)
+                    .await?,
+            )
+        };
+        let to_add: Vec<String> = planned_ids
+            .iter()
+            .filter(|id| !current.contains(id))
+            .cloned()
+            .collect();
+        let to_remove: Vec<String> = current
+            .iter()
+            .filter(|id| added.contains(id) && !planned_ids.contains(id))
+            .cloned()
+            .collect();
+
+        if !to_add.is_empty() {
+            groups
+                .add_members(
+                    &group.id,
+                    &AddMembersRequest {
+                        identity_ids: to_add.clone(),
+                    },
+                )
+                .await?;
+            api_client
+                .db()?
+                .add_migrated_assignments(&group.id, MigrationResourceKind::GroupMember, &to_add)
+                .await?;
+            // Members of a created group are removed along with it
+            if !created {
+                for identity_id in &to_add {
+                    record(
+                        api_client,
+                        run_id,
+                        MigrationResourceKind::GroupMember,
+                        identity_id,
+                        Some(&group.id),
+                    )
+                    .await?;
+                }
+            }
+        }
+        if !to_remove.is_empty() {
+            groups
+                .delete_members(
+                    &group.id,
+                    &DeleteMembersRequest {
+                        identity_ids: to_remove.clone(),
+                    },
+                )
+                .await?;
+            api_client
+                .db()?
+                .delete_migrated_assignments(
+                    &group.id,
+                    MigrationResourceKind::GroupMember,
+                    &to_remove,
+                )
+                .await?;
+            for identity_id in &to_remove {
+                record(
+                    api_client,
+                    run_id,
+                    MigrationResourceKind::RemovedGroupMember,
+                    identity_id,
+                    Some(&group.id),
+                )
+                .await?;
+            }
+        }
+
+        Ok(AppliedGroup {
+            group,
+            created,
+            added: to_add.len(),
+            removed: to_remove.len(),
+        })
+    }
+}
+
+impl PlannedApplication {
+    // Payload of the SSO config for the application. Federated tiles sign in
+    // through Beyond Identity rather than the source identity provider by
+    // opening the relying party's login URL.
+    fn payload(&self) -> SsoConfigPayload {
+        let icon = self.icon.clone();
+        match self.federation.clone() {
+            None => SsoConfigPayload::bookmark(self.login_link.clone(), icon),
+            Some(Federation::Oidc {
+                redirect_uris,
+                post_logout_redirect_uris,
+                public_client,
+                login_uri,
+            }) => SsoConfigPayload::GenericOidc {
+                redirect_uris,
+                post_logout_redirect_uris,
+                confidentiality: if public_client {
+                    "public"
+                } else {
+                    "confidential"
+                }
+                .to_string(),
+                login_link: login_uri,
+                icon,
+                is_tile_visible: true,
+            },
+        }
+    }
+}
+
+struct AppliedApplication {
+    sso_config: sso_configs::SsoConfig,
+    created: bool,
+    added: usize,
+    removed: usize,
+}
+
+// IDs of the identities to assign: the matched ones along with any created for
+// unmatched users
+fn planned_identity_ids(
+    assignments: &[PlannedAssignment],
+    unmatched: &[SourceUser],
+    identity_ids: &HashMap<String, String>,
+) -> Vec<String> {
+    assignments
+        .iter()
+        .map(|assignment| assignment.identity_id.clone())
+        .chain(
+            unmatched
+                .iter()
+                .filter_map(|user| identity_ids.get(&user.id).cloned()),
+        )
+        .collect()
+}
+
+struct AppliedGroup {
+    group: Group,
+    created: bool,
+    added: usize,
+    removed: usize,
+}
+
+// Get a group, or `None` if it no longer exists
+async fn get_group(groups: &GroupsService, group_id: &str) -> Result<Option<Group>, BiError> {
+    match groups.get_group(group_id).await {
+        Ok(group) => Ok(Some(group)),
+        Err(BiError::RequestError(StatusCode::NOT_FOUND, _)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn plan_application(
+    app: SourceApplication,
+    identities: &[Identity],
+    strategies: &[MatchStrategy],
+) -> PlannedApplication {
+    let (assignments, unmatched) = match_users(app.users, identities, strategies);
+
+    PlannedApplication {
+        source_id: app.id,
+        display_name: sso_configs::sanitize_label(&app.name),
+        login_link: app.login_link,
+        icon: app.icon,
+        federation: app.federation,
+        assignments,
+        unmatched,
+        groups: app.groups,
+    }
+}
+
+fn plan_group(
+    group: SourceGroup,
+    identities: &[Identity],
+    strategies: &[MatchStrategy],
+) -> PlannedGroup {
+    let (members, unmatched) = match_users(group.users, identities, strategies);
+
+    PlannedGroup {
+        source_id: group.id,
+        display_name: group.name,
+        description: group.description,
+        members,
+        unmatched,
+    }
+}
+
+// Split source users into those matching an identity and those without a
+// match
+fn match_users(
+    users: Vec<SourceUser>,
+    identities: &[Identity],
+    strategies: &[MatchStrategy],
+) -> (Vec<PlannedAssignment>, Vec<SourceUser>) {
+    let mut assignments = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for user in users {
+        match match_user(&user, identities, strategies) {
+            Some((identity, strategy)) => assignments.push(PlannedAssignment {
+                identity_id: identity.id.clone(),
+                source_user_id: user.id,
+                email: user.email,
+                matched_by: Some(strategy),
+            }),
+            None => unmatched.push(user),
+        }
+    }
+
+    (assignments, unmatched)
+}
+
+// Try each strategy in turn until one matches exactly one identity. A user
+// matching several identities falls through to the next strategy.
+fn match_user<'a>(
+    user: &SourceUser,
+    identities: &'a [Identity],
+    strategies: &[MatchStrategy],
+) -> Option<(&'a Identity, MatchStrategy)> {
+    for strategy in strategies {
+        let mut candidates = identities
+            .iter()
+            .filter(|identity| strategy.matches(user, identity));
+        match (candidates.next(), candidates.next()) {
+            (Some(identity), None) => return Some((identity, *strategy)),
+            (Some(_), Some(_)) => println!(
+                "User {} matches more than one identity by {}",
+                user.id,
+                strategy.name()
+            ),
+            _ => {}
+        }
+    }
+    None
+}
+
+async fn record(
+    api_client: &ApiClient,
+    run_id: &str,
+    kind: MigrationResourceKind,
+    resource_id: &str,
+    parent_id: Option<&str>,
+) -> Result<(), BiError> {
+    api_client
+        .db()?
+        .add_migration_run_resource(&MigrationRunResource {
+            run_id: run_id.to_string(),
+            kind,
+            resource_id: resource_id.to_string(),
+            parent_id: parent_id.map(String::from),
+        })
+        .await
+}
+
+// Quote a CSV field if it contains a separator, quote or line break. Fields
+// that a spreadsheet would evaluate as a formula are prefixed with `'`, since
+// they come from user profiles in the source.
+fn csv_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+// Read a plan or snapshot written by `write_json`
+pub fn read_json<T: DeserializeOwned>(path: &Path, what: &str) -> Result<T, BiError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| BiError::StringError(format!("Failed to read {}: {}", path.display(), e)))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| BiError::StringError(format!("Invalid {} {}: {}", what, path.display(), e)))
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), BiError> {
+    let contents = serde_json::to_string_pretty(value)?;
+    std::fs::write(path, contents)
+        .map_err(|e| BiError::StringError(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+async fn start_run(
+    api_client: &ApiClient,
+    tenant_id: &str,
+    realm_id: &str,
+    source: &str,
+) -> Result<MigrationRun, BiError> {
+    let run = MigrationRun {
+        id: generate_run_id(),
+        tenant_id: tenant_id.to_string(),
+        realm_id: realm_id.to_string(),
+        source: source.to_string(),
+        started_at: Utc::now().timestamp(),
+        rolled_back_at: None,
+    };
+    api_client.db()?.create_migration_run(&run).await?;
+    Ok(run)
+}
+
+// Run IDs sort by start time, e.g. `20241018-141500-3fa9`
+fn generate_run_id() -> String {
+    format!(
+        "{}-{:04x}",
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        rand::random::<u16>()
+    )
+}
+
+// ====================================
+// Drift
+// ====================================
+
+// Assignment differences between a migrated source application and its SSO
+// config
+struct ApplicationDrift {
+    display_name: String,
+    sso_config_id: String,
+    to_add: Vec<String>,
+    to_remove: Vec<String>,
+    unmatched: usize,
+    deactivated: bool,
+}
+
+// Membership differences between a source group and the group it was
+// mirrored to
+struct GroupDrift {
+    display_name: String,
+    group_id: String,
+    to_add: Vec<String>,
+    to_remove: Vec<String>,
+    unmatched: usize,
+}
+
+// Print the client credentials of each federated SSO config, which its
+// relying party must be switched over to before users sign in through Beyond
+// Identity
+fn report_federated(federated: &[(&String, sso_configs::SsoConfig)]) {
+    if federated.is_empty() {
+        return;
+    }
+    println!(
+        "{} federated applications sign in through Beyond Identity once switched over to these clients:",
+        federated.len()
+    );
+    for (display_name, sso_config) in federated {
+        match sso_config.oidc_client() {
+            Some((client_id, Some(client_secret))) => println!(
+                "  {}: client ID {}, client secret {}",
+                display_name, client_id, client_secret
+            ),
+            Some((client_id, None)) => println!(
+                "  {}: client ID {}, client secret not returned",
+                display_name, client_id
+            ),
+            None => println!(
+                "  {}: no client credentials returned, find them on SSO config {}",
+                display_name, sso_config.id
+            ),
+        }
+    }
+}
+
+// Match source users to identities for drift, returning the sorted identity
+// IDs and the number of users without a match. Identities fast-migrate created
+// carry the source user ID as their external ID, so they are matched by it
+// first whatever strategies are given now.
+fn match_drift_users(
+    users: Vec<SourceUser>,
+    identities: &[Identity],
+    created: &[String],
+    strategies: &[MatchStrategy],
+) -> (Vec<String>, usize) {
+    let created: Vec<Identity> = identities
+        .iter()
+        .filter(|identity| created.contains(&identity.id))
+        .cloned()
+        .collect();
+    let (mut assignments, unmatched) = match_users(users, &created, &[MatchStrategy::ExternalId]);
+    let (matched, unmatched) = match_users(unmatched, identities, strategies);
+    assignments.extend(matched);
+
+    let mut planned: Vec<String> = assignments
+        .into_iter()
+        .map(|assignment| assignment.identity_id)
+        .collect();
+    planned.sort();
+    planned.dedup();
+    (planned, unmatched.len())
+}
+
+// Split the difference between planned and current members into those to add
+// and those to remove. Only members in `removable`, which fast-migrate added,
+// are ever removed.
+fn diff_members(
+    planned: &[String],
+    current: Vec<String>,
+    removable: &[String],
+) -> (Vec<String>, Vec<String>) {
+    let to_add = planned
+        .iter()
+        .filter(|id| !current.contains(id))
+        .cloned()
+        .collect();
+    let to_remove = current
+        .into_iter()
+        .filter(|id| removable.contains(id) && !planned.contains(id))
+        .collect();
+    (to_add, to_remove)
+}
+
+// Compare the current source applications with the SSO configs they were
+// migrated to. Identities assigned in the source but not in Beyond Identity
+// are added, and those fast-migrate assigned that are no longer assigned in
+// the source are removed. Applications added to the source since the last run
+// are migrated as bookmark tiles, and applications no longer active in the
+// source have every identity fast-migrate assigned removed. Source groups are
+// compared with the groups they were mirrored to the same way, only removing
+// members fast-migrate added. Nothing is
+// changed unless `fix` is set, in which case the changes are recorded against
+// a new run so they can be rolled back.
+pub async fn drift(
+    api_client: &ApiClient,
+    source: &str,
+    applications: Vec<SourceApplication>,
+    groups: Vec<SourceGroup>,
+    strategies: &[MatchStrategy],
+    fix: bool,
+) -> Result<(), BiError> {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
+        Some((t, r)) => (t, r),
+        None => {
+            return Err(BiError::StringError(
+                "No default tenant/realm set".to_string(),
+            ))
+        }
+    };
+
+    let db = api_client.db()?;
+    let identities = IdentitiesService::from(api_client.clone())
+        .list_identities(None, None)
+        .await?
+        .identities;
+    let mut migrated = db
+        .get_migrated_applications(&tenant.id, &realm.id, source)
+        .await?;
+    let offered = db
+        .get_offered_applications(&tenant.id, &realm.id, source)
+        .await?;
+    let created = db
+        .get_migrated_resources(
+            &tenant.id,
+            &realm.id,
+            source,
+            MigrationResourceKind::Identity,
+        )
+        .await?;
+
+    let mut drifts = Vec::new();
+    let mut added = Vec::new();
+    for app in applications {
+        let Some(index) = migrated.iter().position(|m| m.source_id == app.id) else {
+            // Applications offered by an earlier run were deliberately left out
+            if !offered.contains(&app.id) {
+                added.push(plan_application(app, &identities, strategies));
+            }
+            continue;
+        };
+        let migrated = migrated.remove(index);
+        // Applications whose SSO config was deleted are migrated again
+        let Some(sso_config) =
+            sso_configs::get_sso_config(api_client, &migrated.sso_config_id).await?
+        else {
+            added.push(plan_application(app, &identities, strategies));
+            continue;
+        };
+
+        let (planned, unmatched) = match_drift_users(app.users, &identities, &created, strategies);
+        let current = sso_configs::list_sso_config_identity_ids(api_client, &sso_config.id).await?;
+        let assigned = db
+            .get_migrated_assignments(&sso_config.id, MigrationResourceKind::SsoConfigIdentity)
+            .await?;
+        let (to_add, to_remove) = diff_members(&planned, current, &assigned);
+
+        drifts.push(ApplicationDrift {
+            display_name: sso_config.display_name,
+            to_add,
+            to_remove,
+            sso_config_id: sso_config.id,
+            unmatched,
+            deactivated: false,
+        });
+    }
+
+    // Whatever is left was migrated but is no longer active in the source.
+    // Those with no identities left that fast-migrate assigned have already
+    // been reconciled.
+    for migrated in migrated {
+        let Some(sso_config) =
+            sso_configs::get_sso_config(api_client, &migrated.sso_config_id).await?
+        else {
+            continue;
+        };
+        let assigned = db
+            .get_migrated_assignments(&sso_config.id, MigrationResourceKind::SsoConfigIdentity)
+            .await?;
+        let to_remove: Vec<String> =
+            sso_configs::list_sso_config_identity_ids(api_client, &sso_config.id)
+                .await?
+                .into_iter()
+                .filter(|id| assigned.contains(id))
+                .collect();
+        if to_remove.is_empty() {
+            continue;
+        }
+        drifts.push(ApplicationDrift {
+            display_name: sso_config.display_name,
+            to_add: Vec::new(),
+            to_remove,
+            sso_config_id: sso_config.id,
+            unmatched: 0,
+            deactivated: true,
+        });
+    }
+
+    // Groups that were never mirrored are left to fast-migrate
+    let groups_service = GroupsService::from(api_client.clone());
+    let mut group_drifts = Vec::new();
+    let mut unmirrored = 0;
+    for group in groups {
+        let mirrored = match db
+            .get_migrated_group(&tenant.id, &realm.id, source, &group.id)
+            .await?
+        {
+            Some(migrated) => get_group(&groups_service, &migrated.group_id).await?,
+            None => None,
+        };
+        let Some(mirrored) = mirrored else {
+            unmirrored += 1;
+            continue;
+        };
+
+        let (planned, unmatched) =
+            match_drift_users(group.users, &identities, &created, strategies);
+        let current: Vec<String> = groups_service
+            .list_members(&mirrored.id, None)
+            .await?
+            .identities
+            .into_iter()
+            .map(|identity| identity.id)
+            .collect();
+        let added = db
+            .get_migrated_assignments(&mirrored.id, MigrationResourceKind::GroupMember)
+            .await?;
+        let (to_add, to_remove) = diff_members(&planned, current, &added);
+
+        group_drifts.push(GroupDrift {
+            display_name: mirrored.display_name,
+            to_add,
+            to_remove,
+            group_id: mirrored.id,
+            unmatched,
+        });
+    }
+
+    let describe = |identity_id: &str| {
+        identities
+            .iter()
+            .find(|identity| identity.id == identity_id)
+            .and_then(|identity| identity.traits.primary_email_address.clone())
+            .unwrap_or_else(|| identity_id.to_string())
+    };
+    let print_changes = |to_add: &[String], to_remove: &[String], unmatched: usize| {
+        for identity_id in to_add {
+            println!("  + {}", describe(identity_id));
+        }
+        for identity_id in to_remove {
+            println!("  - {}", describe(identity_id));
+        }
+        if unmatched > 0 {
+            println!(
+                "  {} users without a matching identity are not assigned",
+                unmatched
+            );
+        }
+    };
+
+    let mut in_sync = 0;
+    for drift in &drifts {
+        if drift.to_add.is_empty() && drift.to_remove.is_empty() && !drift.deactivated {
+            in_sync += 1;
+            continue;
+        }
+        if drift.deactivated {
+            println!(
+                "{}: no longer active in {}, {} identities to remove",
+                drift.display_name,
+                source,
+                drift.to_remove.len()
+            );
+        } else {
+            println!(
+                "{}: {} identities to add, {} to remove",
+                drift.display_name,
+                drift.to_add.len(),
+                drift.to_remove.len()
+            );
+        }
+        print_changes(&drift.to_add, &drift.to_remove, drift.unmatched);
+    }
+    for app in &added {
+        println!(
+            "{}: added in {} since migrating, {} identities to assign",
+            app.display_name,
+            source,
+            app.assignments.len()
+        );
+    }
+    let mut groups_in_sync = 0;
+    for drift in &group_drifts {
+        if drift.to_add.is_empty() && drift.to_remove.is_empty() {
+            groups_in_sync += 1;
+            continue;
+        }
+        println!(
+            "Group {}: {} members to add, {} to remove",
+            drift.display_name,
+            drift.to_add.len(),
+            drift.to_remove.len()
+        );
+        print_changes(&drift.to_add, &drift.to_remove, drift.unmatched);
+    }
+
+    let deactivated = drifts.iter().filter(|drift| drift.deactivated).count();
+    println!(
+        "{} applications in sync, {} drifted, {} added and {} deactivated",
+        in_sync,
+        drifts.len() - in_sync - deactivated,
+        added.len(),
+        deactivated
+    );
+    if !group_drifts.is_empty() || unmirrored > 0 {
+        println!(
+            "{} groups in sync, {} drifted and {} not mirrored",
+            groups_in_sync,
+            group_drifts.len() - groups_in_sync,
+            unmirrored
+        );
+    }
+    if in_sync == drifts.len() && added.is_empty() && groups_in_sync == group_drifts.len() {
+        return Ok(());
+    }
+    if !fix {
+        println!("Reconcile with `bi {} drift --fix`.", source);
+        return Ok(());
+    }
+
+    let run = start_run(api_client, &tenant.id, &realm.id, source).await?;
+    for drift in &drifts {
+        if !drift.to_add.is_empty() {
+            sso_configs::assign_identities_to_sso_config(
+                api_client,
+                &drift.sso_config_id,
+                &drift.to_add,
+            )
+            .await?;
+            db.add_migrated_assignments(
+                &drift.sso_config_id,
+                MigrationResourceKind::SsoConfigIdentity,
+                &drift.to_add,
+            )
+            .await?;
+            for identity_id in &drift.to_add {
+                record(
+                    api_client,
+                    &run.id,
+                    MigrationResourceKind::SsoConfigIdentity,
+                    identity_id,
+                    Some(&drift.sso_config_id),
+                )
+                .await?;
+            }
+        }
+        if !drift.to_remove.is_empty() {
+            sso_configs::unassign_identities_from_sso_config(
+                api_client,
+                &drift.sso_config_id,
+                &drift.to_remove,
+            )
+            .await?;
+            db.delete_migrated_assignments(
+                &drift.sso_config_id,
+                MigrationResourceKind::SsoConfigIdentity,
+                &drift.to_remove,
+            )
+            .await?;
+            for identity_id in &drift.to_remove {
+                record(
+                    api_client,
+                    &run.id,
+                    MigrationResourceKind::RemovedSsoConfigIdentity,
+                    identity_id,
+                    Some(&drift.sso_config_id),
+                )
+                .await?;
+            }
+        }
+    }
+    for drift in &group_drifts {
+        if !drift.to_add.is_empty() {
+            groups_service
+                .add_members(
+                    &drift.group_id,
+                    &AddMembersRequest {
+                        identity_ids: drift.to_add.clone(),
+                    },
+                )
+                .await?;
+            db.add_migrated_assignments(
+                &drift.group_id,
+                MigrationResourceKind::GroupMember,
+                &drift.to_add,
+            )
+            .await?;
+            for identity_id in &drift.to_add {
+                record(
+                    api_client,
+                    &run.id,
+                    MigrationResourceKind::GroupMember,
+                    identity_id,
+                    Some(&drift.group_id),
+                )
+                .await?;
+            }
+        }
+        if !drift.to_remove.is_empty() {
+            groups_service
+                .delete_members(
+                    &drift.group_id,
+                    &DeleteMembersRequest {
+                        identity_ids: drift.to_remove.clone(),
+                    },
+                )
+                .await?;
+            db.delete_migrated_assignments(
+                &drift.group_id,
+                MigrationResourceKind::GroupMember,
+                &drift.to_remove,
+            )
+            .await?;
+            for identity_id in &drift.to_remove {
+                record(
+                    api_client,
+                    &run.id,
+                    MigrationResourceKind::RemovedGroupMember,
+                    identity_id,
+                    Some(&drift.group_id),
+                )
+                .await?;
+            }
+        }
+    }
+
+    // Added applications are migrated the same way fast-migrate would
+    let plan = MigrationPlan {
+        source: source.to_string(),
+        created_at: Utc::now(),
+        tenant_id: tenant.id,
+        realm_id: realm.id,
+        applications: added,
+        groups: Vec::new(),
+        identities: Vec::new(),
+        untranslated: Vec::new(),
+        offered: Vec::new(),
+    };
+    let no_ids = HashMap::new();
+    for app in &plan.applications {
+        match plan
+            .apply_application(api_client, &run.id, app, &no_ids, &no_ids)
+            .await
+        {
+            Ok(applied) => println!(
+                "SSO config created for {} with {} assignments",
+                app.display_name, applied.added
+            ),
+            Err(err) => println!(
+                "Failed to migrate {} to an SSO config: {}",
+                app.display_name, err
+            ),
+        }
+    }
+
+    println!(
+        "Run ID: {}. Undo the changes made by this run with `bi {} fast-migrate rollback {}`.",
+        run.id, source, run.id
+    );
+
+    Ok(())
+}
+
+// ====================================
+// Rollback
+// ====================================
+
+// Delete every resource created by a fast-migrate run, newest first, and
+// restore the assignments and group members it removed. Resources that existed
+// before the run, and updates made to them, are left untouched.
+pub async fn rollback(api_client: &ApiClient, source: &str, run_id: &str) -> Result<(), BiError> {
+    let run = api_client
+        .db()?
+        .get_migration_run(run_id)
+        .await?
+        .filter(|run| run.source == source)
+        .ok_or_else(|| {
+            BiError::StringError(format!("No {} fast-migrate run {}", source, run_id))
+        })?;
+
+    if let Some(rolled_back_at) = run.rolled_back_at {
+        return Err(BiError::StringError(format!(
+            "Run {} was already rolled back at {}",
+            run.id,
+            format_timestamp(rolled_back_at)
+        )));
+    }
+
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
+        Some((t, r)) => (t, r),
+        None => {
+            return Err(BiError::StringError(
+                "No default tenant/realm set".to_string(),
+            ))
+        }
+    };
+    if tenant.id != run.tenant_id || realm.id != run.realm_id {
+        return Err(BiError::StringError(format!(
+            "Run {} targeted tenant {} realm {}, but the default is tenant {} realm {}",
+            run.id, run.tenant_id, run.realm_id, tenant.id, realm.id
+        )));
+    }
+
+    let groups = GroupsService::from(api_client.clone());
+    let resources = api_client
+        .db()?
+        .get_migration_run_resources(&run.id)
+        .await?;
+    for resource in resources.iter().rev() {
+        match resource.kind {
+            MigrationResourceKind::SsoConfig => {
+                sso_configs::delete_sso_config(api_client, &resource.resource_id).await?;
+                api_client
+                    .db()?
+                    .delete_migrated_applications_for_sso_config(&resource.resource_id)
+                    .await?;
+                api_client
+                    .db()?
+                    .delete_migrated_assignments_for_parent(&resource.resource_id)
+                    .await?;
+                println!("Deleted SSO config {}", resource.resource_id);
+            }
+            MigrationResourceKind::Identity => {
+                match IdentitiesService::from(api_client.clone())
+                    .delete_identity(&resource.resource_id)
+                    .await
+                {
+                    Ok(_) | Err(BiError::RequestError(StatusCode::NOT_FOUND, _)) => {}
+                    Err(err) => return Err(err),
+                }
+                println!("Deleted identity {}", resource.resource_id);
+            }
+            MigrationResourceKind::Group => {
+                match groups.delete_group(&resource.resource_id).await {
+                    Ok(_) | Err(BiError::RequestError(StatusCode::NOT_FOUND, _)) => {}
+                    Err(err) => return Err(err),
+                }
+                api_client
+                    .db()?
+                    .delete_migrated_groups_for_group(&resource.resource_id)
+                    .await?;
+                api_client
+                    .db()?
+                    .delete_migrated_assignments_for_parent(&resource.resource_id)
+                    .await?;
+                println!("Deleted group {}", resource.resource_id);
+            }
+            MigrationResourceKind::GroupMember => {
+                let group_id = resource.parent_id.as_deref().unwrap_or_default();
+                if get_group(&groups, group_id).await?.is_some() {
+                    groups
+                        .delete_members(
+                            group_id,
+                            &DeleteMembersRequest {
+                                identity_ids: vec![resource.resource_id.clone()],
+                            },
+                        )
+                        .await?;
+                }
+                api_client
+                    .db()?
+                    .delete_migrated_assignments(
+                        group_id,
+                        MigrationResourceKind::GroupMember,
+                        &[resource.resource_id.clone()],
+                    )
+                    .await?;
+                println!(
+                    "Removed identity {} from group {}",
+                    resource.resource_id, group_id
+                );
+            }
+            MigrationResourceKind::SsoConfigGroup => {
+                let sso_config_id = resource.parent_id.as_deref().unwrap_or_default();
+                if sso_configs::get_sso_config(api_client, sso_config_id)
+                    .await?
+                    .is_some()
+                {
+                    sso_configs::unassign_groups_from_sso_config(
+                        api_client,
+                        sso_config_id,
+                        &[resource.resource_id.clone()],
+                    )
+                    .await?;
+                }
+                api_client
+                    .db()?
+                    .delete_migrated_assignments(
+                        sso_config_id,
+                        MigrationResourceKind::SsoConfigGroup,
+                        &[resource.resource_id.clone()],
+                    )
+                    .await?;
+                println!(
+                    "Unassigned group {} from SSO config {}",
+                    resource.resource_id, sso_config_id
+                );
+            }
+            MigrationResourceKind::RemovedSsoConfigIdentity => {
+                let sso_config_id = resource.parent_id.as_deref().unwrap_or_default();
+                // The SSO config or identity may have been deleted since the run
+                if sso_configs::get_sso_config(api_client, sso_config_id)
+                    .await?
+                    .is_some()
+                {
+                    match sso_configs::assign_identities_to_sso_config(
+                        api_client,
+                        sso_config_id,
+                        &[resource.resource_id.clone()],
+                    )
+                    .await
+                    {
+                        Ok(_) => {
+                            api_client
+                                .db()?
+                                .add_migrated_assignments(
+                                    sso_config_id,
+                                    MigrationResourceKind::SsoConfigIdentity,
+                                    &[resource.resource_id.clone()],
+                                )
+                                .await?
+                        }
+                        Err(BiError::RequestError(StatusCode::NOT_FOUND, _)) => {}
+                        Err(err) => return Err(err),
+                    }
+                }
+                println!(
+                    "Reassigned identity {} to SSO config {}",
+                    resource.resource_id, sso_config_id
+                );
+            }
+            MigrationResourceKind::RemovedGroupMember => {
+                let group_id = resource.parent_id.as_deref().unwrap_or_default();
+                if get_group(&groups, group_id).await?.is_some() {
+                    match groups
+                        .add_members(
+                            group_id,
+                            &AddMembersRequest {
+                                identity_ids: vec![resource.resource_id.clone()],
+                            },
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            api_client
+                                .db()?
+                                .add_migrated_assignments(
+                                    group_id,
+                                    MigrationResourceKind::GroupMember,
+                                    &[resource.resource_id.clone()],
+                                )
+                                .await?
+                        }
+                        Err(BiError::RequestError(StatusCode::NOT_FOUND, _)) => {}
+                        Err(err) => return Err(err),
+                    }
+                }
+                println!(
+                    "Added identity {} back to group {}",
+                    resource.resource_id, group_id
+                );
+            }
+            MigrationResourceKind::SsoConfigIdentity => {
+                let sso_config_id = resource.parent_id.as_deref().unwrap_or_default();
+                // The SSO config may have been deleted since the run
+                if sso_configs::get_sso_config(api_client, sso_config_id)
+                    .await?
+                    .is_some()
+                {
+                    sso_configs::unassign_identities_from_sso_config(
+                        api_client,
+                        sso_config_id,
+                        &[resource.resource_id.clone()],
+                    )
+                    .await?;
+                }
+                api_client
+                    .db()?
+                    .delete_migrated_assignments(
+                        sso_config_id,
+                        MigrationResourceKind::SsoConfigIdentity,
+                        &[resource.resource_id.clone()],
+                    )
+                    .await?;
+                println!(
+                    "Unassigned identity {} from SSO config {}",
+                    resource.resource_id, sso_config_id
+                );
+            }
+        }
+    }
+
+    api_client
+        .db()?
+        .set_migration_run_rolled_back(&run.id, Utc::now().timestamp())
+        .await?;
+    println!("Rolled back run {} ({} resources)", run.id, resources.len());
+
+    Ok(())
+}
+
+// Print every fast-migrate run from a source, oldest first
+pub async fn list_runs(api_client: &ApiClient, source: &str) -> Result<(), BiError> {
+    let runs = api_client.db()?.get_migration_runs(source).await?;
+    if runs.is_empty() {
+        println!("No {} fast-migrate runs found.", source);
+        return Ok(());
+    }
+
+    for run in runs {
+        let resources = api_client
+            .db()?
+            .get_migration_run_resources(&run.id)
+            .await?;
+        let removed = resources
+            .iter()
+            .filter(|resource| {
+                matches!(
+                    resource.kind,
+                    MigrationResourceKind::RemovedSsoConfigIdentity
+                        | MigrationResourceKind::RemovedGroupMember
+                )
+            })
+            .count();
+        let status = match run.rolled_back_at {
+            Some(at) => format!("rolled back {}", format_timestamp(at)),
+            None if removed > 0 => format!(
+                "{} resources created, {} removed",
+                resources.len() - removed,
+                removed
+            ),
+            None => format!("{} resources created", resources.len()),
+        };
+        println!(
+            "{}  {}  tenant {} realm {}  {}",
+            run.id,
+            format_timestamp(run.started_at),
+            run.tenant_id,
+            run.realm_id,
+            status
+        );
+    }
+
+    Ok(())
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|at| at.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(id: &str, username: &str, email: &str, external_id: Option<&str>) -> Identity {
+        Identity {
+            id: id.to_string(),
+            realm_id: "r1".to_string(),
+            tenant_id: "t1".to_string(),
+            display_name: username.to_string(),
+            status: None,
+            create_time: String::new(),
+            update_time: String::new(),
+            traits: Traits {
+                r#type: Type::TraitsV0,
+                username: username.to_string(),
+                primary_email_address: Some(email.to_string()),
+                external_id: external_id.map(String::from),
+                family_name: None,
+                given_name: None,
+            },
+        }
+    }
+
+    fn user(id: &str, username: &str, email: &str) -> SourceUser {
+        SourceUser {
+            id: id.to_string(),
+            email: Some(email.to_string()),
+            username: Some(username.to_string()),
+            given_name: None,
+            family_name: None,
+            attribute: None,
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn strategies_compare_the_matching_fields() {
+        let alice = identity("i1", "alice", "alice@example.com", Some("00u1"));
+
+        let exact = user("00u1", "ALICE", "alice@example.com");
+        let shouting = user("00u9", "bob", "ALICE@example.com");
+        assert!(MatchStrategy::Email.matches(&exact, &alice));
+        assert!(!MatchStrategy::Email.matches(&shouting, &alice));
+        assert!(MatchStrategy::EmailCi.matches(&shouting, &alice));
+
+        assert!(MatchStrategy::Username.matches(&exact, &alice));
+        assert!(!MatchStrategy::Username.matches(&shouting, &alice));
+
+        assert!(MatchStrategy::ExternalId.matches(&exact, &alice));
+        assert!(!MatchStrategy::ExternalId.matches(&shouting, &alice));
+
+        let mut employee = user("00u9", "bob", "bob@example.com");
+        assert!(!MatchStrategy::CustomAttribute.matches(&employee, &alice));
+        employee.attribute = Some("00u1".to_string());
+        assert!(MatchStrategy::CustomAttribute.matches(&employee, &alice));
+    }
+
+    #[test]
+    fn ambiguous_matches_fall_through_to_the_next_strategy() {
+        let identities = vec![
+            identity("i1", "alice", "shared@example.com", None),
+            identity("i2", "alice.b", "shared@example.com", None),
+        ];
+        let alice = user("00u1", "Alice.B", "shared@example.com");
+
+        let (matched, strategy) = match_user(
+            &alice,
+            &identities,
+            &[MatchStrategy::Email, MatchStrategy::Username],
+        )
+        .unwrap();
+        assert_eq!(matched.id, "i2");
+        assert_eq!(strategy, MatchStrategy::Username);
+
+        assert!(match_user(&alice, &identities, &[MatchStrategy::Email]).is_none());
+    }
+
+    #[test]
+    fn unmatched_users_are_kept_for_the_report() {
+        let identities = vec![identity("i1", "alice", "alice@example.com", None)];
+        let users = vec![
+            user("00u1", "alice", "alice@example.com"),
+            user("00u2", "bob", "bob@example.com"),
+        ];
+
+        let (assignments, unmatched) = match_users(users, &identities, &[MatchStrategy::Email]);
+        assert_eq!(assignments.len(), 1);
+        assert_eq!(assignments[0].identity_id, "i1");
+        assert_eq!(assignments[0].source_user_id, "00u1");
+        assert_eq!(assignments[0].matched_by, Some(MatchStrategy::Email));
+        assert_eq!(unmatched.len(), 1);
+        assert_eq!(unmatched[0].id, "00u2");
+    }
+
+    #[test]
+    fn planned_applications_keep_their_source_settings() {
+        let identities = vec![identity("i1", "alice", "alice@example.com", None)];
+        let app = SourceApplication {
+            id: "0oa1".to_string(),
+            name: "Wiki".to_string(),
+            login_link: "https://okta.example.com/home/wiki/0oa1".to_string(),
+            icon: None,
+            users: vec![
+                user("00u1", "alice", "alice@example.com"),
+                user("00u2", "bob", "bob@example.com"),
+            ],
+            groups: ids(&["00g1"]),
+            federation: None,
+            untranslated: None,
+        };
+
+        let planned = plan_application(app, &identities, &[MatchStrategy::Email]);
+        assert_eq!(planned.source_id, "0oa1");
+        assert_eq!(planned.display_name, "Wiki");
+        assert_eq!(
+            planned.login_link,
+            "https://okta.example.com/home/wiki/0oa1"
+        );
+        assert_eq!(planned.groups, ids(&["00g1"]));
+        assert_eq!(planned.assignments[0].identity_id, "i1");
+        assert_eq!(planned.unmatched[0].id, "00u2");
+    }
+
+    #[test]
+    fn drift_matches_created_identities_by_external_id() {
+        let identities = vec![
+            identity("i1", "alice", "alice@example.com", None),
+            identity("i2", "bob", "bob@corp.example.com", Some("00u2")),
+        ];
+        let users = vec![
+            user("00u1", "alice", "alice@example.com"),
+            user("00u2", "bob", "bob@example.com"),
+            user("00u3", "carol", "carol@example.com"),
+        ];
+
+        let (planned, unmatched) =
+            match_drift_users(users, &identities, &ids(&["i2"]), &[MatchStrategy::Email]);
+        assert_eq!(planned, ids(&["i1", "i2"]));
+        assert_eq!(unmatched, 1);
+    }
+
+    #[test]
+    fn diff_only_removes_members_fast_migrate_added() {
+        let (to_add, to_remove) =
+            diff_members(&ids(&["a", "b"]), ids(&["b", "c", "d"]), &ids(&["b", "c"]));
+        assert_eq!(to_add, ids(&["a"]));
+        assert_eq!(to_remove, ids(&["c"]));
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_and_neutralized() {
+        assert_eq!(csv_field("alice@example.com"), "alice@example.com");
+        assert_eq!(csv_field("Doe, Jane"), "\"Doe, Jane\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+
+        assert_eq!(csv_field("=SUM(A1:A9)"), "'=SUM(A1:A9)");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("@cmd"), "'@cmd");
+        assert_eq!(
+            csv_field("=HYPERLINK(\"x\",1)"),
+            "\"'=HYPERLINK(\"\"x\"\",1)\""
+        );
+    }
+}
@@ -3,6 +3,7 @@ pub mod command;
 pub mod enrollment;
 pub mod groups;
 pub mod identities;
+pub mod migration;
 pub mod resource_servers;
 pub mod roles;
 pub mod sso_configs;
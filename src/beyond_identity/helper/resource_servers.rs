@@ -15,7 +15,7 @@ pub struct ResourceServer {
 pub async fn fetch_beyond_identity_resource_servers(
     api_client: &ApiClient,
 ) -> Result<Vec<ResourceServer>, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -8,7 +8,7 @@ pub async fn fetch_role_memberships(
     identity_id: &str,
     resource_server_id: &str,
 ) -> Result<Vec<Role>, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -60,7 +60,7 @@ pub async fn fetch_beyond_identity_roles(
     api_client: &ApiClient,
     resource_server_id: &str,
 ) -> Result<Vec<Role>, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
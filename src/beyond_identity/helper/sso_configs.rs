@@ -1,7 +1,7 @@
 use crate::beyond_identity::api::common::api_client::ApiClient;
-use crate::beyond_identity::api::identities::types::Identity;
 use crate::common::error::BiError;
 
+use http::{Method, StatusCode};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -12,35 +12,69 @@ pub struct DeleteSsoConfig {
     display_name: String,
 }
 
+// An SSO config of any type. The payload is keyed by its type, e.g.
+// `{"Bookmark": {...}}`.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct SsoConfigBookmark {
-    id: String,
-    display_name: String,
+pub struct SsoConfig {
+    pub id: String,
+    pub display_name: String,
+    #[serde(default)]
     is_migrated: bool,
-    payload: SsoConfigPayloadBookmark,
+    payload: serde_json::Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SsoConfigPayloadBookmark {
-    #[serde(rename = "Bookmark")]
-    bookmark: Bookmark,
+impl SsoConfig {
+    // Client ID and secret of a generic OIDC SSO config, which its relying
+    // party needs to sign users in through Beyond Identity
+    pub fn oidc_client(&self) -> Option<(String, Option<String>)> {
+        let config = self.payload.get("GenericOidc")?;
+        let client_id = config.get("client_id")?.as_str()?.to_string();
+        let client_secret = config
+            .get("client_secret")
+            .and_then(serde_json::Value::as_str)
+            .map(String::from);
+        Some((client_id, client_secret))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Bookmark {
-    login_link: String,
-    icon: String,
-    is_tile_visible: bool,
-    application_tile_id: String,
+// Payload of an SSO config created or updated by fast-migrate
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SsoConfigPayload {
+    // Tile that redirects to the source identity provider
+    Bookmark {
+        login_link: String,
+        icon: Option<String>,
+        is_tile_visible: bool,
+    },
+    // OpenID Connect relying party that Beyond Identity signs users in to directly
+    GenericOidc {
+        redirect_uris: Vec<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        post_logout_redirect_uris: Vec<String>,
+        confidentiality: String,
+        login_link: String,
+        icon: Option<String>,
+        is_tile_visible: bool,
+    },
+}
+
+impl SsoConfigPayload {
+    pub fn bookmark(login_link: String, icon: Option<String>) -> Self {
+        Self::Bookmark {
+            login_link,
+            icon,
+            is_tile_visible: true,
+        }
+    }
 }
 
 pub async fn create_sso_config(
     api_client: &ApiClient,
     name: String,
-    login_link: String,
-    icon_url: Option<String>,
-) -> Result<SsoConfigBookmark, BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
+    payload: &SsoConfigPayload,
+) -> Result<SsoConfig, BiError> {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
         Some((t, r)) => (t, r),
         None => {
             return Err(BiError::StringError(
@@ -60,12 +94,7 @@ pub async fn create_sso_config(
         "sso_config": {
             "display_name": name,
             "is_migrated": true,
-            "payload": {
-                "type": "bookmark",
-                "login_link": login_link,
-                "icon": icon_url,
-                "is_tile_visible": true
-            }
+            "payload": payload
         }
     });
 
@@ -91,11 +120,11 @@ pub async fn create_sso_config(
         return Err(BiError::RequestError(status, response_text));
     }
 
-    let sso_config: SsoConfigBookmark = serde_json::from_str(&response_text)?;
+    let sso_config: SsoConfig = serde_json::from_str(&response_text)?;
     Ok(sso_config)
 }
 
-fn sanitize_label(label: &str) -> String {
+pub fn sanitize_label(label: &str) -> String {
     let re = Regex::new(r"[^a-zA-Z\s]").unwrap();
     let sanitized_label: String = re.replace_all(label, "").to_string();
     let trimmed_label = sanitized_label.trim();
@@ -108,29 +137,82 @@ fn sanitize_label(label: &str) -> String {
 
 pub async fn assign_identities_to_sso_config(
     api_client: &ApiClient,
-    sso_config: &SsoConfigBookmark,
-    identities: &[Identity],
+    sso_config_id: &str,
+    identity_ids: &[String],
 ) -> Result<(), BiError> {
-    let (tenant, realm) = match api_client.db.get_default_tenant_and_realm().await? {
-        Some((t, r)) => (t, r),
-        None => {
-            return Err(BiError::StringError(
-                "No default tenant/realm set".to_string(),
-            ))
-        }
-    };
+    update_sso_config_members(
+        api_client,
+        sso_config_id,
+        "addIdentities",
+        "identity_ids",
+        identity_ids,
+    )
+    .await
+}
 
+pub async fn unassign_identities_from_sso_config(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+    identity_ids: &[String],
+) -> Result<(), BiError> {
+    update_sso_config_members(
+        api_client,
+        sso_config_id,
+        "deleteIdentities",
+        "identity_ids",
+        identity_ids,
+    )
+    .await
+}
+
+pub async fn assign_groups_to_sso_config(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+    group_ids: &[String],
+) -> Result<(), BiError> {
+    update_sso_config_members(
+        api_client,
+        sso_config_id,
+        "addGroups",
+        "group_ids",
+        group_ids,
+    )
+    .await
+}
+
+pub async fn unassign_groups_from_sso_config(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+    group_ids: &[String],
+) -> Result<(), BiError> {
+    update_sso_config_members(
+        api_client,
+        sso_config_id,
+        "deleteGroups",
+        "group_ids",
+        group_ids,
+    )
+    .await
+}
+
+// Add or remove identities or groups with one of the SSO config custom
+// methods, e.g. `:addIdentities` with the IDs under `identity_ids`
+async fn update_sso_config_members(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+    method: &str,
+    field: &str,
+    ids: &[String],
+) -> Result<(), BiError> {
     let url = format!(
-        "{}/v1/tenants/{}/realms/{}/sso-configs/{}:addIdentities",
-        realm.api_base_url, tenant.id, realm.id, sso_config.id
+        "{}/{}:{}",
+        sso_configs_url(api_client).await?,
+        sso_config_id,
+        method
     );
 
-    let identity_ids: Vec<String> = identities
-        .iter()
-        .map(|identity| identity.id.clone())
-        .collect();
     let payload = json!({
-        "identity_ids": identity_ids,
+        field: ids,
     });
 
     let response = api_client
@@ -157,3 +239,159 @@ pub async fn assign_identities_to_sso_config(
 
     Ok(())
 }
+
+#[derive(Debug, Deserialize)]
+struct Member {
+    id: String,
+}
+
+// Get the IDs of all identities directly assigned to an SSO config
+pub async fn list_sso_config_identity_ids(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+) -> Result<Vec<String>, BiError> {
+    list_sso_config_member_ids(api_client, sso_config_id, "listIdentities").await
+}
+
+// Get the IDs of all groups assigned to an SSO config
+pub async fn list_sso_config_group_ids(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+) -> Result<Vec<String>, BiError> {
+    list_sso_config_member_ids(api_client, sso_config_id, "listGroups").await
+}
+
+// Page through one of the SSO config list methods, collecting the IDs of the
+// listed resources
+async fn list_sso_config_member_ids(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+    method: &str,
+) -> Result<Vec<String>, BiError> {
+    let url = format!(
+        "{}/{}:{}",
+        sso_configs_url(api_client).await?,
+        sso_config_id,
+        method
+    );
+
+    let (members, _) = api_client
+        .send_request_paginated::<(), Member>(Method::GET, &url, None, None, None)
+        .await?;
+
+    Ok(members.into_iter().map(|member| member.id).collect())
+}
+
+// Get an SSO config, or `None` if it no longer exists
+pub async fn get_sso_config(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+) -> Result<Option<SsoConfig>, BiError> {
+    let url = format!("{}/{}", sso_configs_url(api_client).await?, sso_config_id);
+
+    let response = api_client.client.get(&url).send().await?;
+
+    let status = response.status();
+    let response_text = response.text().await?;
+
+    log::debug!(
+        "{} response status: {} and text: {}",
+        url,
+        status,
+        response_text
+    );
+
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(BiError::RequestError(status, response_text));
+    }
+
+    let sso_config: SsoConfig = serde_json::from_str(&response_text)?;
+    Ok(Some(sso_config))
+}
+
+pub async fn update_sso_config(
+    api_client: &ApiClient,
+    sso_config_id: &str,
+    name: String,
+    payload: &SsoConfigPayload,
+) -> Result<SsoConfig, BiError> {
+    let url = format!("{}/{}", sso_configs_url(api_client).await?, sso_config_id);
+
+    let name = sanitize_label(&name);
+
+    let payload = json!({
+        "sso_config": {
+            "display_name": name,
+            "payload": payload
+        }
+    });
+
+    let response = api_client
+        .client
+        .patch(&url)
+        .header("Content-Type", "application/json")
+        .json(&payload)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let response_text = response.text().await?;
+
+    log::debug!(
+        "{} response status: {} and text: {}",
+        url,
+        status,
+        response_text
+    );
+
+    if !status.is_success() {
+        return Err(BiError::RequestError(status, response_text));
+    }
+
+    let sso_config: SsoConfig = serde_json::from_str(&response_text)?;
+    Ok(sso_config)
+}
+
+// Delete an SSO config along with its assignments. Deleting an SSO config
+// that no longer exists is not an error.
+pub async fn delete_sso_config(api_client: &ApiClient, sso_config_id: &str) -> Result<(), BiError> {
+    let url = format!("{}/{}", sso_configs_url(api_client).await?, sso_config_id);
+
+    let response = api_client.client.delete(&url).send().await?;
+
+    let status = response.status();
+    let response_text = response.text().await?;
+
+    log::debug!(
+        "{} response status: {} and text: {}",
+        url,
+        status,
+        response_text
+    );
+
+    if !status.is_success() && status != StatusCode::NOT_FOUND {
+        return Err(BiError::RequestError(status, response_text));
+    }
+
+    Ok(())
+}
+
+// URL of the SSO configs collection in the default tenant and realm
+async fn sso_configs_url(api_client: &ApiClient) -> Result<String, BiError> {
+    let (tenant, realm) = match api_client.db()?.get_default_tenant_and_realm().await? {
+        Some((t, r)) => (t, r),
+        None => {
+            return Err(BiError::StringError(
+                "No default tenant/realm set".to_string(),
+            ))
+        }
+    };
+
+    Ok(format!(
+        "{}/v1/tenants/{}/realms/{}/sso-configs",
+        realm.api_base_url, tenant.id, realm.id
+    ))
+}
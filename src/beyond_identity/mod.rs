@@ -1,2 +1,5 @@
 pub mod api;
+#[cfg(feature = "cli")]
 pub mod helper;
+#[cfg(feature = "cli")]
+pub mod openapi;
@@ -0,0 +1,329 @@
+use super::spec::{Kind, Location, Operation, Spec};
+
+use crate::beyond_identity::api::common::api_client::ApiClient;
+use crate::beyond_identity::api::common::serialize::output;
+use crate::beyond_identity::api::raw::command::read_data;
+use crate::common::command::Executable;
+use crate::common::error::BiError;
+
+use async_trait::async_trait;
+use clap::error::ErrorKind;
+use clap::{Arg, ArgAction, ArgMatches, Args, Command};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+// Path parameters filled in from the configured tenant and realm.
+const TENANT_ID: &str = "tenant_id";
+const REALM_ID: &str = "realm_id";
+
+// Flag used to pass a full JSON request body.
+const BODY: &str = "body";
+
+// ====================================
+// OpenAPI Operations
+// ====================================
+
+#[derive(Args, Debug, Clone)]
+pub struct Openapi {
+    /// Path to the Beyond Identity OpenAPI document (JSON or YAML). Defaults to
+    /// `openapi.json`, `openapi.yaml` or `openapi.yml` in the config directory. No spec is
+    /// bundled with the CLI, so one of these is required.
+    #[clap(long, env = "BI_OPENAPI_SPEC")]
+    spec: Option<PathBuf>,
+
+    /// Tag, operation and operation flags, e.g. `identities list-identities --page-size 5`.
+    /// Use `--help` after a tag or operation to list what is available.
+    #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+#[async_trait]
+impl Executable for Openapi {
+    async fn execute(&self) -> Result<(), BiError> {
+        let spec = Spec::load(self.spec.as_deref())?;
+        let operations = spec.operations();
+
+        let command = build_command(&spec, &operations);
+        let matches = match command
+            .try_get_matches_from(std::iter::once("openapi".to_string()).chain(self.args.clone()))
+        {
+            Ok(matches) => matches,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::DisplayHelp
+                        | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+                        | ErrorKind::DisplayVersion
+                ) =>
+            {
+                return e.print().map_err(|e| BiError::StringError(e.to_string()));
+            }
+            Err(e) => return Err(BiError::StringError(e.render().to_string())),
+        };
+
+        let (tag, tag_matches) = matches.subcommand().expect("Subcommand is required");
+        let (name, operation_matches) = tag_matches.subcommand().expect("Subcommand is required");
+        let operation = operations
+            .iter()
+            .find(|o| o.tag == tag && o.name == name)
+            .expect("Operation exists for every subcommand");
+
+        execute_operation(operation, operation_matches).await
+    }
+}
+
+// Build a command tree of `<tag> <operation>` from the operations in the spec
+fn build_command(spec: &Spec, operations: &[Operation]) -> Command {
+    let mut command = Command::new("openapi")
+        .bin_name("bi openapi")
+        .about(
+            spec.title()
+                .map(String::from)
+                .unwrap_or_else(|| "Beyond Identity API".to_string()),
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true);
+
+    let mut tags: Vec<&str> = operations.iter().map(|o| o.tag.as_str()).collect();
+    tags.dedup();
+
+    for tag in tags {
+        let mut tag_command = Command::new(tag.to_string())
+            .subcommand_required(true)
+            .arg_required_else_help(true);
+        for operation in operations.iter().filter(|o| o.tag == tag) {
+            tag_command = tag_command.subcommand(build_operation_command(operation));
+        }
+        command = command.subcommand(tag_command);
+    }
+
+    command
+}
+
+fn build_operation_command(operation: &Operation) -> Command {
+    let mut command = Command::new(operation.name.clone()).about(format!(
+        "{} {}{}",
+        operation.method,
+        operation.path,
+        operation
+            .summary
+            .as_ref()
+            .map(|s| format!(" - {}", s))
+            .unwrap_or_default()
+    ));
+
+    let mut ids: Vec<String> = Vec::new();
+
+    for parameter in &operation.parameters {
+        let id = flag_name(&[parameter.name.clone()]);
+        let default = match (parameter.location, parameter.name.as_str()) {
+            (Location::Path, TENANT_ID) => Some("Defaults to the configured tenant."),
+            (Location::Path, REALM_ID) => Some("Defaults to the configured realm."),
+            _ => None,
+        };
+        let help = [parameter.description.as_deref(), default]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        command = command.arg(flag(
+            &id,
+            parameter.kind,
+            parameter.required && default.is_none(),
+            help,
+        ));
+        ids.push(id);
+    }
+
+    if let Some(body) = &operation.body {
+        command = command.arg(
+            Arg::new(BODY)
+                .long(BODY)
+                .value_name("JSON")
+                .required(body.required && body.fields.is_empty())
+                .help("JSON request body, `@file.json` or `@-` for standard input. Other body flags are applied on top of it."),
+        );
+        ids.push(BODY.to_string());
+
+        for field in &body.fields {
+            let id = flag_name(&field.path);
+            // Parameters take precedence over body fields with the same name
+            if ids.contains(&id) {
+                continue;
+            }
+            let mut arg = flag(
+                &id,
+                field.kind,
+                false,
+                field.description.clone().unwrap_or_default(),
+            );
+            // Required fields may also be supplied through --body
+            if field.required {
+                arg = arg.required_unless_present(BODY);
+            }
+            command = command.arg(arg);
+            ids.push(id);
+        }
+    }
+
+    command
+}
+
+fn flag(id: &str, kind: Kind, required: bool, help: String) -> Arg {
+    let arg = Arg::new(id.to_string())
+        .long(id.to_string())
+        .required(required)
+        .help(help);
+    match kind {
+        Kind::Array => arg.action(ArgAction::Append).value_delimiter(','),
+        Kind::Boolean => arg.value_parser(["true", "false"]),
+        Kind::Integer | Kind::Number => arg.value_name("NUMBER"),
+        Kind::Object => arg.value_name("JSON"),
+        Kind::String => arg,
+    }
+}
+
+// `identity.display_name` -> `identity.display-name`
+fn flag_name(path: &[String]) -> String {
+    path.iter()
+        .map(|segment| segment.replace('_', "-"))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+async fn execute_operation(operation: &Operation, matches: &ArgMatches) -> Result<(), BiError> {
+    let api_client = ApiClient::new(None, None).await?;
+    let defaults = api_client.db()?.get_default_tenant_and_realm().await?;
+
+    // Path parameters are substituted segment by segment with their raw
+    // values so that the URL builder percent-encodes each of them once.
+    let mut segments: Vec<String> = operation
+        .path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+    let mut builder = api_client.builder().await?.api();
+    let mut query: Vec<(String, String)> = Vec::new();
+
+    for parameter in &operation.parameters {
+        let values = values(matches, &flag_name(&[parameter.name.clone()]));
+        match parameter.location {
+            Location::Path => {
+                let value = match (values.first(), parameter.name.as_str(), &defaults) {
+                    (Some(value), _, _) => value.clone(),
+                    (None, TENANT_ID, Some((tenant, _))) => tenant.id.clone(),
+                    (None, REALM_ID, Some((_, realm))) => realm.id.clone(),
+                    _ => {
+                        return Err(BiError::StringError(format!(
+                            "Missing value for path parameter {}",
+                            parameter.name
+                        )))
+                    }
+                };
+                let placeholder = format!("{{{}}}", parameter.name);
+                for segment in segments.iter_mut() {
+                    *segment = segment.replace(&placeholder, &value);
+                }
+            }
+            Location::Query => {
+                query.extend(values.into_iter().map(|v| (parameter.name.clone(), v)));
+            }
+        }
+    }
+
+    builder = builder.add_path(segments.iter().map(String::as_str).collect());
+    for (key, value) in &query {
+        builder = builder.add_query_param(key, Some(value));
+    }
+    let url = builder.to_string()?;
+
+    let body = match &operation.body {
+        Some(request_body) => {
+            let mut body = match matches.get_one::<String>(BODY) {
+                Some(data) => read_data(data)?,
+                None => Value::Object(Map::new()),
+            };
+            for field in &request_body.fields {
+                let values = values(matches, &flag_name(&field.path));
+                if values.is_empty() {
+                    continue;
+                }
+                insert(
+                    &mut body,
+                    &field.path,
+                    convert(&field.path, field.kind, values)?,
+                )?;
+            }
+            Some(body)
+        }
+        None => None,
+    };
+
+    output(api_client.send_request::<Value, Value>(operation.method.clone(), &url, body.as_ref()))
+        .await
+}
+
+fn values(matches: &ArgMatches, id: &str) -> Vec<String> {
+    matches
+        .try_get_many::<String>(id)
+        .ok()
+        .flatten()
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default()
+}
+
+// Convert flag values into the JSON type declared by the schema
+fn convert(path: &[String], kind: Kind, values: Vec<String>) -> Result<Value, BiError> {
+    let invalid = |value: &str| {
+        BiError::StringError(format!(
+            "Invalid value for --{}: {}",
+            flag_name(path),
+            value
+        ))
+    };
+
+    match kind {
+        Kind::Array => Ok(Value::Array(
+            values
+                .into_iter()
+                .map(|v| serde_json::from_str(&v).unwrap_or(Value::String(v)))
+                .collect(),
+        )),
+        _ => {
+            let value = values.into_iter().next().unwrap_or_default();
+            match kind {
+                Kind::String => Ok(Value::String(value)),
+                Kind::Boolean => Ok(Value::Bool(value == "true")),
+                Kind::Integer => value
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .map_err(|_| invalid(&value)),
+                Kind::Number => value
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(|n| serde_json::Number::from_f64(n).map(Value::Number))
+                    .ok_or_else(|| invalid(&value)),
+                _ => serde_json::from_str(&value).map_err(|_| invalid(&value)),
+            }
+        }
+    }
+}
+
+// Insert a value at a nested path, creating intermediate objects as needed
+fn insert(body: &mut Value, path: &[String], value: Value) -> Result<(), BiError> {
+    let (last, parents) = path.split_last().expect("Body fields have a path");
+    let mut current = body;
+    for segment in parents {
+        current = current
+            .as_object_mut()
+            .ok_or_else(|| BiError::StringError("Request body must be a JSON object".to_string()))?
+            .entry(segment.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    current
+        .as_object_mut()
+        .ok_or_else(|| BiError::StringError("Request body must be a JSON object".to_string()))?
+        .insert(last.clone(), value);
+    Ok(())
+}
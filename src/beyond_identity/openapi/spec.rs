@@ -0,0 +1,340 @@
+use crate::common::database::Database;
+use crate::common::error::BiError;
+
+use convert_case::{Case, Casing};
+use http::Method;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+// File names looked up in the config directory when no spec is given.
+const SPEC_FILE_NAMES: &[&str] = &["openapi.json", "openapi.yaml", "openapi.yml"];
+
+// Request body properties are flattened into flags up to this depth. Deeper
+// values can still be supplied through `--body`.
+const MAX_BODY_DEPTH: usize = 3;
+
+// Tag used for operations that do not declare one.
+const DEFAULT_TAG: &str = "default";
+
+pub struct Spec {
+    document: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub tag: String,
+    pub name: String,
+    pub method: Method,
+    pub path: String,
+    pub summary: Option<String>,
+    pub parameters: Vec<Parameter>,
+    pub body: Option<RequestBody>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Path,
+    Query,
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    pub location: Location,
+    pub required: bool,
+    pub description: Option<String>,
+    pub kind: Kind,
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestBody {
+    pub required: bool,
+    pub fields: Vec<BodyField>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BodyField {
+    pub path: Vec<String>,
+    pub required: bool,
+    pub description: Option<String>,
+    pub kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array,
+    Object,
+}
+
+impl Kind {
+    fn from_schema(schema: &Value) -> Self {
+        match schema.get("type").and_then(Value::as_str) {
+            Some("integer") => Kind::Integer,
+            Some("number") => Kind::Number,
+            Some("boolean") => Kind::Boolean,
+            Some("array") => Kind::Array,
+            Some("object") => Kind::Object,
+            Some(_) => Kind::String,
+            None if schema.get("properties").is_some() => Kind::Object,
+            None => Kind::String,
+        }
+    }
+}
+
+impl Spec {
+    // Load the spec from the given path, falling back to the first
+    // `openapi.{json,yaml,yml}` found in the config directory
+    pub fn load(path: Option<&Path>) -> Result<Self, BiError> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path()? {
+                Some(path) => path,
+                None => {
+                    return Err(BiError::StringError(format!(
+                        "No OpenAPI spec found. Pass --spec or save the Beyond Identity OpenAPI document as {} in {}",
+                        SPEC_FILE_NAMES[0],
+                        Database::config_dir()?.display()
+                    )))
+                }
+            },
+        };
+
+        let contents = std::fs::read_to_string(&path).map_err(|e| {
+            BiError::StringError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+
+        Self::parse(&contents, &path.display().to_string())
+    }
+
+    fn parse(contents: &str, source: &str) -> Result<Self, BiError> {
+        let document: Value = match serde_json::from_str(contents) {
+            Ok(document) => document,
+            Err(_) => serde_yaml::from_str(contents).map_err(|e| {
+                BiError::StringError(format!("Failed to parse {} as JSON or YAML: {}", source, e))
+            })?,
+        };
+
+        if document.get("openapi").is_none() || document.get("paths").is_none() {
+            return Err(BiError::StringError(format!(
+                "{} is not an OpenAPI 3 document",
+                source
+            )));
+        }
+
+        Ok(Self { document })
+    }
+
+    fn default_path() -> Result<Option<PathBuf>, BiError> {
+        let config_dir = Database::config_dir()?;
+        Ok(SPEC_FILE_NAMES
+            .iter()
+            .map(|name| config_dir.join(name))
+            .find(|path| path.exists()))
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.document.pointer("/info/title").and_then(Value::as_str)
+    }
+
+    // Every operation in the document, ordered by tag and then by name
+    pub fn operations(&self) -> Vec<Operation> {
+        let mut operations = Vec::new();
+
+        let paths = match self.document.get("paths").and_then(Value::as_object) {
+            Some(paths) => paths,
+            None => return operations,
+        };
+
+        for (path, item) in paths {
+            let item = self.resolve(item);
+            let shared_parameters = item.get("parameters");
+
+            for method in ["get", "put", "post", "delete", "patch", "head", "options"] {
+                let operation = match item.get(method) {
+                    Some(operation) => self.resolve(operation),
+                    None => continue,
+                };
+
+                let method = Method::from_bytes(method.to_uppercase().as_bytes())
+                    .expect("Valid HTTP method");
+
+                let tag = operation
+                    .pointer("/tags/0")
+                    .and_then(Value::as_str)
+                    .unwrap_or(DEFAULT_TAG)
+                    .to_case(Case::Kebab);
+
+                let name = match operation.get("operationId").and_then(Value::as_str) {
+                    Some(id) => id.to_case(Case::Kebab),
+                    None => format!("{} {}", method.as_str(), path).to_case(Case::Kebab),
+                };
+
+                let summary = operation
+                    .get("summary")
+                    .or_else(|| operation.get("description"))
+                    .and_then(Value::as_str)
+                    .map(|s| s.trim().to_string());
+
+                // Operation level parameters override path level ones
+                let mut parameters: Vec<Parameter> = Vec::new();
+                for parameter in [shared_parameters, operation.get("parameters")]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_array)
+                    .flatten()
+                    .filter_map(|p| self.parameter(p))
+                {
+                    parameters.retain(|p| p.name != parameter.name);
+                    parameters.push(parameter);
+                }
+
+                let body = operation
+                    .get("requestBody")
+                    .map(|body| self.request_body(body));
+
+                operations.push(Operation {
+                    tag,
+                    name,
+                    method,
+                    path: path.clone(),
+                    summary,
+                    parameters,
+                    body,
+                });
+            }
+        }
+
+        operations.sort_by(|a, b| (&a.tag, &a.name).cmp(&(&b.tag, &b.name)));
+        operations
+    }
+
+    fn parameter(&self, parameter: &Value) -> Option<Parameter> {
+        let parameter = self.resolve(parameter);
+        let location = match parameter.get("in").and_then(Value::as_str) {
+            Some("path") => Location::Path,
+            Some("query") => Location::Query,
+            // Header and cookie parameters are handled by the client
+            _ => return None,
+        };
+
+        Some(Parameter {
+            name: parameter.get("name")?.as_str()?.to_string(),
+            location,
+            required: location == Location::Path
+                || parameter
+                    .get("required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            description: description(parameter),
+            kind: parameter
+                .get("schema")
+                .map(|s| Kind::from_schema(self.resolve(s)))
+                .unwrap_or(Kind::String),
+        })
+    }
+
+    fn request_body(&self, body: &Value) -> RequestBody {
+        let body = self.resolve(body);
+        let mut fields = Vec::new();
+
+        if let Some(schema) = body.pointer("/content/application~1json/schema") {
+            self.collect_fields(schema, &mut Vec::new(), true, &mut fields);
+        }
+
+        RequestBody {
+            required: body
+                .get("required")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            fields,
+        }
+    }
+
+    // Flatten the properties of an object schema into body fields, descending
+    // into nested objects so that e.g. `{"identity": {"display_name": ..}}`
+    // becomes the field `identity.display_name`.
+    fn collect_fields(
+        &self,
+        schema: &Value,
+        path: &mut Vec<String>,
+        required: bool,
+        fields: &mut Vec<BodyField>,
+    ) {
+        let schema = self.resolve(schema);
+        let (properties, required_properties) = self.properties(schema);
+
+        for (name, property) in properties {
+            let property = self.resolve(property);
+            let property_required = required && required_properties.contains(&name);
+            path.push(name);
+
+            let kind = Kind::from_schema(property);
+            let (nested, _) = self.properties(property);
+            if kind == Kind::Object && !nested.is_empty() && path.len() < MAX_BODY_DEPTH {
+                self.collect_fields(property, path, property_required, fields);
+            } else if property.get("readOnly").and_then(Value::as_bool) != Some(true) {
+                fields.push(BodyField {
+                    path: path.clone(),
+                    required: property_required,
+                    description: description(property),
+                    kind,
+                });
+            }
+
+            path.pop();
+        }
+    }
+
+    // The properties of an object schema and the names of the required ones,
+    // merging any `allOf` sub-schemas.
+    fn properties<'a>(&'a self, schema: &'a Value) -> (Vec<(String, &'a Value)>, Vec<String>) {
+        let mut properties = Vec::new();
+        let mut required = Vec::new();
+
+        if let Some(object) = schema.get("properties").and_then(Value::as_object) {
+            properties.extend(object.iter().map(|(k, v)| (k.clone(), v)));
+        }
+        if let Some(names) = schema.get("required").and_then(Value::as_array) {
+            required.extend(names.iter().filter_map(Value::as_str).map(String::from));
+        }
+        if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+            for sub_schema in all_of {
+                let (sub_properties, sub_required) = self.properties(self.resolve(sub_schema));
+                properties.extend(sub_properties);
+                required.extend(sub_required);
+            }
+        }
+
+        (properties, required)
+    }
+
+    // Follow local `$ref` pointers such as `#/components/schemas/Identity`.
+    // Unresolvable references are returned unchanged.
+    fn resolve<'a>(&'a self, value: &'a Value) -> &'a Value {
+        let mut current = value;
+        // Guard against reference cycles
+        for _ in 0..32 {
+            match current
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|r| r.strip_prefix('#'))
+                .and_then(|pointer| self.document.pointer(pointer))
+            {
+                Some(target) => current = target,
+                None => break,
+            }
+        }
+        current
+    }
+}
+
+fn description(value: &Value) -> Option<String> {
+    value
+        .get("description")
+        .and_then(Value::as_str)
+        .map(|s| s.trim().to_string())
+}
@@ -0,0 +1,89 @@
+use crate::ai::command::AiCommands;
+use crate::beyond_identity::api::common::command::BeyondIdentityApiCommands;
+use crate::beyond_identity::helper::command::BeyondIdentityHelperCommands;
+use crate::beyond_identity::openapi::command::Openapi;
+use crate::common::command::{ambassador_impl_Executable, Executable};
+use crate::common::error::BiError;
+use crate::common::global::GlobalOptions;
+use crate::config::command::ConfigCommands;
+use crate::dev::command::DevCommands;
+use crate::okta::command::OktaCommands;
+use crate::onelogin::command::OneloginCommands;
+
+use async_trait::async_trait;
+use clap::{Args, Parser, Subcommand};
+use clap_markdown::MarkdownOptions;
+
+#[derive(Parser)]
+#[clap(
+    name = "bi",
+    about = "Official Beyond Identity command-line interface.",
+    version = env!("CARGO_PKG_VERSION"), // Dynamically pulls the version from Cargo.toml
+    long_about = None
+)]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Commands,
+    #[clap(short, long)]
+    pub log_level: Option<String>,
+    #[clap(flatten)]
+    pub global: GlobalOptions,
+}
+
+#[derive(Subcommand, ambassador::Delegate)]
+#[delegate(Executable)]
+pub enum Commands {
+    /// Manage CLI tool configuration settings
+    #[clap(subcommand)]
+    Config(ConfigCommands),
+
+    /// Interact with Beyond Identity API endpoints
+    #[clap(subcommand)]
+    Api(BeyondIdentityApiCommands),
+
+    /// Run any operation from the Beyond Identity OpenAPI spec
+    Openapi(Openapi),
+
+    /// Access helper functions for Beyond Identity API operations
+    #[clap(subcommand)]
+    Helper(BeyondIdentityHelperCommands),
+
+    /// Helper tool to generate example commands for CLI operations
+    #[clap(subcommand)]
+    Ai(AiCommands),
+
+    /// Commands solely for fast migration off of Okta
+    #[clap(subcommand)]
+    Okta(OktaCommands),
+
+    /// Commands solely for fast migration off of OneLogin
+    #[clap(subcommand)]
+    Onelogin(OneloginCommands),
+
+    /// Tools for developing and testing against Beyond Identity locally
+    #[clap(subcommand)]
+    Dev(DevCommands),
+
+    /// Generate Markdown documentation (hidden)
+    #[clap(hide = true)]
+    GenerateMarkdown(GenerateMarkdownCommand),
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct GenerateMarkdownCommand;
+
+#[async_trait]
+impl Executable for GenerateMarkdownCommand {
+    async fn execute(&self) -> Result<(), BiError> {
+        println!(
+            "{}",
+            clap_markdown::help_markdown_custom::<Cli>(
+                &MarkdownOptions::new()
+                    .title("bi".to_string())
+                    .show_footer(false)
+                    .show_table_of_contents(true),
+            )
+        );
+        Ok(())
+    }
+}
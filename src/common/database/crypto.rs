@@ -0,0 +1,221 @@
+use crate::common::error::BiError;
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use rand::RngCore;
+
+// Prefix used to tell encrypted column values apart from plaintext ones.
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+// Known plaintext stored encrypted so that a passphrase can be checked
+// without having to decrypt any real secrets.
+const VERIFIER_PLAINTEXT: &str = "beyond-identity-cli";
+
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+
+#[derive(Clone)]
+pub struct Cipher {
+    cipher: XChaCha20Poly1305,
+}
+
+impl Cipher {
+    // Derive a cipher from a passphrase and salt using Argon2id.
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Result<Self, BiError> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| BiError::StringError(format!("Failed to derive key: {}", e)))?;
+        Ok(Self {
+            cipher: XChaCha20Poly1305::new(&key.into()),
+        })
+    }
+
+    // Generate a new random salt, base64 encoded for storage.
+    pub fn generate_salt() -> String {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        STANDARD.encode(salt)
+    }
+
+    // Decode a base64 encoded salt as stored in the database.
+    pub fn decode_salt(salt: &str) -> Result<Vec<u8>, BiError> {
+        STANDARD
+            .decode(salt)
+            .map_err(|e| BiError::StringError(format!("Invalid encryption salt: {}", e)))
+    }
+
+    // Encrypt a value. The result is prefixed so it can be recognized on read.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, BiError> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| BiError::StringError("Failed to encrypt value".to_string()))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend(ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload)))
+    }
+
+    // Decrypt a value. Values without the encrypted prefix are rejected, since
+    // every secret is encrypted once encryption is enabled and a plaintext one
+    // can only have been written behind the CLI's back.
+    pub fn decrypt(&self, value: &str) -> Result<String, BiError> {
+        let encoded = value.strip_prefix(ENCRYPTED_PREFIX).ok_or_else(|| {
+            BiError::StringError(
+                "Found an unencrypted secret in the encrypted configuration".to_string(),
+            )
+        })?;
+
+        let payload = STANDARD
+            .decode(encoded)
+            .map_err(|e| BiError::StringError(format!("Invalid encrypted value: {}", e)))?;
+        if payload.len() < NONCE_LEN {
+            return Err(BiError::StringError("Invalid encrypted value".to_string()));
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                BiError::StringError("Failed to decrypt value. Wrong passphrase?".to_string())
+            })?;
+
+        String::from_utf8(plaintext).map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Produce a verifier for this cipher's key.
+    pub fn verifier(&self) -> Result<String, BiError> {
+        self.encrypt(VERIFIER_PLAINTEXT)
+    }
+
+    // Check that this cipher's key matches the stored verifier.
+    pub fn verify(&self, verifier: &str) -> bool {
+        matches!(self.decrypt(verifier), Ok(v) if v == VERIFIER_PLAINTEXT)
+    }
+}
+
+// Returns true if the value was produced by `Cipher::encrypt`.
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+// Environment variable that can be used to supply the passphrase non-interactively.
+pub const PASSPHRASE_ENV: &str = "BI_PASSPHRASE";
+
+// Read the passphrase from `BI_PASSPHRASE`, falling back to an interactive prompt.
+pub fn read_passphrase(prompt: &str) -> Result<String, BiError> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    rpassword::prompt_password(prompt).map_err(|e| BiError::StringError(e.to_string()))
+}
+
+// Read a new passphrase from `BI_PASSPHRASE`, or prompt twice and confirm it matches.
+pub fn read_new_passphrase() -> Result<String, BiError> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return non_empty(passphrase);
+    }
+    let passphrase = non_empty(
+        rpassword::prompt_password("Enter new passphrase: ")
+            .map_err(|e| BiError::StringError(e.to_string()))?,
+    )?;
+    let confirmation = rpassword::prompt_password("Confirm passphrase: ")
+        .map_err(|e| BiError::StringError(e.to_string()))?;
+    if passphrase != confirmation {
+        return Err(BiError::StringError("Passphrases do not match".to_string()));
+    }
+    Ok(passphrase)
+}
+
+fn non_empty(passphrase: String) -> Result<String, BiError> {
+    if passphrase.is_empty() {
+        return Err(BiError::StringError(
+            "Passphrase must not be empty".to_string(),
+        ));
+    }
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher(passphrase: &str, salt: &str) -> Cipher {
+        Cipher::derive(passphrase, &Cipher::decode_salt(salt).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let salt = Cipher::generate_salt();
+        let cipher = cipher("correct horse", &salt);
+
+        let encrypted = cipher.encrypt("client-secret").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("client-secret"));
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), "client-secret");
+
+        // Every encryption uses a fresh nonce
+        assert_ne!(cipher.encrypt("client-secret").unwrap(), encrypted);
+    }
+
+    #[test]
+    fn same_passphrase_and_salt_derive_same_key() {
+        let salt = Cipher::generate_salt();
+        let encrypted = cipher("correct horse", &salt).encrypt("value").unwrap();
+        assert_eq!(
+            cipher("correct horse", &salt).decrypt(&encrypted).unwrap(),
+            "value"
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_or_salt_fails() {
+        let salt = Cipher::generate_salt();
+        let encrypted = cipher("correct horse", &salt).encrypt("value").unwrap();
+
+        assert!(cipher("battery staple", &salt).decrypt(&encrypted).is_err());
+        assert!(cipher("correct horse", &Cipher::generate_salt())
+            .decrypt(&encrypted)
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_unencrypted_and_tampered_values() {
+        let cipher = cipher("correct horse", &Cipher::generate_salt());
+        assert!(cipher.decrypt("planted-secret").is_err());
+        assert!(cipher.decrypt("").is_err());
+        assert!(cipher.decrypt("enc:v1:not base64!").is_err());
+        assert!(cipher.decrypt("enc:v1:AAAA").is_err());
+
+        let encrypted = cipher.encrypt("value").unwrap();
+        let mut payload = STANDARD
+            .decode(encrypted.strip_prefix(ENCRYPTED_PREFIX).unwrap())
+            .unwrap();
+        *payload.last_mut().unwrap() ^= 1;
+        let tampered = format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload));
+        assert!(cipher.decrypt(&tampered).is_err());
+    }
+
+    #[test]
+    fn verifier() {
+        let salt = Cipher::generate_salt();
+        let verifier = cipher("correct horse", &salt).verifier().unwrap();
+
+        assert!(cipher("correct horse", &salt).verify(&verifier));
+        assert!(!cipher("battery staple", &salt).verify(&verifier));
+        assert!(!cipher("correct horse", &salt).verify("beyond-identity-cli"));
+    }
+
+    #[test]
+    fn empty_passphrase_is_rejected() {
+        assert!(non_empty(String::new()).is_err());
+        assert_eq!(non_empty("pass".to_string()).unwrap(), "pass");
+    }
+}
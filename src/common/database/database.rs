@@ -1,8 +1,12 @@
+use super::crypto::{self, Cipher};
 use super::models::{
-    AiProvider, AnthropicConfig, OktaConfig, OneloginConfig, OpenaiConfig, Realm, Tenant, Token,
+    AiProvider, AnthropicConfig, CachedResponse, MigratedApplication, MigratedGroup,
+    MigrationResourceKind, MigrationRun, MigrationRunResource, OktaConfig, OktaToken,
+    OneloginConfig, OpenaiConfig, Realm, Tenant, Token,
 };
 
 use crate::common::error::BiError;
+use crate::common::global;
 
 use directories::ProjectDirs;
 use log::debug;
@@ -11,22 +15,34 @@ use sqlx::{
     migrate::{MigrateDatabase, Migrator},
     query, query_as,
     sqlite::SqlitePool,
-    Row, Sqlite,
+    Row, Sqlite, Transaction,
 };
+use std::path::PathBuf;
+use std::sync::OnceLock;
 
 #[derive(Clone)]
 pub struct Database {
     pool: SqlitePool,
+    cipher: Option<Cipher>,
 }
 
 static MIGRATOR: Migrator = sqlx::migrate!();
 
+// Cipher unlocked for the lifetime of the process so the passphrase is only
+// requested once, no matter how many times the database is initialized.
+static UNLOCKED_CIPHER: OnceLock<Cipher> = OnceLock::new();
+
 const OKTA_CONFIG_KEY: &str = "okta_config";
+const OKTA_TOKEN_KEY: &str = "okta_token";
 const ONELOGIN_CONFIG_KEY: &str = "onelogin_config";
 const OPENAI_CONFIG_KEY: &str = "openai_config";
 const ANTHROPIC_CONFIG_KEY: &str = "anthropic_config";
 const DEFAULT_AI_PROVIDER_KEY: &str = "default_ai_provider";
 
+const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR: &str = "profiles";
+const DB_FILE_NAME: &str = "sqlite.db";
+
 impl Database {
     // Initialize the database, create if not exists, and run migrations
     pub async fn initialize() -> Result<Self, BiError> {
@@ -55,21 +71,273 @@ impl Database {
             debug!("Detected migration: {:?}", migration);
         }
 
+        let cipher = Self::unlock(&pool).await?;
+
         debug!("Database and migrations initialized successfully.");
-        Ok(Database { pool })
+        Ok(Database { pool, cipher })
+    }
+
+    // Unlock the database if encryption is enabled, returning the cipher used
+    // to encrypt and decrypt secrets.
+    async fn unlock(pool: &SqlitePool) -> Result<Option<Cipher>, BiError> {
+        let (salt, verifier) = match query_as::<_, (String, String)>(
+            "SELECT salt, verifier FROM encryption WHERE id = 1",
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))?
+        {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        if let Some(cipher) = UNLOCKED_CIPHER.get() {
+            if cipher.verify(&verifier) {
+                return Ok(Some(cipher.clone()));
+            }
+        }
+
+        let passphrase = crypto::read_passphrase("Enter passphrase to unlock configuration: ")?;
+        let cipher = Cipher::derive(&passphrase, &Cipher::decode_salt(&salt)?)?;
+        if !cipher.verify(&verifier) {
+            return Err(BiError::StringError("Incorrect passphrase".to_string()));
+        }
+
+        debug!("Database unlocked successfully.");
+        _ = UNLOCKED_CIPHER.set(cipher.clone());
+        Ok(Some(cipher))
+    }
+
+    // Whether secrets in this database are encrypted at rest
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    // Encrypt all secrets in the database with a key derived from the passphrase
+    pub async fn enable_encryption(&self, passphrase: &str) -> Result<(), BiError> {
+        if self.is_encrypted() {
+            return Err(BiError::StringError(
+                "Configuration is already encrypted".to_string(),
+            ));
+        }
+
+        let salt = Cipher::generate_salt();
+        let cipher = Cipher::derive(passphrase, &Cipher::decode_salt(&salt)?)?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Self::rewrite_secrets(&mut tx, |value| {
+            if crypto::is_encrypted(value) {
+                Ok(value.to_string())
+            } else {
+                cipher.encrypt(value)
+            }
+        })
+        .await?;
+
+        query("INSERT INTO encryption (id, salt, verifier) VALUES (1, ?, ?)")
+            .bind(&salt)
+            .bind(cipher.verifier()?)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Decrypt all secrets in the database and disable encryption
+    pub async fn disable_encryption(&self) -> Result<(), BiError> {
+        let cipher = match &self.cipher {
+            Some(cipher) => cipher,
+            None => {
+                return Err(BiError::StringError(
+                    "Configuration is not encrypted".to_string(),
+                ))
+            }
+        };
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Self::rewrite_secrets(&mut tx, |value| cipher.decrypt(value)).await?;
+
+        query("DELETE FROM encryption WHERE id = 1")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Apply a transformation to every secret column in the database
+    async fn rewrite_secrets<F>(
+        tx: &mut Transaction<'_, Sqlite>,
+        transform: F,
+    ) -> Result<(), BiError>
+    where
+        F: Fn(&str) -> Result<String, BiError>,
+    {
+        let realms = query_as::<_, (String, String, String)>(
+            "SELECT tenant_id, id, client_secret FROM realms",
+        )
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        for (tenant_id, realm_id, client_secret) in realms {
+            query("UPDATE realms SET client_secret = ? WHERE tenant_id = ? AND id = ?")
+                .bind(transform(&client_secret)?)
+                .bind(&tenant_id)
+                .bind(&realm_id)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| BiError::StringError(e.to_string()))?;
+        }
+
+        let tokens = query_as::<_, (String, String, String)>(
+            "SELECT tenant_id, realm_id, access_token FROM tokens",
+        )
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        for (tenant_id, realm_id, access_token) in tokens {
+            query("UPDATE tokens SET access_token = ? WHERE tenant_id = ? AND realm_id = ?")
+                .bind(transform(&access_token)?)
+                .bind(&tenant_id)
+                .bind(&realm_id)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| BiError::StringError(e.to_string()))?;
+        }
+
+        let settings = query_as::<_, (String, String)>("SELECT key, value FROM settings")
+            .fetch_all(&mut **tx)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        for (key, value) in settings {
+            query("UPDATE settings SET value = ? WHERE key = ?")
+                .bind(transform(&value)?)
+                .bind(&key)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| BiError::StringError(e.to_string()))?;
+        }
+
+        // Cached responses can simply be fetched again
+        query("DELETE FROM http_cache")
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Encrypt a value if encryption is enabled
+    fn encrypt_value(&self, value: &str) -> Result<String, BiError> {
+        match &self.cipher {
+            Some(cipher) => cipher.encrypt(value),
+            None => Ok(value.to_string()),
+        }
+    }
+
+    // Decrypt a value if encryption is enabled
+    fn decrypt_value(&self, value: &str) -> Result<String, BiError> {
+        match &self.cipher {
+            Some(cipher) => cipher.decrypt(value),
+            None => Ok(value.to_string()),
+        }
+    }
+
+    // Decrypt the secrets held by a realm
+    fn decrypt_realm(&self, mut realm: Realm) -> Result<Realm, BiError> {
+        realm.client_secret = self.decrypt_value(&realm.client_secret)?;
+        Ok(realm)
     }
 
     // db_url creates and returns url of a database in a user writable
-    // directory that is meant for storing application specific data
+    // directory that is meant for storing application specific data.
+    // The default profile lives at the root of the configuration directory
+    // while named profiles each get their own subdirectory.
     fn db_url() -> Result<String, BiError> {
+        let db_dir = match global::options().profile.as_deref() {
+            None | Some(DEFAULT_PROFILE) => Self::config_dir()?,
+            Some(profile) => {
+                Self::validate_profile_name(profile)?;
+                Self::config_dir()?.join(PROFILES_DIR).join(profile)
+            }
+        };
+        std::fs::create_dir_all(&db_dir).map_err(|e| BiError::StringError(e.to_string()))?;
+        let db_path = db_dir.join(DB_FILE_NAME);
+        let db_url = format!("sqlite://{}", db_path.display());
+        Ok(db_url)
+    }
+
+    // config_dir returns the directory configuration is stored in, honoring
+    // the `--config-dir` option and `BI_CONFIG_DIR` environment variable.
+    pub fn config_dir() -> Result<PathBuf, BiError> {
+        if let Some(config_dir) = &global::options().config_dir {
+            return Ok(config_dir.clone());
+        }
         let proj_dirs = ProjectDirs::from("com", "BeyondIdentity", env!("CARGO_PKG_NAME")).ok_or(
             BiError::StringError("Failed to determine project directory".to_string()),
         )?;
-        let db_dir = proj_dirs.data_local_dir();
-        std::fs::create_dir_all(db_dir).map_err(|e| BiError::StringError(e.to_string()))?;
-        let db_path = db_dir.join("sqlite.db");
-        let db_url = format!("sqlite://{}", db_path.display());
-        Ok(db_url)
+        Ok(proj_dirs.data_local_dir().to_path_buf())
+    }
+
+    // List the names of all profiles that have been created, including the default profile
+    pub fn profiles() -> Result<Vec<String>, BiError> {
+        let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+        let profiles_dir = Self::config_dir()?.join(PROFILES_DIR);
+        if let Ok(entries) = std::fs::read_dir(profiles_dir) {
+            let mut named: Vec<String> = entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().join(DB_FILE_NAME).exists())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect();
+            named.sort();
+            profiles.extend(named);
+        }
+        Ok(profiles)
+    }
+
+    // The name of the profile in use
+    pub fn active_profile() -> String {
+        global::options()
+            .profile
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+    }
+
+    // Profile names are used as directory names so only allow a safe subset of characters
+    fn validate_profile_name(profile: &str) -> Result<(), BiError> {
+        if profile.is_empty()
+            || !profile
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(BiError::StringError(format!(
+                "Invalid profile name '{}'. Use only letters, numbers, '-' and '_'.",
+                profile
+            )));
+        }
+        Ok(())
     }
 
     // Get all tenants with their corresponding realms
@@ -88,6 +356,10 @@ impl Database {
                 .fetch_all(&self.pool)
                 .await
                 .map_err(|e| BiError::StringError(e.to_string()))?;
+            let realms = realms
+                .into_iter()
+                .map(|realm| self.decrypt_realm(realm))
+                .collect::<Result<Vec<_>, _>>()?;
 
             tenants_with_realms.push((tenant, realms));
         }
@@ -109,7 +381,7 @@ impl Database {
                 .bind(&realm.tenant_id)
                 .bind(&realm.application_id)
                 .bind(&realm.client_id)
-                .bind(&realm.client_secret)
+                .bind(self.encrypt_value(&realm.client_secret)?)
                 .bind(&realm.open_id_configuration_url)
                 .bind(&realm.auth_base_url)
                 .bind(&realm.api_base_url)
@@ -120,6 +392,21 @@ impl Database {
         Ok(())
     }
 
+    // Update the base URLs of an existing realm
+    pub async fn update_realm_urls(&self, realm: &Realm) -> Result<(), BiError> {
+        query("UPDATE realms SET open_id_configuration_url = ?, auth_base_url = ?, api_base_url = ? WHERE tenant_id = ? AND id = ?")
+            .bind(&realm.open_id_configuration_url)
+            .bind(&realm.auth_base_url)
+            .bind(&realm.api_base_url)
+            .bind(&realm.tenant_id)
+            .bind(&realm.id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
     // Delete a tenant/realm pair, removing the tenant if it has no other realms.
     // Also unsets the default if the tenant/realm pair being deleted is set as the default.
     pub async fn delete_tenant_realm_pair(
@@ -201,7 +488,7 @@ impl Database {
                 .await
                 .map_err(|e| BiError::StringError(e.to_string()))?;
 
-            Ok(Some((tenant, realm)))
+            Ok(Some((tenant, self.decrypt_realm(realm)?)))
         } else {
             Ok(None)
         }
@@ -237,7 +524,12 @@ impl Database {
                 .await
                 .map_err(|e| BiError::StringError(e.to_string()))?;
 
-        Ok(token)
+        token
+            .map(|mut token| {
+                token.access_token = self.decrypt_value(&token.access_token)?;
+                Ok(token)
+            })
+            .transpose()
     }
 
     // Set or update a token
@@ -246,7 +538,7 @@ impl Database {
                 "INSERT OR REPLACE INTO tokens (access_token, expires_at, tenant_id, realm_id, application_id)
                 VALUES (?, ?, ?, ?, ?)"
             )
-            .bind(&token.access_token)
+            .bind(self.encrypt_value(&token.access_token)?)
             .bind(token.expires_at)
             .bind(&token.tenant_id)
             .bind(&token.realm_id)
@@ -270,17 +562,474 @@ impl Database {
         Ok(())
     }
 
-    // Get okta config from db
+    // Get a response cached for the given credential scope by request URL
+    pub async fn get_cached_response(
+        &self,
+        scope: &str,
+        url: &str,
+    ) -> Result<Option<CachedResponse>, BiError> {
+        let cached =
+            query_as::<_, CachedResponse>("SELECT * FROM http_cache WHERE scope = ? AND url = ?")
+                .bind(scope)
+                .bind(url)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        cached
+            .map(|mut cached| {
+                cached.body = self.decrypt_value(&cached.body)?;
+                Ok(cached)
+            })
+            .transpose()
+    }
+
+    // Set or update a cached response
+    pub async fn set_cached_response(&self, cached: &CachedResponse) -> Result<(), BiError> {
+        query(
+            "INSERT OR REPLACE INTO http_cache (scope, url, status, headers, body, etag, stored_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&cached.scope)
+        .bind(&cached.url)
+        .bind(cached.status)
+        .bind(&cached.headers)
+        .bind(self.encrypt_value(&cached.body)?)
+        .bind(&cached.etag)
+        .bind(cached.stored_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Get the URLs of all cached responses starting with the given prefix,
+    // whichever scope they were cached for
+    pub async fn get_cached_response_urls(&self, prefix: &str) -> Result<Vec<String>, BiError> {
+        query_as::<_, (String,)>(
+            "SELECT DISTINCT url FROM http_cache WHERE substr(url, 1, length(?1)) = ?1",
+        )
+        .bind(prefix)
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| rows.into_iter().map(|(url,)| url).collect())
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Delete the cached responses for a request URL in every scope
+    pub async fn delete_cached_response(&self, url: &str) -> Result<(), BiError> {
+        query("DELETE FROM http_cache WHERE url = ?")
+            .bind(url)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Get the SSO config a source application was migrated to
+    pub async fn get_migrated_application(
+        &self,
+        tenant_id: &str,
+        realm_id: &str,
+        source: &str,
+        source_id: &str,
+    ) -> Result<Option<MigratedApplication>, BiError> {
+        query_as::<_, MigratedApplication>(
+            "SELECT * FROM migrated_applications
+            WHERE tenant_id = ? AND realm_id = ? AND source = ? AND source_id = ?",
+        )
+        .bind(tenant_id)
+        .bind(realm_id)
+        .bind(source)
+        .bind(source_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Get every source application migrated to the tenant and realm
+    pub async fn get_migrated_applications(
+        &self,
+        tenant_id: &str,
+        realm_id: &str,
+        source: &str,
+    ) -> Result<Vec<MigratedApplication>, BiError> {
+        query_as::<_, MigratedApplication>(
+            "SELECT * FROM migrated_applications
+            WHERE tenant_id = ? AND realm_id = ? AND source = ?
+            ORDER BY source_id",
+        )
+        .bind(tenant_id)
+        .bind(realm_id)
+        .bind(source)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Set or update the SSO config a source application was migrated to
+    pub async fn set_migrated_application(
+        &self,
+        migrated: &MigratedApplication,
+    ) -> Result<(), BiError> {
+        query(
+            "INSERT OR REPLACE INTO migrated_applications
+            (tenant_id, realm_id, source, source_id, sso_config_id, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&migrated.tenant_id)
+        .bind(&migrated.realm_id)
+        .bind(&migrated.source)
+        .bind(&migrated.source_id)
+        .bind(&migrated.sso_config_id)
+        .bind(migrated.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Forget the source applications migrated to an SSO config
+    pub async fn delete_migrated_applications_for_sso_config(
+        &self,
+        sso_config_id: &str,
+    ) -> Result<(), BiError> {
+        query("DELETE FROM migrated_applications WHERE sso_config_id = ?")
+            .bind(sso_config_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Get the members of the given kind fast-migrate assigned to an SSO config
+    // or added to a group
+    pub async fn get_migrated_assignments(
+        &self,
+        parent_id: &str,
+        kind: MigrationResourceKind,
+    ) -> Result<Vec<String>, BiError> {
+        query_as::<_, (String,)>(
+            "SELECT member_id FROM migrated_assignments
+            WHERE parent_id = ? AND kind = ? ORDER BY member_id",
+        )
+        .bind(parent_id)
+        .bind(kind)
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| rows.into_iter().map(|(id,)| id).collect())
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Record members fast-migrate assigned to an SSO config or added to a group
+    pub async fn add_migrated_assignments(
+        &self,
+        parent_id: &str,
+        kind: MigrationResourceKind,
+        member_ids: &[String],
+    ) -> Result<(), BiError> {
+        for member_id in member_ids {
+            query(
+                "INSERT OR IGNORE INTO migrated_assignments (parent_id, kind, member_id)
+                VALUES (?, ?, ?)",
+            )
+            .bind(parent_id)
+            .bind(kind)
+            .bind(member_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    // Forget members fast-migrate assigned to an SSO config or added to a group
+    pub async fn delete_migrated_assignments(
+        &self,
+        parent_id: &str,
+        kind: MigrationResourceKind,
+        member_ids: &[String],
+    ) -> Result<(), BiError> {
+        for member_id in member_ids {
+            query(
+                "DELETE FROM migrated_assignments
+                WHERE parent_id = ? AND kind = ? AND member_id = ?",
+            )
+            .bind(parent_id)
+            .bind(kind)
+            .bind(member_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    // Forget every member fast-migrate assigned to an SSO config or added to a
+    // group
+    pub async fn delete_migrated_assignments_for_parent(
+        &self,
+        parent_id: &str,
+    ) -> Result<(), BiError> {
+        query("DELETE FROM migrated_assignments WHERE parent_id = ?")
+            .bind(parent_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Get the group a source group was mirrored to by fast-migrate
+    pub async fn get_migrated_group(
+        &self,
+        tenant_id: &str,
+        realm_id: &str,
+        source: &str,
+        source_id: &str,
+    ) -> Result<Option<MigratedGroup>, BiError> {
+        query_as::<_, MigratedGroup>(
+            "SELECT * FROM migrated_groups
+            WHERE tenant_id = ? AND realm_id = ? AND source = ? AND source_id = ?",
+        )
+        .bind(tenant_id)
+        .bind(realm_id)
+        .bind(source)
+        .bind(source_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Set or update the group a source group was mirrored to
+    pub async fn set_migrated_group(&self, migrated: &MigratedGroup) -> Result<(), BiError> {
+        query(
+            "INSERT OR REPLACE INTO migrated_groups
+            (tenant_id, realm_id, source, source_id, group_id, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&migrated.tenant_id)
+        .bind(&migrated.realm_id)
+        .bind(&migrated.source)
+        .bind(&migrated.source_id)
+        .bind(&migrated.group_id)
+        .bind(migrated.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Forget the source groups mirrored to a group
+    pub async fn delete_migrated_groups_for_group(&self, group_id: &str) -> Result<(), BiError> {
+        query("DELETE FROM migrated_groups WHERE group_id = ?")
+            .bind(group_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Record the start of a fast-migrate run
+    pub async fn create_migration_run(&self, run: &MigrationRun) -> Result<(), BiError> {
+        query(
+            "INSERT INTO migration_runs (id, tenant_id, realm_id, source, started_at, rolled_back_at)
+            VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&run.id)
+        .bind(&run.tenant_id)
+        .bind(&run.realm_id)
+        .bind(&run.source)
+        .bind(run.started_at)
+        .bind(run.rolled_back_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Get a fast-migrate run by id
+    pub async fn get_migration_run(&self, id: &str) -> Result<Option<MigrationRun>, BiError> {
+        query_as::<_, MigrationRun>("SELECT * FROM migration_runs WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Get all fast-migrate runs from a source, oldest first
+    pub async fn get_migration_runs(&self, source: &str) -> Result<Vec<MigrationRun>, BiError> {
+        query_as::<_, MigrationRun>(
+            "SELECT * FROM migration_runs WHERE source = ? ORDER BY started_at, rowid",
+        )
+        .bind(source)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Mark a fast-migrate run as rolled back
+    pub async fn set_migration_run_rolled_back(
+        &self,
+        id: &str,
+        rolled_back_at: i64,
+    ) -> Result<(), BiError> {
+        query("UPDATE migration_runs SET rolled_back_at = ? WHERE id = ?")
+            .bind(rolled_back_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Record a resource created by a fast-migrate run
+    pub async fn add_migration_run_resource(
+        &self,
+        resource: &MigrationRunResource,
+    ) -> Result<(), BiError> {
+        query(
+            "INSERT INTO migration_run_resources (run_id, kind, resource_id, parent_id)
+            VALUES (?, ?, ?, ?)",
+        )
+        .bind(&resource.run_id)
+        .bind(resource.kind)
+        .bind(&resource.resource_id)
+        .bind(&resource.parent_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Record the source applications offered for migration by a fast-migrate run
+    pub async fn add_migration_run_applications(
+        &self,
+        run_id: &str,
+        source_ids: &[String],
+    ) -> Result<(), BiError> {
+        for source_id in source_ids {
+            query(
+                "INSERT OR IGNORE INTO migration_run_applications (run_id, source_id)
+                VALUES (?, ?)",
+            )
+            .bind(run_id)
+            .bind(source_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    // Get the source applications offered for migration by any fast-migrate
+    // run from a source to the tenant and realm
+    pub async fn get_offered_applications(
+        &self,
+        tenant_id: &str,
+        realm_id: &str,
+        source: &str,
+    ) -> Result<Vec<String>, BiError> {
+        query_as::<_, (String,)>(
+            "SELECT DISTINCT a.source_id FROM migration_run_applications a
+            JOIN migration_runs r ON r.id = a.run_id
+            WHERE r.tenant_id = ? AND r.realm_id = ? AND r.source = ?
+            ORDER BY a.source_id",
+        )
+        .bind(tenant_id)
+        .bind(realm_id)
+        .bind(source)
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| rows.into_iter().map(|(id,)| id).collect())
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Get the resources of the given kind created by fast-migrate runs from a
+    // source to the tenant and realm that have not been rolled back
+    pub async fn get_migrated_resources(
+        &self,
+        tenant_id: &str,
+        realm_id: &str,
+        source: &str,
+        kind: MigrationResourceKind,
+    ) -> Result<Vec<String>, BiError> {
+        query_as::<_, (String,)>(
+            "SELECT DISTINCT s.resource_id FROM migration_run_resources s
+            JOIN migration_runs r ON r.id = s.run_id
+            WHERE r.tenant_id = ? AND r.realm_id = ? AND r.source = ?
+            AND r.rolled_back_at IS NULL AND s.kind = ?
+            ORDER BY s.resource_id",
+        )
+        .bind(tenant_id)
+        .bind(realm_id)
+        .bind(source)
+        .bind(kind)
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| rows.into_iter().map(|(id,)| id).collect())
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Get the resources created by a fast-migrate run, in the order they were created
+    pub async fn get_migration_run_resources(
+        &self,
+        run_id: &str,
+    ) -> Result<Vec<MigrationRunResource>, BiError> {
+        query_as::<_, MigrationRunResource>(
+            "SELECT run_id, kind, resource_id, parent_id FROM migration_run_resources
+            WHERE run_id = ? ORDER BY rowid",
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))
+    }
+
+    // Get okta config from db, resolving any secret references
     pub async fn get_okta_config(&self) -> Result<Option<OktaConfig>, BiError> {
         self.get_config(OKTA_CONFIG_KEY).await
     }
 
-    // Set okta config in db
+    // Set okta config in db, dropping any token issued for the previous config
     pub async fn set_okta_config(&self, config: OktaConfig) -> Result<(), BiError> {
+        self.delete_okta_token().await?;
         self.set_config(OKTA_CONFIG_KEY, &config).await
     }
 
-    // Get onelogin config from db
+    // Get the cached okta service app token
+    pub async fn get_okta_token(&self) -> Result<Option<OktaToken>, BiError> {
+        self.get_config(OKTA_TOKEN_KEY).await
+    }
+
+    // Cache an okta service app token
+    pub async fn set_okta_token(&self, token: &OktaToken) -> Result<(), BiError> {
+        self.set_config(OKTA_TOKEN_KEY, token).await
+    }
+
+    // Drop the cached okta service app token
+    pub async fn delete_okta_token(&self) -> Result<(), BiError> {
+        query("DELETE FROM settings WHERE key = ?")
+            .bind(OKTA_TOKEN_KEY)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| BiError::StringError(e.to_string()))?;
+        Ok(())
+    }
+
+    // Get onelogin config from db, resolving any secret references
     pub async fn get_onelogin_config(&self) -> Result<Option<OneloginConfig>, BiError> {
         self.get_config(ONELOGIN_CONFIG_KEY).await
     }
@@ -335,6 +1084,7 @@ impl Database {
             let value: String = row
                 .try_get("value")
                 .map_err(|e| BiError::StringError(e.to_string()))?;
+            let value = self.decrypt_value(&value)?;
             let config: T =
                 serde_json::from_str(&value).map_err(|e| BiError::StringError(e.to_string()))?;
             Ok(Some(config))
@@ -349,7 +1099,7 @@ impl Database {
             serde_json::to_string(config).map_err(|e| BiError::StringError(e.to_string()))?;
         query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
             .bind(key)
-            .bind(value)
+            .bind(self.encrypt_value(&value)?)
             .execute(&self.pool)
             .await
             .map_err(|e| BiError::StringError(e.to_string()))?;
@@ -1,4 +1,9 @@
+#[cfg(feature = "cli")]
+pub mod crypto;
+#[cfg(feature = "cli")]
+#[allow(clippy::module_inception)]
 mod database;
 pub mod models;
 
+#[cfg(feature = "cli")]
 pub use database::Database;
@@ -1,13 +1,18 @@
+#[cfg(feature = "cli")]
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "cli")]
 use sqlx::prelude::FromRow;
+use std::path::PathBuf;
 
-#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "cli", derive(FromRow))]
 pub struct Tenant {
     pub id: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "cli", derive(FromRow))]
 pub struct Realm {
     pub id: String,
     pub tenant_id: String,
@@ -19,7 +24,8 @@ pub struct Realm {
     pub api_base_url: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "cli", derive(FromRow))]
 pub struct Token {
     pub access_token: String,
     pub expires_at: i64,
@@ -28,10 +34,111 @@ pub struct Token {
     pub application_id: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "cli", derive(FromRow))]
+pub struct CachedResponse {
+    // Tenant, realm and client the response was fetched with
+    pub scope: String,
+    pub url: String,
+    pub status: i64,
+    // JSON object of header names to values
+    pub headers: String,
+    pub body: String,
+    pub etag: Option<String>,
+    pub stored_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "cli", derive(FromRow))]
+pub struct MigratedApplication {
+    pub tenant_id: String,
+    pub realm_id: String,
+    pub source: String,
+    pub source_id: String,
+    pub sso_config_id: String,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "cli", derive(FromRow))]
+pub struct MigratedGroup {
+    pub tenant_id: String,
+    pub realm_id: String,
+    pub source: String,
+    pub source_id: String,
+    pub group_id: String,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "cli", derive(FromRow))]
+pub struct MigrationRun {
+    pub id: String,
+    pub tenant_id: String,
+    pub realm_id: String,
+    pub source: String,
+    pub started_at: i64,
+    pub rolled_back_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "cli", derive(FromRow))]
+pub struct MigrationRunResource {
+    pub run_id: String,
+    pub kind: MigrationResourceKind,
+    pub resource_id: String,
+    // Resource the created resource belongs to, e.g. the SSO config of an assignment
+    pub parent_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(sqlx::Type))]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "cli", sqlx(type_name = "TEXT", rename_all = "snake_case"))]
+pub enum MigrationResourceKind {
+    SsoConfig,
+    SsoConfigIdentity,
+    SsoConfigGroup,
+    Group,
+    GroupMember,
+    Identity,
+    // Assignments and members removed by a run, restored on rollback
+    RemovedSsoConfigIdentity,
+    RemovedGroupMember,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OktaConfig {
     pub domain: String,
-    pub api_key: String,
+    // SSWS API token. Not set when authenticating as an OAuth 2.0 service app.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth: Option<OktaOAuthConfig>,
+}
+
+// Okta service app authenticating with a JWT signed by its private key
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OktaOAuthConfig {
+    pub client_id: String,
+    pub private_key_file: PathBuf,
+    // Key ID of the private key registered with the service app, if it has several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    pub scopes: Vec<String>,
+}
+
+// Access token issued to an Okta service app. DPoP-bound tokens keep the key
+// they are bound to.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OktaToken {
+    pub client_id: String,
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_at: i64,
+    // PKCS#8 DER of the DPoP key, base64 encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dpop_key: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -51,7 +158,8 @@ pub struct AnthropicConfig {
     pub api_key: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ValueEnum)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[serde(rename_all = "lowercase")]
 pub enum AiProvider {
     Openai,
@@ -0,0 +1,125 @@
+use super::http_client::HttpOptions;
+
+use clap::{Args, ValueEnum};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Options that apply to every command.
+#[derive(Args, Debug, Clone, Default)]
+pub struct GlobalOptions {
+    /// Name of the configuration profile to use. Each profile has its own tenants, defaults and
+    /// integration settings.
+    #[clap(long, env = "BI_PROFILE", global = true)]
+    pub profile: Option<String>,
+
+    /// Directory in which configuration is stored. Defaults to the platform specific application
+    /// data directory.
+    #[clap(long, env = "BI_CONFIG_DIR", global = true)]
+    pub config_dir: Option<PathBuf>,
+
+    /// Proxy URL to send all HTTP requests through. When unset, the standard `HTTPS_PROXY`,
+    /// `HTTP_PROXY` and `NO_PROXY` environment variables are honored.
+    #[clap(long, env = "BI_PROXY", global = true)]
+    pub proxy: Option<String>,
+
+    /// PEM file containing additional root certificates to trust, e.g. for an inspecting proxy.
+    #[clap(long, env = "BI_CA_CERT", global = true)]
+    pub ca_cert: Option<PathBuf>,
+
+    /// PEM file containing a client certificate (and optionally its private key) for mutual TLS.
+    #[clap(long, env = "BI_CLIENT_CERT", global = true)]
+    pub client_cert: Option<PathBuf>,
+
+    /// PEM file containing the private key for --client-cert, if not included in that file.
+    #[clap(long, env = "BI_CLIENT_KEY", global = true, requires = "client_cert")]
+    pub client_key: Option<PathBuf>,
+
+    /// Timeout in seconds for establishing HTTP connections.
+    #[clap(long, env = "BI_CONNECT_TIMEOUT", global = true)]
+    pub connect_timeout: Option<u64>,
+
+    /// Timeout in seconds for reading HTTP responses.
+    #[clap(long, env = "BI_READ_TIMEOUT", global = true)]
+    pub read_timeout: Option<u64>,
+
+    /// Record every HTTP request and response to this cassette file. Tokens and secrets are
+    /// redacted.
+    #[clap(long, env = "BI_RECORD", global = true, conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Serve HTTP responses from this cassette file instead of the network. Requests are matched
+    /// by method and URL, so the configured tenant must match the one that was recorded.
+    #[clap(long, env = "BI_REPLAY", global = true)]
+    pub replay: Option<PathBuf>,
+
+    /// Write every HTTP request and response, with timings, to this HAR file. Tokens and secrets
+    /// are redacted.
+    #[clap(long, env = "BI_TRACE_HTTP", global = true)]
+    pub trace_http: Option<PathBuf>,
+
+    /// Additional request and response fields to redact from logs, HAR files and cassettes, e.g.
+    /// personal information such as `primary_email_address`. Credentials are always redacted.
+    #[clap(
+        long = "redact-field",
+        env = "BI_REDACT_FIELDS",
+        global = true,
+        value_delimiter = ','
+    )]
+    pub redact_fields: Vec<String>,
+
+    /// Print wall time, request counts by endpoint, retries, rate limit waits, token refreshes
+    /// and latency percentiles to stderr when the command finishes.
+    #[clap(long, env = "BI_STATS", global = true)]
+    pub stats: bool,
+
+    /// Cache Beyond Identity API GET responses per tenant, realm and client. `memory` keeps them for
+    /// this invocation only while `persistent` stores them in the configuration database. Cached
+    /// responses are dropped when the same resource is successfully modified.
+    #[clap(
+        long,
+        env = "BI_CACHE",
+        global = true,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "memory"
+    )]
+    pub cache: Option<CacheMode>,
+
+    /// Seconds a cached response is served without checking with the server. Stale responses are
+    /// revalidated with `If-None-Match` when the server returned an `ETag`.
+    #[clap(long, env = "BI_CACHE_TTL", global = true, default_value_t = 300)]
+    pub cache_ttl: u64,
+}
+
+impl GlobalOptions {
+    // Transport settings for the HTTP clients created by the CLI
+    pub fn http_options(&self) -> HttpOptions {
+        HttpOptions {
+            proxy: self.proxy.clone(),
+            ca_cert: self.ca_cert.clone(),
+            client_cert: self.client_cert.clone(),
+            client_key: self.client_key.clone(),
+            connect_timeout: self.connect_timeout,
+            read_timeout: self.read_timeout,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CacheMode {
+    Memory,
+    Persistent,
+}
+
+static GLOBAL_OPTIONS: OnceLock<GlobalOptions> = OnceLock::new();
+
+// Set the global options for this process. Only the first call has any effect.
+pub fn init(options: GlobalOptions) {
+    _ = GLOBAL_OPTIONS.set(options);
+}
+
+// Get the global options for this process, falling back to defaults if they were never set.
+pub fn options() -> &'static GlobalOptions {
+    GLOBAL_OPTIONS.get_or_init(GlobalOptions::default)
+}
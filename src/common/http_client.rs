@@ -0,0 +1,87 @@
+use super::error::BiError;
+
+use reqwest::{Certificate, Client, Identity, Proxy};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_READ_TIMEOUT_SECS: u64 = 120;
+
+/// Transport settings for the HTTP clients used to reach Beyond Identity and
+/// the identity providers migrated from. The defaults use the system roots and
+/// the standard proxy environment variables.
+#[derive(Debug, Clone, Default)]
+pub struct HttpOptions {
+    /// Proxy URL to send all requests through.
+    pub proxy: Option<String>,
+    /// PEM file containing additional root certificates to trust.
+    pub ca_cert: Option<PathBuf>,
+    /// PEM file containing a client certificate, and optionally its private
+    /// key, for mutual TLS.
+    pub client_cert: Option<PathBuf>,
+    /// PEM file containing the private key for `client_cert`.
+    pub client_key: Option<PathBuf>,
+    /// Timeout in seconds for establishing connections.
+    pub connect_timeout: Option<u64>,
+    /// Timeout in seconds for reading responses.
+    pub read_timeout: Option<u64>,
+}
+
+// Build an HTTP client with the given transport settings. Every outgoing
+// request should go through a client created here so that proxies, custom
+// roots, client certificates and timeouts apply everywhere.
+pub fn new(options: &HttpOptions) -> Result<Client, BiError> {
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(
+            options
+                .connect_timeout
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+        ))
+        .read_timeout(Duration::from_secs(
+            options.read_timeout.unwrap_or(DEFAULT_READ_TIMEOUT_SECS),
+        ));
+
+    if let Some(proxy) = &options.proxy {
+        builder =
+            builder
+                .proxy(Proxy::all(proxy).map_err(|e| {
+                    BiError::StringError(format!("Invalid proxy {}: {}", proxy, e))
+                })?);
+    }
+
+    if let Some(ca_cert) = &options.ca_cert {
+        let certificates = Certificate::from_pem_bundle(&read(ca_cert)?).map_err(|e| {
+            BiError::StringError(format!(
+                "Invalid CA certificate {}: {}",
+                ca_cert.display(),
+                e
+            ))
+        })?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if let Some(client_cert) = &options.client_cert {
+        let mut pem = read(client_cert)?;
+        if let Some(client_key) = &options.client_key {
+            pem.push(b'\n');
+            pem.extend(read(client_key)?);
+        }
+        let identity = Identity::from_pem(&pem).map_err(|e| {
+            BiError::StringError(format!(
+                "Invalid client certificate {}: {}",
+                client_cert.display(),
+                e
+            ))
+        })?;
+        builder = builder.identity(identity);
+    }
+
+    builder.build().map_err(BiError::from)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, BiError> {
+    std::fs::read(path)
+        .map_err(|e| BiError::StringError(format!("Failed to read {}: {}", path.display(), e)))
+}
@@ -1,3 +1,12 @@
+#[cfg(feature = "cli")]
 pub mod command;
 pub mod database;
 pub mod error;
+#[cfg(feature = "cli")]
+pub mod global;
+pub mod http_client;
+pub mod redact;
+#[cfg(feature = "cli")]
+pub mod secret;
+#[cfg(feature = "cli")]
+pub mod stats;
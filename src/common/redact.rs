@@ -0,0 +1,230 @@
+#[cfg(feature = "cli")]
+use crate::common::global;
+
+use serde_json::Value;
+use url::Url;
+
+// Replacement for redacted values.
+pub const REDACTED: &str = "REDACTED";
+
+// Headers that carry credentials.
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "dpop",
+];
+
+// Body fields and query parameters that carry credentials.
+const SENSITIVE_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "client_secret",
+    "client_assertion",
+    "api_key",
+    "password",
+    "private_key",
+];
+
+pub fn is_sensitive_header(name: &str) -> bool {
+    SENSITIVE_HEADERS
+        .iter()
+        .any(|header| header.eq_ignore_ascii_case(name))
+}
+
+// Credentials are always redacted, along with any fields given by `--redact-field`
+#[cfg(feature = "cli")]
+fn is_sensitive_field(name: &str) -> bool {
+    is_sensitive(name, &global::options().redact_fields)
+}
+
+#[cfg(not(feature = "cli"))]
+fn is_sensitive_field(name: &str) -> bool {
+    is_sensitive(name, &[])
+}
+
+fn is_sensitive(name: &str, redact_fields: &[String]) -> bool {
+    SENSITIVE_FIELDS
+        .iter()
+        .any(|field| field.eq_ignore_ascii_case(name))
+        || redact_fields
+            .iter()
+            .any(|field| field.eq_ignore_ascii_case(name))
+}
+
+// Redact a header value if the header carries credentials
+pub fn header(name: &str, value: &str) -> String {
+    if is_sensitive_header(name) {
+        REDACTED.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+// Redact sensitive query parameters of a URL
+pub fn url(url: &Url) -> String {
+    if !url.query_pairs().any(|(key, _)| is_sensitive_field(&key)) {
+        return url.to_string();
+    }
+
+    let mut redacted = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| {
+            let value = if is_sensitive_field(&key) {
+                REDACTED.to_string()
+            } else {
+                value.into_owned()
+            };
+            (key.into_owned(), value)
+        })
+        .collect();
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    redacted.to_string()
+}
+
+// Redact sensitive fields of a JSON or form encoded body. Other bodies are
+// returned unchanged.
+pub fn body(body: &str) -> String {
+    if let Ok(mut value) = serde_json::from_str::<Value>(body) {
+        if value.is_object() || value.is_array() {
+            json(&mut value);
+            return value.to_string();
+        }
+    }
+
+    if is_form_encoded(body) {
+        let pairs: Vec<(String, String)> = url::form_urlencoded::parse(body.as_bytes())
+            .map(|(key, value)| {
+                let value = if is_sensitive_field(&key) {
+                    REDACTED.to_string()
+                } else {
+                    value.into_owned()
+                };
+                (key.into_owned(), value)
+            })
+            .collect();
+        return url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(pairs)
+            .finish();
+    }
+
+    body.to_string()
+}
+
+// Recursively redact sensitive fields of a JSON value
+pub fn json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_field(key) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    json(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(json),
+        _ => {}
+    }
+}
+
+fn is_form_encoded(body: &str) -> bool {
+    !body.is_empty()
+        && body.contains('=')
+        && body
+            .chars()
+            .all(|c| c.is_ascii_graphic() && c != '{' && c != '[' && c != '"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn redacts_credential_headers() {
+        assert_eq!(header("Authorization", "Bearer abc"), REDACTED);
+        assert_eq!(header("DPoP", "eyJ..."), REDACTED);
+        assert_eq!(header("set-cookie", "session=1"), REDACTED);
+        assert_eq!(
+            header("Content-Type", "application/json"),
+            "application/json"
+        );
+    }
+
+    #[test]
+    fn redacts_sensitive_query_parameters() {
+        let unchanged = Url::parse("https://api.example.com/v1/identities?page_size=5").unwrap();
+        assert_eq!(url(&unchanged), unchanged.to_string());
+
+        let sensitive =
+            Url::parse("https://api.example.com/cb?code=1&access_token=secret&Client_Secret=s")
+                .unwrap();
+        let redacted = url(&sensitive);
+        assert!(!redacted.contains("secret=s") && !redacted.contains("=secret"));
+        assert!(redacted.contains("code=1"));
+        assert!(redacted.contains(&format!("access_token={}", REDACTED)));
+        assert!(redacted.contains(&format!("Client_Secret={}", REDACTED)));
+    }
+
+    #[test]
+    fn redacts_nested_json_fields() {
+        let redacted: Value = serde_json::from_str(&body(
+            &json!({
+                "access_token": "secret",
+                "expires_in": 3600,
+                "realm": { "client_secret": "secret", "client_id": "id" },
+                "users": [{ "password": "secret", "name": "alice" }],
+                "refresh_token": null,
+            })
+            .to_string(),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            redacted,
+            json!({
+                "access_token": REDACTED,
+                "expires_in": 3600,
+                "realm": { "client_secret": REDACTED, "client_id": "id" },
+                "users": [{ "password": REDACTED, "name": "alice" }],
+                "refresh_token": null,
+            })
+        );
+    }
+
+    #[test]
+    fn redacts_form_fields() {
+        let redacted = body("grant_type=client_credentials&client_assertion=eyJ.x.y&scope=a+b");
+        assert_eq!(
+            redacted,
+            format!(
+                "grant_type=client_credentials&client_assertion={}&scope=a+b",
+                REDACTED
+            )
+        );
+    }
+
+    #[test]
+    fn redacts_extra_fields() {
+        let extra = vec!["SSN".to_string(), "employee_id".to_string()];
+        assert!(is_sensitive("ssn", &extra));
+        assert!(is_sensitive("Employee_ID", &extra));
+        assert!(is_sensitive("password", &extra));
+        assert!(!is_sensitive("email", &extra));
+        assert!(!is_sensitive("ssn", &[]));
+    }
+
+    #[test]
+    fn leaves_other_bodies_unchanged() {
+        assert_eq!(body(""), "");
+        assert_eq!(body("plain text"), "plain text");
+        assert_eq!(body("\"a string\""), "\"a string\"");
+        assert_eq!(body("<xml password=\"x\"/>"), "<xml password=\"x\"/>");
+    }
+}
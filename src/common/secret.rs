@@ -0,0 +1,85 @@
+use super::error::BiError;
+use super::redact::REDACTED;
+
+use std::process::Command;
+
+// Secret references allow configuration values to point at where a secret
+// lives instead of storing the secret itself. They are resolved at use time:
+//
+//   - `env:NAME` reads the environment variable `NAME`
+//   - `file:/path/to/secret` reads the contents of a file
+//   - `cmd:some command` runs a command and reads its standard output
+//
+// Any other value is treated as a literal secret.
+const ENV_PREFIX: &str = "env:";
+const FILE_PREFIX: &str = "file:";
+const CMD_PREFIX: &str = "cmd:";
+
+pub fn resolve(value: &str) -> Result<String, BiError> {
+    if let Some(name) = value.strip_prefix(ENV_PREFIX) {
+        return std::env::var(name).map_err(|_| {
+            BiError::StringError(format!(
+                "Failed to resolve secret: environment variable {} is not set",
+                name
+            ))
+        });
+    }
+
+    if let Some(path) = value.strip_prefix(FILE_PREFIX) {
+        return std::fs::read_to_string(path)
+            .map(|contents| contents.trim_end_matches(['\r', '\n']).to_string())
+            .map_err(|e| {
+                BiError::StringError(format!(
+                    "Failed to resolve secret: unable to read {}: {}",
+                    path, e
+                ))
+            });
+    }
+
+    if let Some(command) = value.strip_prefix(CMD_PREFIX) {
+        return run(command);
+    }
+
+    Ok(value.to_string())
+}
+
+// Show a configured secret without revealing it. References are shown as is
+// since they only say where the secret lives.
+pub fn display(value: &str) -> &str {
+    if [ENV_PREFIX, FILE_PREFIX, CMD_PREFIX]
+        .iter()
+        .any(|prefix| value.starts_with(prefix))
+    {
+        value
+    } else {
+        REDACTED
+    }
+}
+
+fn run(command: &str) -> Result<String, BiError> {
+    log::debug!("Resolving secret with command: {}", command);
+
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| {
+        BiError::StringError(format!(
+            "Failed to resolve secret: unable to run command: {}",
+            e
+        ))
+    })?;
+
+    if !output.status.success() {
+        return Err(BiError::StringError(format!(
+            "Failed to resolve secret: command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8(output.stdout)
+        .map(|stdout| stdout.trim_end_matches(['\r', '\n']).to_string())
+        .map_err(|e| BiError::StringError(e.to_string()))
+}
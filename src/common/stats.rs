@@ -0,0 +1,196 @@
+use crate::common::global;
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tabled::{Table, Tabled};
+
+// ====================================
+// Request Statistics
+// ====================================
+
+// Collected for the whole process when `--stats` is given and printed once the
+// command finishes. Every function here is a no-op otherwise.
+#[derive(Debug, Default)]
+struct Stats {
+    requests: BTreeMap<RequestKey, Vec<Duration>>,
+    retries: u64,
+    rate_limit_waits: u64,
+    rate_limit_wait_time: Duration,
+    token_refreshes: u64,
+    cache_hits: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RequestKey {
+    endpoint: String,
+    method: String,
+    status: String,
+}
+
+#[derive(Tabled)]
+struct EndpointDisplay {
+    #[tabled(rename = "Endpoint")]
+    endpoint: String,
+    #[tabled(rename = "Method")]
+    method: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Count")]
+    count: usize,
+    #[tabled(rename = "p50")]
+    p50: String,
+    #[tabled(rename = "p95")]
+    p95: String,
+}
+
+static STATS: OnceLock<Mutex<Stats>> = OnceLock::new();
+
+fn with_stats(f: impl FnOnce(&mut Stats)) {
+    if !global::options().stats {
+        return;
+    }
+    if let Ok(mut stats) = STATS.get_or_init(Default::default).lock() {
+        f(&mut stats);
+    }
+}
+
+// Record a request sent over the network. `status` is `None` when no response
+// was received.
+pub fn record_request(method: &str, url: &url::Url, status: Option<u16>, elapsed: Duration) {
+    with_stats(|stats| {
+        let key = RequestKey {
+            endpoint: endpoint(url),
+            method: method.to_string(),
+            status: status
+                .map(|status| status.to_string())
+                .unwrap_or_else(|| "error".to_string()),
+        };
+        stats.requests.entry(key).or_default().push(elapsed);
+    });
+}
+
+// Record a request being sent again, e.g. after a 429 or a rejected token.
+pub fn record_retry() {
+    with_stats(|stats| stats.retries += 1);
+}
+
+// Record time spent waiting before retrying a rate limited request.
+pub fn record_rate_limit_wait(wait: Duration) {
+    with_stats(|stats| {
+        stats.rate_limit_waits += 1;
+        stats.rate_limit_wait_time += wait;
+    });
+}
+
+// Record a new access token being fetched.
+pub fn record_token_refresh() {
+    with_stats(|stats| stats.token_refreshes += 1);
+}
+
+// Record a response served from the cache given by `--cache`.
+pub fn record_cache_hit() {
+    with_stats(|stats| stats.cache_hits += 1);
+}
+
+// Print the collected statistics to stderr so they never mix with command output.
+pub fn report(wall_time: Duration) {
+    with_stats(|stats| {
+        let all: Vec<Duration> = stats.requests.values().flatten().copied().collect();
+        let request_time: Duration = all.iter().sum();
+
+        eprintln!();
+        eprintln!("Wall time:        {}", format_duration(wall_time));
+        eprintln!(
+            "Requests:         {} ({} in requests)",
+            all.len(),
+            format_duration(request_time)
+        );
+        eprintln!("Retries:          {}", stats.retries);
+        eprintln!(
+            "Rate limit waits: {} ({})",
+            stats.rate_limit_waits,
+            format_duration(stats.rate_limit_wait_time)
+        );
+        eprintln!("Token refreshes:  {}", stats.token_refreshes);
+        eprintln!("Cache hits:       {}", stats.cache_hits);
+        eprintln!(
+            "Latency:          p50 {}, p95 {}",
+            format_percentile(&all, 50),
+            format_percentile(&all, 95)
+        );
+
+        if stats.requests.is_empty() {
+            return;
+        }
+
+        let rows = stats
+            .requests
+            .iter()
+            .map(|(key, durations)| EndpointDisplay {
+                endpoint: key.endpoint.clone(),
+                method: key.method.clone(),
+                status: key.status.clone(),
+                count: durations.len(),
+                p50: format_percentile(durations, 50),
+                p95: format_percentile(durations, 95),
+            });
+        eprintln!();
+        eprintln!("{}", Table::new(rows));
+    });
+}
+
+// Group URLs by endpoint by replacing identifiers in the path, e.g.
+// `/v1/tenants/{id}/realms/{id}/identities/{id}`.
+fn endpoint(url: &url::Url) -> String {
+    let path = url
+        .path_segments()
+        .map(|segments| {
+            segments
+                .map(|segment| {
+                    // Custom methods such as `{id}:addMembers` keep their suffix
+                    let (id, action) = match segment.split_once(':') {
+                        Some((id, action)) => (id, Some(action)),
+                        None => (segment, None),
+                    };
+                    let id = if is_identifier(id) { "{id}" } else { id };
+                    match action {
+                        Some(action) => format!("{}:{}", id, action),
+                        None => id.to_string(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .unwrap_or_default();
+    format!("{}/{}", url.host_str().unwrap_or_default(), path)
+}
+
+// Identifiers contain digits, but API versions such as `v1` or `2` are kept.
+fn is_identifier(segment: &str) -> bool {
+    let version = segment.strip_prefix('v').unwrap_or(segment);
+    let is_version =
+        !version.is_empty() && version.len() <= 2 && version.chars().all(|c| c.is_ascii_digit());
+    !is_version && segment.chars().any(|c| c.is_ascii_digit())
+}
+
+// Nearest-rank percentile of the given durations
+fn percentile(durations: &[Duration], percentile: usize) -> Option<Duration> {
+    if durations.is_empty() {
+        return None;
+    }
+    let mut sorted = durations.to_vec();
+    sorted.sort();
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    Some(sorted[rank - 1])
+}
+
+fn format_percentile(durations: &[Duration], p: usize) -> String {
+    percentile(durations, p)
+        .map(|d| format!("{}ms", d.as_millis()))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
@@ -1,10 +1,8 @@
-use crate::{
-    beyond_identity::api::common::api_client::ApiClient,
-    common::{
-        command::{ambassador_impl_Executable, Executable},
-        database::models::{AiProvider, AnthropicConfig, OpenaiConfig},
-        error::BiError,
-    },
+use crate::common::{
+    command::{ambassador_impl_Executable, Executable},
+    database::models::{AiProvider, AnthropicConfig, OpenaiConfig},
+    database::Database,
+    error::BiError,
 };
 
 use async_trait::async_trait;
@@ -55,7 +53,7 @@ pub struct SetProvider {
     #[clap(long, value_enum)]
     pub provider: AiProvider,
 
-    /// API Key
+    /// API Key. Accepts a secret reference such as `env:NAME`, `file:/path` or `cmd:command`.
     #[clap(long)]
     pub api_key: String,
 
@@ -67,11 +65,11 @@ pub struct SetProvider {
 #[async_trait]
 impl Executable for SetProvider {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
+        let db = Database::initialize().await?;
 
         match self.provider {
             AiProvider::Openai => {
-                if let Ok(Some(c)) = api_client.db.get_openai_config().await {
+                if let Ok(Some(c)) = db.get_openai_config().await {
                     if !self.force {
                         println!("Already configured: {:?}", c);
                         return Ok(());
@@ -83,10 +81,10 @@ impl Executable for SetProvider {
                     api_key: self.api_key.to_string(),
                 };
 
-                Ok(api_client.db.set_openai_config(openai_config).await?)
+                Ok(db.set_openai_config(openai_config).await?)
             }
             AiProvider::Anthropic => {
-                if let Ok(Some(c)) = api_client.db.get_anthropic_config().await {
+                if let Ok(Some(c)) = db.get_anthropic_config().await {
                     if !self.force {
                         println!("Already configured: {:?}", c);
                         return Ok(());
@@ -98,7 +96,7 @@ impl Executable for SetProvider {
                     api_key: self.api_key.to_string(),
                 };
 
-                Ok(api_client.db.set_anthropic_config(anthropic_config).await?)
+                Ok(db.set_anthropic_config(anthropic_config).await?)
             }
         }
     }
@@ -118,10 +116,10 @@ pub struct GetProvider {
 #[async_trait]
 impl Executable for GetProvider {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
+        let db = Database::initialize().await?;
         match self.provider {
             AiProvider::Openai => {
-                if let Ok(Some(c)) = api_client.db.get_openai_config().await {
+                if let Ok(Some(c)) = db.get_openai_config().await {
                     println!("{:?}", c);
                     return Ok(());
                 }
@@ -130,7 +128,7 @@ impl Executable for GetProvider {
                 ))
             }
             AiProvider::Anthropic => {
-                if let Ok(Some(c)) = api_client.db.get_anthropic_config().await {
+                if let Ok(Some(c)) = db.get_anthropic_config().await {
                     println!("{:?}", c);
                     return Ok(());
                 }
@@ -155,11 +153,8 @@ pub struct SetDefault {
 #[async_trait]
 impl Executable for SetDefault {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        Ok(api_client
-            .db
-            .set_default_ai_provider(self.provider.clone())
-            .await?)
+        let db = Database::initialize().await?;
+        Ok(db.set_default_ai_provider(self.provider.clone()).await?)
     }
 }
 
@@ -173,8 +168,8 @@ pub struct GetDefault;
 #[async_trait]
 impl Executable for GetDefault {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        if let Ok(Some(c)) = api_client.db.get_default_ai_provider().await {
+        let db = Database::initialize().await?;
+        if let Ok(Some(c)) = db.get_default_ai_provider().await {
             println!("{:?}", c);
             return Ok(());
         }
@@ -1,6 +1,8 @@
 use super::ai::command::Ai;
+use super::encryption::command::{Decrypt, Encrypt};
 use super::okta::command::OktaConfigCommands;
 use super::onelogin::command::OneloginConfigCommands;
+use super::profiles::command::ProfilesCommands;
 use super::tenants::command::Tenants;
 
 use crate::common::command::{ambassador_impl_Executable, Executable};
@@ -27,4 +29,14 @@ pub enum ConfigCommands {
     /// Configure Onelogin settings to enable the CLI tool to interact with Onelogin APIs
     #[clap(subcommand)]
     Onelogin(OneloginConfigCommands),
+
+    /// List configuration profiles
+    #[clap(subcommand)]
+    Profiles(ProfilesCommands),
+
+    /// Encrypt secrets stored in the local configuration database with a passphrase
+    Encrypt(Encrypt),
+
+    /// Decrypt secrets stored in the local configuration database and disable encryption
+    Decrypt(Decrypt),
 }
@@ -0,0 +1,49 @@
+use crate::common::database::crypto::read_new_passphrase;
+use crate::common::database::Database;
+use crate::common::{command::Executable, error::BiError};
+
+use async_trait::async_trait;
+use clap::Args;
+
+// ====================================
+// Encrypt
+// ====================================
+
+#[derive(Args)]
+pub struct Encrypt;
+
+#[async_trait]
+impl Executable for Encrypt {
+    async fn execute(&self) -> Result<(), BiError> {
+        let db = Database::initialize().await?;
+        if db.is_encrypted() {
+            println!("Configuration is already encrypted.");
+            return Ok(());
+        }
+        let passphrase = read_new_passphrase()?;
+        db.enable_encryption(&passphrase).await?;
+        println!("Configuration encrypted. Set BI_PASSPHRASE or enter the passphrase when prompted to unlock it.");
+        Ok(())
+    }
+}
+
+// ====================================
+// Decrypt
+// ====================================
+
+#[derive(Args)]
+pub struct Decrypt;
+
+#[async_trait]
+impl Executable for Decrypt {
+    async fn execute(&self) -> Result<(), BiError> {
+        let db = Database::initialize().await?;
+        if !db.is_encrypted() {
+            println!("Configuration is not encrypted.");
+            return Ok(());
+        }
+        db.disable_encryption().await?;
+        println!("Configuration decrypted.");
+        Ok(())
+    }
+}
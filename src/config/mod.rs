@@ -1,5 +1,7 @@
 pub mod ai;
 pub mod command;
-pub mod tenants;
+pub mod encryption;
 pub mod okta;
 pub mod onelogin;
+pub mod profiles;
+pub mod tenants;
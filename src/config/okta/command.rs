@@ -1,10 +1,13 @@
-use crate::beyond_identity::api::common::api_client::ApiClient;
 use crate::common::command::ambassador_impl_Executable;
-use crate::common::database::models::OktaConfig;
+use crate::common::database::models::{OktaConfig, OktaOAuthConfig};
+use crate::common::database::Database;
+use crate::common::secret;
 use crate::common::{command::Executable, error::BiError};
+use crate::okta::auth;
 
 use async_trait::async_trait;
 use clap::{Args, Subcommand};
+use std::path::PathBuf;
 
 // ====================================
 // Okta Commands
@@ -31,9 +34,31 @@ pub struct Set {
     #[clap(long)]
     domain: String,
 
-    /// Okta API key
-    #[clap(long)]
-    api_key: String,
+    /// Okta API key. Accepts a secret reference such as `env:NAME`, `file:/path` or `cmd:command`.
+    #[clap(
+        long,
+        required_unless_present = "client_id",
+        conflicts_with = "client_id"
+    )]
+    api_key: Option<String>,
+
+    /// Client ID of an Okta API service app to authenticate as instead of using an API key.
+    /// Access tokens are requested with a JWT signed by the private key and bound with DPoP if
+    /// the service app requires it.
+    #[clap(long, requires = "private_key_file")]
+    client_id: Option<String>,
+
+    /// PEM file with the RSA or EC private key registered with the service app.
+    #[clap(long, requires = "client_id")]
+    private_key_file: Option<PathBuf>,
+
+    /// Key ID of the private key, if the service app has several keys.
+    #[clap(long, requires = "client_id")]
+    key_id: Option<String>,
+
+    /// Scopes to request for the service app.
+    #[clap(long, use_value_delimiter = true, default_value = auth::DEFAULT_SCOPES)]
+    scopes: Vec<String>,
 
     /// Flag to allow force reconfiguration
     #[clap(long)]
@@ -43,20 +68,41 @@ pub struct Set {
 #[async_trait]
 impl Executable for Set {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        if let Ok(Some(c)) = api_client.db.get_okta_config().await {
+        let db = Database::initialize().await?;
+        if let Some(c) = db.get_okta_config().await? {
             if !self.force {
-                println!("Already configured: {:?}", c);
+                println!("Already configured for {}", c.domain);
                 return Ok(());
             } else {
                 println!("Forcing reconfiguration...");
             }
         }
+        let oauth = match (&self.client_id, &self.private_key_file) {
+            (Some(client_id), Some(private_key_file)) => {
+                // Store an absolute path and fail early on an unusable key
+                let private_key_file = std::fs::canonicalize(private_key_file).map_err(|e| {
+                    BiError::StringError(format!(
+                        "Failed to read {}: {}",
+                        private_key_file.display(),
+                        e
+                    ))
+                })?;
+                auth::load_private_key(&private_key_file)?;
+                Some(OktaOAuthConfig {
+                    client_id: client_id.to_string(),
+                    private_key_file,
+                    key_id: self.key_id.clone(),
+                    scopes: self.scopes.clone(),
+                })
+            }
+            _ => None,
+        };
         let okta_config = OktaConfig {
             domain: self.domain.to_string(),
-            api_key: self.api_key.to_string(),
+            api_key: self.api_key.clone(),
+            oauth,
         };
-        Ok(api_client.db.set_okta_config(okta_config).await?)
+        Ok(db.set_okta_config(okta_config).await?)
     }
 }
 
@@ -70,9 +116,20 @@ pub struct Get;
 #[async_trait]
 impl Executable for Get {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        if let Ok(Some(c)) = api_client.db.get_okta_config().await {
-            println!("{:?}", c);
+        let db = Database::initialize().await?;
+        if let Some(c) = db.get_okta_config().await? {
+            println!("Domain: {}", c.domain);
+            if let Some(api_key) = &c.api_key {
+                println!("API key: {}", secret::display(api_key));
+            }
+            if let Some(oauth) = &c.oauth {
+                println!("Client ID: {}", oauth.client_id);
+                println!("Private key file: {}", oauth.private_key_file.display());
+                if let Some(key_id) = &oauth.key_id {
+                    println!("Key ID: {}", key_id);
+                }
+                println!("Scopes: {}", oauth.scopes.join(","));
+            }
             return Ok(());
         }
         return Err(BiError::StringError("Okta not yet configured".to_string()));
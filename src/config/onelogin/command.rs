@@ -1,6 +1,7 @@
-use crate::beyond_identity::api::common::api_client::ApiClient;
 use crate::common::command::ambassador_impl_Executable;
 use crate::common::database::models::OneloginConfig;
+use crate::common::database::Database;
+use crate::common::secret;
 use crate::common::{command::Executable, error::BiError};
 
 use async_trait::async_trait;
@@ -35,7 +36,7 @@ pub struct Set {
     #[clap(long)]
     client_id: String,
 
-    /// Onelogin client secret
+    /// Onelogin client secret. Accepts a secret reference such as `env:NAME`, `file:/path` or `cmd:command`.
     #[clap(long)]
     client_secret: String,
 
@@ -47,10 +48,10 @@ pub struct Set {
 #[async_trait]
 impl Executable for Set {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        if let Ok(Some(c)) = api_client.db.get_onelogin_config().await {
+        let db = Database::initialize().await?;
+        if let Some(c) = db.get_onelogin_config().await? {
             if !self.force {
-                println!("Already configured: {:?}", c);
+                println!("Already configured for {}", c.domain);
                 return Ok(());
             } else {
                 println!("Forcing reconfiguration...");
@@ -61,7 +62,7 @@ impl Executable for Set {
             client_id: self.client_id.to_string(),
             client_secret: self.client_secret.to_string(),
         };
-        Ok(api_client.db.set_onelogin_config(onelogin_config).await?)
+        Ok(db.set_onelogin_config(onelogin_config).await?)
     }
 }
 
@@ -75,9 +76,11 @@ pub struct Get;
 #[async_trait]
 impl Executable for Get {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        if let Ok(Some(c)) = api_client.db.get_onelogin_config().await {
-            println!("{:?}", c);
+        let db = Database::initialize().await?;
+        if let Some(c) = db.get_onelogin_config().await? {
+            println!("Domain: {}", c.domain);
+            println!("Client ID: {}", c.client_id);
+            println!("Client secret: {}", secret::display(&c.client_secret));
             return Ok(());
         }
         return Err(BiError::StringError(
@@ -0,0 +1,63 @@
+use crate::common::command::ambassador_impl_Executable;
+use crate::common::database::Database;
+use crate::common::{command::Executable, error::BiError};
+
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+
+// ====================================
+// Profiles Commands
+// ====================================
+
+/// Actions for inspecting configuration profiles. Profiles are selected with `--profile` or
+/// `BI_PROFILE` and are created the first time they are used.
+#[derive(Subcommand, ambassador::Delegate)]
+#[delegate(Executable)]
+pub enum ProfilesCommands {
+    /// Display a list of all configuration profiles. The active profile is marked with `*`.
+    List(List),
+
+    /// Display the active configuration profile.
+    Current(Current),
+}
+
+// ====================================
+// Profiles List
+// ====================================
+
+#[derive(Args)]
+pub struct List;
+
+#[async_trait]
+impl Executable for List {
+    async fn execute(&self) -> Result<(), BiError> {
+        let active_profile = Database::active_profile();
+        println!(
+            "Configuration directory: {}",
+            Database::config_dir()?.display()
+        );
+        for profile in Database::profiles()? {
+            if profile == active_profile {
+                println!("* {}", profile);
+            } else {
+                println!("  {}", profile);
+            }
+        }
+        Ok(())
+    }
+}
+
+// ====================================
+// Profiles Current
+// ====================================
+
+#[derive(Args)]
+pub struct Current;
+
+#[async_trait]
+impl Executable for Current {
+    async fn execute(&self) -> Result<(), BiError> {
+        println!("{}", Database::active_profile());
+        Ok(())
+    }
+}
@@ -1,12 +1,16 @@
-use super::tenant::{delete_tenant_ui, list_tenants_ui, provision_tenant, set_default_tenant_ui};
+use super::tenant::{
+    delete_tenant_ui, list_tenants_ui, provision_tenant, provision_tenant_with_credentials,
+    set_default_tenant_ui, set_realm_urls, TenantCredentials,
+};
+use super::urls::BaseUrls;
 
 use crate::{
-    beyond_identity::api::common::{
-        api_client::ApiClient, middleware::rate_limit::RespectRateLimitMiddleware,
-    },
+    beyond_identity::api::common::middleware::rate_limit::RespectRateLimitMiddleware,
     common::{
         command::{ambassador_impl_Executable, Executable},
+        database::Database,
         error::BiError,
+        global,
     },
 };
 
@@ -17,7 +21,8 @@ use clap::{Args, Subcommand};
 #[derive(Subcommand, ambassador::Delegate)]
 #[delegate(Executable)]
 pub enum Tenants {
-    /// Provision an existing tenant using the provided API token.
+    /// Provision an existing tenant using the provided API token, or non-interactively from the
+    /// client credentials of the tenant's management API application.
     Add(Add),
 
     /// Display a list of all configured tenants.
@@ -29,6 +34,10 @@ pub enum Tenants {
 
     /// Remove a tenant from the configured list.
     Remove(Remove),
+
+    /// Update the API and auth base URLs of a configured tenant/realm. Without explicit URLs or a
+    /// region, the URLs are discovered from the realm's OpenID configuration.
+    SetUrls(SetUrls),
 }
 
 /// Actions for managing the default tenant/realm.
@@ -45,21 +54,76 @@ pub enum DefaultCommands {
 #[derive(Args)]
 pub struct Add {
     /// The API token associated with the tenant/realm you would like to add.
-    #[clap(long)]
-    token: String,
+    #[clap(
+        long,
+        required_unless_present = "client_id",
+        conflicts_with = "client_id"
+    )]
+    token: Option<String>,
+
+    /// ID of the tenant. Required when configuring from client credentials.
+    #[clap(long, requires = "client_id")]
+    tenant_id: Option<String>,
+
+    /// ID of the realm. Required when configuring from client credentials.
+    #[clap(long, requires = "client_id")]
+    realm_id: Option<String>,
+
+    /// ID of the management API application. Required when configuring from client credentials.
+    #[clap(long, requires = "client_id")]
+    application_id: Option<String>,
+
+    /// Client ID of the management API application.
+    #[clap(
+        long,
+        requires_all = ["tenant_id", "realm_id", "application_id", "client_secret"]
+    )]
+    client_id: Option<String>,
+
+    /// Client secret of the management API application. Accepts a secret reference such as
+    /// `env:NAME`, `file:/path` or `cmd:command`.
+    #[clap(long, requires = "client_id")]
+    client_secret: Option<String>,
+
+    #[clap(flatten)]
+    urls: BaseUrls,
+
+    /// Additional token issuer hosts to trust, for self-hosted environments. Tokens issued by
+    /// Beyond Identity domains are always trusted.
+    #[clap(
+        long = "allowed-issuer-host",
+        env = "BI_ALLOWED_ISSUER_HOSTS",
+        value_delimiter = ','
+    )]
+    allowed_issuer_hosts: Vec<String>,
 }
 
 #[async_trait]
 impl Executable for Add {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        _ = provision_tenant(
-            &RespectRateLimitMiddleware::new_client(),
-            &api_client.db,
-            &self.token,
-        )
-        .await
-        .expect("Failed to provision tenant/realm");
+        let db = Database::initialize().await?;
+        let client = RespectRateLimitMiddleware::new_client(&global::options().http_options())?;
+        match &self.token {
+            Some(token) => {
+                _ = provision_tenant(&client, &db, token, &self.urls, &self.allowed_issuer_hosts)
+                    .await?
+            }
+            None => {
+                _ = provision_tenant_with_credentials(
+                    &client,
+                    &db,
+                    TenantCredentials {
+                        tenant_id: self.tenant_id.clone().unwrap_or_default(),
+                        realm_id: self.realm_id.clone().unwrap_or_default(),
+                        application_id: self.application_id.clone().unwrap_or_default(),
+                        client_id: self.client_id.clone().unwrap_or_default(),
+                        client_secret: self.client_secret.clone().unwrap_or_default(),
+                    },
+                    &self.urls,
+                )
+                .await?
+            }
+        }
         Ok(())
     }
 }
@@ -70,8 +134,8 @@ pub struct List;
 #[async_trait]
 impl Executable for List {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        Ok(list_tenants_ui(&api_client.db).await?)
+        let db = Database::initialize().await?;
+        Ok(list_tenants_ui(&db).await?)
     }
 }
 
@@ -81,8 +145,8 @@ pub struct SetDefault;
 #[async_trait]
 impl Executable for SetDefault {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        Ok(set_default_tenant_ui(&api_client.db).await?)
+        let db = Database::initialize().await?;
+        Ok(set_default_tenant_ui(&db).await?)
     }
 }
 
@@ -92,8 +156,8 @@ pub struct GetDefault;
 #[async_trait]
 impl Executable for GetDefault {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        Ok(list_tenants_ui(&api_client.db).await?)
+        let db = Database::initialize().await?;
+        Ok(list_tenants_ui(&db).await?)
     }
 }
 
@@ -103,7 +167,36 @@ pub struct Remove;
 #[async_trait]
 impl Executable for Remove {
     async fn execute(&self) -> Result<(), BiError> {
-        let api_client = ApiClient::new(None, None).await;
-        Ok(delete_tenant_ui(&api_client.db).await?)
+        let db = Database::initialize().await?;
+        Ok(delete_tenant_ui(&db).await?)
+    }
+}
+
+#[derive(Args)]
+pub struct SetUrls {
+    /// ID of the tenant to update. Defaults to the default tenant.
+    #[clap(long, requires = "realm_id")]
+    tenant_id: Option<String>,
+
+    /// ID of the realm to update. Defaults to the default realm.
+    #[clap(long, requires = "tenant_id")]
+    realm_id: Option<String>,
+
+    #[clap(flatten)]
+    urls: BaseUrls,
+}
+
+#[async_trait]
+impl Executable for SetUrls {
+    async fn execute(&self) -> Result<(), BiError> {
+        let db = Database::initialize().await?;
+        set_realm_urls(
+            &RespectRateLimitMiddleware::new_client(&global::options().http_options())?,
+            &db,
+            self.tenant_id.as_deref(),
+            self.realm_id.as_deref(),
+            &self.urls,
+        )
+        .await
     }
 }
@@ -1,3 +1,5 @@
 pub mod application;
 pub mod command;
 pub mod tenant;
+pub mod token;
+pub mod urls;
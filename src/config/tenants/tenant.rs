@@ -1,16 +1,18 @@
 use crate::beyond_identity::api;
+use crate::beyond_identity::api::common::middleware::authorization::fetch_token;
 use crate::beyond_identity::api::common::service::{RealmsService, TenantsService};
 use crate::beyond_identity::api::realms::api::RealmsApi;
 use crate::beyond_identity::api::tenants::api::TenantsApi;
 use crate::common::database;
 use crate::common::database::Database;
 use crate::common::error::BiError;
+use crate::common::secret;
 use crate::config::tenants::application::get_management_api_application;
+use crate::config::tenants::token::verify_token;
+use crate::config::tenants::urls::{derive_api_base_url, discover_auth_base_url, BaseUrls, Region};
 
 use futures::future::join_all;
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use reqwest_middleware::ClientWithMiddleware as Client;
-use serde::{Deserialize, Serialize};
 use std::io::{self, Write};
 use tabled::settings::object::Rows;
 use tabled::settings::themes::Colorization;
@@ -21,36 +23,24 @@ use tabled::{
 };
 use url::Url;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    iss: String, // The issuer field in the JWT
+// Everything needed to configure a tenant/realm without an API token.
+pub struct TenantCredentials {
+    pub tenant_id: String,
+    pub realm_id: String,
+    pub application_id: String,
+    pub client_id: String,
+    pub client_secret: String,
 }
 
 pub async fn provision_tenant(
     client: &Client,
     db: &Database,
     token: &str,
+    urls: &BaseUrls,
+    allowed_issuer_hosts: &[String],
 ) -> Result<(database::models::Tenant, database::models::Realm), BiError> {
-    let mut validation = Validation::new(Algorithm::RS256);
-    validation.insecure_disable_signature_validation();
-    validation.validate_aud = false;
-
-    // Decode the JWT (without signature verification)
-    let decoded_token = match decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret("".as_ref()), // A dummy secret since we are disabling signature verification
-        &validation,
-    ) {
-        Ok(token) => token,
-        Err(err) => {
-            return Err(BiError::StringError(format!(
-                "Failed to decode JWT: {:?}",
-                err
-            )));
-        }
-    };
-
-    let claims = decoded_token.claims;
+    // Verify the token before trusting anything it claims
+    let claims = verify_token(client, token, allowed_issuer_hosts).await?;
 
     // Extract the issuer (iss) from the claims
     let issuer_url = claims.iss;
@@ -74,11 +64,21 @@ pub async fn provision_tenant(
         .get(6)
         .ok_or(BiError::StringError("Invalid application ID".to_string()))?
         .to_string();
-    let auth_base_url = parsed_url.origin().ascii_serialization();
-    let api_base_url = if auth_base_url.contains("localhost") {
-        auth_base_url.replace("localhost:8021", "localhost:8023")
-    } else {
-        auth_base_url.replace("auth", "api")
+    let open_id_configuration_url = format!("{}/.well-known/openid-configuration", issuer_url);
+
+    // Explicit URLs win, then whatever the realm advertises, then the issuer itself
+    let auth_base_url = match urls.auth_base_url() {
+        Some(url) => url,
+        None => discover_auth_base_url(client, &open_id_configuration_url)
+            .await
+            .unwrap_or_else(|e| {
+                log::debug!("Failed to discover auth base URL: {}", e);
+                parsed_url.origin().ascii_serialization()
+            }),
+    };
+    let api_base_url = match urls.api_base_url() {
+        Some(url) => url,
+        None => derive_api_base_url(&auth_base_url)?,
     };
 
     let management_api_application =
@@ -103,17 +103,70 @@ pub async fn provision_tenant(
         application_id,
         client_id,
         client_secret,
-        open_id_configuration_url: format!("{}/.well-known/openid-configuration", issuer_url),
+        open_id_configuration_url,
         api_base_url,
         auth_base_url,
     };
 
+    store_tenant_and_realm(db, tenant, realm).await
+}
+
+pub async fn provision_tenant_with_credentials(
+    client: &Client,
+    db: &Database,
+    credentials: TenantCredentials,
+    urls: &BaseUrls,
+) -> Result<(database::models::Tenant, database::models::Realm), BiError> {
+    let auth_base_url = urls
+        .auth_base_url()
+        .unwrap_or_else(|| Region::Us.auth_base_url().to_string());
+    let api_base_url = match urls.api_base_url() {
+        Some(url) => url,
+        None => derive_api_base_url(&auth_base_url)?,
+    };
+
+    let tenant = database::models::Tenant {
+        id: credentials.tenant_id.clone(),
+    };
+
+    let realm = database::models::Realm {
+        id: credentials.realm_id.clone(),
+        tenant_id: credentials.tenant_id.clone(),
+        application_id: credentials.application_id.clone(),
+        client_id: credentials.client_id,
+        client_secret: credentials.client_secret,
+        open_id_configuration_url: format!(
+            "{}/v1/tenants/{}/realms/{}/applications/{}/.well-known/openid-configuration",
+            auth_base_url, credentials.tenant_id, credentials.realm_id, credentials.application_id
+        ),
+        api_base_url,
+        auth_base_url,
+    };
+
+    // Validate the credentials before storing anything
+    let client_secret = secret::resolve(&realm.client_secret)?;
+    let token = fetch_token(client, &tenant, &realm, &client_secret)
+        .await
+        .map_err(|e| BiError::StringError(format!("Failed to validate credentials: {}", e)))?;
+
+    let provisioned = store_tenant_and_realm(db, tenant, realm).await?;
+
+    db.set_token(token).await?;
+
+    Ok(provisioned)
+}
+
+async fn store_tenant_and_realm(
+    db: &Database,
+    tenant: database::models::Tenant,
+    realm: database::models::Realm,
+) -> Result<(database::models::Tenant, database::models::Realm), BiError> {
     let tenants_with_realms = db.get_all_tenants_with_realms().await?;
 
     // Check for existing tenant-realm combination to avoid duplicates
     if tenants_with_realms
         .iter()
-        .any(|(t, realms)| t.id == tenant_id && realms.iter().any(|r| r.id == realm_id))
+        .any(|(t, realms)| t.id == tenant.id && realms.iter().any(|r| r.id == realm.id))
     {
         return Err(BiError::StringError(
             "Tenant/realm already provisioned".to_string(),
@@ -126,13 +179,75 @@ pub async fn provision_tenant(
     // Check if there is already a default tenant and realm
     if db.get_default_tenant_and_realm().await?.is_none() {
         // Set this tenant and realm as the default
-        db.set_default_tenant_and_realm(&tenant_id, &realm_id)
+        db.set_default_tenant_and_realm(&tenant.id, &realm.id)
             .await?;
     }
 
     _ = display(db).await?;
 
-    Ok((tenant.clone(), realm.clone()))
+    Ok((tenant, realm))
+}
+
+pub async fn set_realm_urls(
+    client: &Client,
+    db: &Database,
+    tenant_id: Option<&str>,
+    realm_id: Option<&str>,
+    urls: &BaseUrls,
+) -> Result<(), BiError> {
+    let (tenant_id, realm_id) = match (tenant_id, realm_id) {
+        (Some(t), Some(r)) => (t.to_string(), r.to_string()),
+        _ => db
+            .get_default_tenant_and_realm()
+            .await?
+            .map(|(t, r)| (t.id, r.id))
+            .ok_or_else(|| BiError::StringError("No default tenant/realm set".to_string()))?,
+    };
+
+    let mut realm = db
+        .get_all_tenants_with_realms()
+        .await?
+        .into_iter()
+        .filter(|(t, _)| t.id == tenant_id)
+        .flat_map(|(_, realms)| realms)
+        .find(|r| r.id == realm_id)
+        .ok_or_else(|| {
+            BiError::StringError(format!(
+                "Tenant/realm {}/{} is not configured",
+                tenant_id, realm_id
+            ))
+        })?;
+
+    // Without explicit URLs, rediscover them from the realm's OpenID configuration
+    let auth_base_url = match urls.auth_base_url() {
+        Some(url) => url,
+        None => discover_auth_base_url(client, &realm.open_id_configuration_url).await?,
+    };
+    let api_base_url = match urls.api_base_url() {
+        Some(url) => url,
+        None if auth_base_url == realm.auth_base_url => realm.api_base_url.clone(),
+        None => derive_api_base_url(&auth_base_url)?,
+    };
+
+    if let Some(path) = realm
+        .open_id_configuration_url
+        .strip_prefix(&realm.auth_base_url)
+    {
+        realm.open_id_configuration_url = format!("{}{}", auth_base_url, path);
+    }
+    realm.auth_base_url = auth_base_url;
+    realm.api_base_url = api_base_url;
+
+    db.update_realm_urls(&realm).await?;
+
+    // Any cached token was issued by the previous auth service
+    db.delete_token(&tenant_id, &realm_id).await?;
+
+    println!(
+        "Updated {}/{}: auth_base_url = {}, api_base_url = {}",
+        tenant_id, realm_id, realm.auth_base_url, realm.api_base_url
+    );
+    Ok(())
 }
 
 pub async fn list_tenants_ui(db: &Database) -> Result<(), BiError> {
@@ -219,7 +334,7 @@ async fn display(
     let tenants_with_realms = db.get_all_tenants_with_realms().await?;
     if tenants_with_realms.is_empty() {
         println!("No tenants found.");
-        return Ok(None);
+        Ok(None)
     } else {
         // Fetch the current default tenant and realm
         let (default_tenant, default_realm) = match db.get_default_tenant_and_realm().await? {
@@ -320,7 +435,7 @@ async fn get_fully_resolved_tenant_and_realm(
         .tenant(tenant.clone())
         .realm(realm.clone())
         .build()
-        .await
+        .await?
         .get_tenant()
         .await?;
 
@@ -328,11 +443,11 @@ async fn get_fully_resolved_tenant_and_realm(
         .tenant(tenant.clone())
         .realm(realm.clone())
         .build()
-        .await
+        .await?
         .get_realm(&realm.id)
         .await?;
 
-    return Ok((api_tenant, api_realm));
+    Ok((api_tenant, api_realm))
 }
 
 fn flatten(
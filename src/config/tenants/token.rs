@@ -0,0 +1,366 @@
+use crate::common::error::BiError;
+use crate::config::tenants::urls::fetch_open_id_configuration;
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use reqwest_middleware::ClientWithMiddleware as Client;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+// Domains Beyond Identity issues API tokens from. Issuers on any other host
+// must be allowed explicitly.
+const ALLOWED_ISSUER_DOMAINS: &[&str] = &[
+    "beyondidentity.com",
+    "beyondidentity.run",
+    "beyondidentity.xyz",
+    "beyondidentity.dev",
+    "beyondidentity.net",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub iss: String, // The issuer field in the JWT
+}
+
+// Verify an API token and return its claims. The issuer host is checked
+// against the allow-list before anything is fetched from it, then the token
+// signature is verified against the JWKS advertised by the issuer's OpenID
+// configuration along with its `exp` and `nbf` claims.
+pub async fn verify_token(
+    client: &Client,
+    token: &str,
+    allowed_issuer_hosts: &[String],
+) -> Result<Claims, BiError> {
+    let header = decode_header(token)
+        .map_err(|err| BiError::StringError(format!("Failed to decode JWT: {:?}", err)))?;
+
+    // Read the issuer without verification so we know where to fetch keys from
+    let issuer = unverified_issuer(token, header.alg)?;
+    check_issuer_host(&issuer, allowed_issuer_hosts)?;
+
+    let configuration = fetch_open_id_configuration(
+        client,
+        &format!("{}/.well-known/openid-configuration", issuer),
+    )
+    .await?;
+    if configuration.issuer != issuer {
+        return Err(BiError::StringError(format!(
+            "Issuer mismatch: token was issued by {} but the OpenID configuration is for {}",
+            issuer, configuration.issuer
+        )));
+    }
+    let jwks_uri = configuration.jwks_uri.ok_or_else(|| {
+        BiError::StringError("OpenID configuration does not include a jwks_uri".to_string())
+    })?;
+
+    let response = client.get(&jwks_uri).send().await?;
+    let status = response.status();
+    let response_text = response.text().await?;
+
+    log::debug!(
+        "{} response status: {} and text: {}",
+        jwks_uri,
+        status,
+        response_text
+    );
+
+    if !status.is_success() {
+        return Err(BiError::RequestError(status, response_text));
+    }
+
+    let jwks: JwkSet = serde_json::from_str(&response_text)?;
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| BiError::StringError("No matching signing key found for JWT".to_string()))?;
+
+    let key = DecodingKey::from_jwk(jwk)
+        .map_err(|err| BiError::StringError(format!("Invalid signing key: {:?}", err)))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.validate_aud = false;
+    validation.validate_exp = true;
+    validation.validate_nbf = true;
+    validation.set_issuer(&[&issuer]);
+    validation.set_required_spec_claims(&["exp", "iss"]);
+
+    decode::<Claims>(token, &key, &validation)
+        .map(|decoded| decoded.claims)
+        .map_err(|err| BiError::StringError(format!("Failed to verify JWT: {:?}", err)))
+}
+
+fn unverified_issuer(token: &str, algorithm: Algorithm) -> Result<String, BiError> {
+    // Only asymmetric algorithms can be verified against a JWKS
+    if matches!(
+        algorithm,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(BiError::StringError(format!(
+            "Unsupported JWT algorithm: {:?}",
+            algorithm
+        )));
+    }
+
+    let mut validation = Validation::new(algorithm);
+    validation.insecure_disable_signature_validation();
+    validation.validate_aud = false;
+    validation.validate_exp = false;
+
+    decode::<Claims>(
+        token,
+        &DecodingKey::from_secret("".as_ref()), // A dummy secret since we are disabling signature verification
+        &validation,
+    )
+    .map(|decoded| decoded.claims.iss)
+    .map_err(|err| BiError::StringError(format!("Failed to decode JWT: {:?}", err)))
+}
+
+fn check_issuer_host(issuer: &str, allowed_issuer_hosts: &[String]) -> Result<(), BiError> {
+    let url = Url::parse(issuer).map_err(BiError::InvalidUrl)?;
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+
+    let allowed_domain = url.scheme() == "https"
+        && ALLOWED_ISSUER_DOMAINS
+            .iter()
+            .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)));
+    let allowed_host = allowed_issuer_hosts
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(&host));
+
+    if allowed_domain || allowed_host {
+        Ok(())
+    } else {
+        Err(BiError::StringError(format!(
+            "Refusing to trust token issuer {}. Use --allowed-issuer-host {} if this is a self-hosted environment.",
+            issuer, host
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{routing::get, Json, Router};
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use reqwest_middleware::ClientBuilder;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+    use serde_json::{json, Value};
+
+    const KID: &str = "key-1";
+
+    struct SigningKey {
+        encoding_key: EncodingKey,
+        jwk: Value,
+    }
+
+    fn signing_key(kid: &str) -> SigningKey {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                .unwrap();
+        // Uncompressed point: 0x04 || x || y
+        let point = key_pair.public_key().as_ref();
+        SigningKey {
+            encoding_key: EncodingKey::from_ec_der(pkcs8.as_ref()),
+            jwk: json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+                "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+                "kid": kid,
+                "alg": "ES256",
+                "use": "sig",
+            }),
+        }
+    }
+
+    fn now() -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+
+    fn sign(key: &SigningKey, kid: Option<&str>, claims: Value) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = kid.map(String::from);
+        encode(&header, &claims, &key.encoding_key).unwrap()
+    }
+
+    // Serve an OpenID configuration and JWKS on a local port, returning the
+    // issuer URL. The configuration advertises `advertised_issuer` if given.
+    async fn serve_issuer(jwks: Value, advertised_issuer: Option<&str>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let configuration = json!({
+            "issuer": advertised_issuer.unwrap_or(&issuer),
+            "jwks_uri": format!("{}/jwks", issuer),
+        });
+        let app = Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                get(move || async move { Json(configuration) }),
+            )
+            .route("/jwks", get(move || async move { Json(jwks) }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        issuer
+    }
+
+    fn client() -> Client {
+        ClientBuilder::new(reqwest::Client::new()).build()
+    }
+
+    fn localhost() -> Vec<String> {
+        vec!["127.0.0.1".to_string()]
+    }
+
+    #[tokio::test]
+    async fn accepts_valid_token() {
+        let key = signing_key(KID);
+        let issuer = serve_issuer(json!({ "keys": [key.jwk] }), None).await;
+        let token = sign(
+            &key,
+            Some(KID),
+            json!({ "iss": issuer, "exp": now() + 300, "nbf": now() - 10 }),
+        );
+
+        let claims = verify_token(&client(), &token, &localhost()).await.unwrap();
+        assert_eq!(claims.iss, issuer);
+    }
+
+    #[tokio::test]
+    async fn uses_only_key_when_kid_is_missing() {
+        let key = signing_key(KID);
+        let issuer = serve_issuer(json!({ "keys": [key.jwk] }), None).await;
+        let token = sign(&key, None, json!({ "iss": issuer, "exp": now() + 300 }));
+
+        assert!(verify_token(&client(), &token, &localhost()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_kid() {
+        let key = signing_key(KID);
+        let issuer = serve_issuer(json!({ "keys": [key.jwk] }), None).await;
+        let token = sign(
+            &key,
+            Some("other-key"),
+            json!({ "iss": issuer, "exp": now() + 300 }),
+        );
+
+        assert!(verify_token(&client(), &token, &localhost()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_token_signed_by_another_key() {
+        let key = signing_key(KID);
+        let forged = signing_key(KID);
+        let issuer = serve_issuer(json!({ "keys": [key.jwk] }), None).await;
+        let token = sign(
+            &forged,
+            Some(KID),
+            json!({ "iss": issuer, "exp": now() + 300 }),
+        );
+
+        assert!(verify_token(&client(), &token, &localhost()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_expired_and_not_yet_valid_tokens() {
+        let key = signing_key(KID);
+        let issuer = serve_issuer(json!({ "keys": [key.jwk] }), None).await;
+
+        let expired = sign(
+            &key,
+            Some(KID),
+            json!({ "iss": issuer, "exp": now() - 600 }),
+        );
+        assert!(verify_token(&client(), &expired, &localhost())
+            .await
+            .is_err());
+
+        let not_yet_valid = sign(
+            &key,
+            Some(KID),
+            json!({ "iss": issuer, "exp": now() + 1200, "nbf": now() + 600 }),
+        );
+        assert!(verify_token(&client(), &not_yet_valid, &localhost())
+            .await
+            .is_err());
+
+        let without_exp = sign(&key, Some(KID), json!({ "iss": issuer }));
+        assert!(verify_token(&client(), &without_exp, &localhost())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_issuer_mismatch() {
+        let key = signing_key(KID);
+        let issuer = serve_issuer(
+            json!({ "keys": [key.jwk] }),
+            Some("https://auth.beyondidentity.com/v1/tenants/t"),
+        )
+        .await;
+        let token = sign(
+            &key,
+            Some(KID),
+            json!({ "iss": issuer, "exp": now() + 300 }),
+        );
+
+        assert!(verify_token(&client(), &token, &localhost()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_symmetric_algorithms() {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(KID.to_string());
+        let token = encode(
+            &header,
+            &json!({ "iss": "https://auth.beyondidentity.com", "exp": now() + 300 }),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        let err = verify_token(&client(), &token, &[]).await.unwrap_err();
+        assert!(err.to_string().contains("Unsupported JWT algorithm"));
+    }
+
+    #[tokio::test]
+    async fn rejects_issuer_not_allowed_before_fetching() {
+        let key = signing_key(KID);
+        // Nothing listens here, so the check must fail before any request
+        let token = sign(
+            &key,
+            Some(KID),
+            json!({ "iss": "http://127.0.0.1:9", "exp": now() + 300 }),
+        );
+
+        let err = verify_token(&client(), &token, &[]).await.unwrap_err();
+        assert!(err.to_string().contains("Refusing to trust token issuer"));
+    }
+
+    #[test]
+    fn issuer_host_allow_list() {
+        let allowed = |issuer: &str, hosts: &[&str]| {
+            let hosts: Vec<String> = hosts.iter().map(|host| host.to_string()).collect();
+            check_issuer_host(issuer, &hosts).is_ok()
+        };
+
+        assert!(allowed(
+            "https://auth-us.beyondidentity.com/v1/tenants/t",
+            &[]
+        ));
+        assert!(allowed("https://beyondidentity.run/v1", &[]));
+        assert!(allowed("https://AUTH.BeyondIdentity.com", &[]));
+        assert!(!allowed("http://auth-us.beyondidentity.com", &[]));
+        assert!(!allowed("https://evilbeyondidentity.com", &[]));
+        assert!(!allowed("https://beyondidentity.com.evil.com", &[]));
+        assert!(!allowed("https://example.com", &[]));
+        assert!(allowed("https://idp.example.com", &["IDP.example.com"]));
+        assert!(!allowed("https://other.example.com", &["idp.example.com"]));
+        assert!(!allowed("not a url", &[]));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::common::error::BiError;
+
+use clap::{Args, ValueEnum};
+use reqwest_middleware::ClientWithMiddleware as Client;
+use serde::Deserialize;
+use url::Url;
+
+/// Beyond Identity hosting regions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Region {
+    Us,
+    Eu,
+}
+
+impl Region {
+    pub fn auth_base_url(&self) -> &'static str {
+        match self {
+            Region::Us => "https://auth-us.beyondidentity.com",
+            Region::Eu => "https://auth-eu.beyondidentity.com",
+        }
+    }
+
+    pub fn api_base_url(&self) -> &'static str {
+        match self {
+            Region::Us => "https://api-us.beyondidentity.com",
+            Region::Eu => "https://api-eu.beyondidentity.com",
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct BaseUrls {
+    /// Base URL of the Beyond Identity API, e.g. https://api-us.beyondidentity.com.
+    /// Takes precedence over --region.
+    #[clap(long)]
+    pub api_base_url: Option<String>,
+
+    /// Base URL of the Beyond Identity auth service, e.g. https://auth-us.beyondidentity.com.
+    /// Takes precedence over --region.
+    #[clap(long)]
+    pub auth_base_url: Option<String>,
+
+    /// Beyond Identity hosting region used to determine the base URLs.
+    #[clap(long, value_enum)]
+    pub region: Option<Region>,
+}
+
+impl BaseUrls {
+    // The explicitly requested auth base URL, if any
+    pub fn auth_base_url(&self) -> Option<String> {
+        self.auth_base_url
+            .as_deref()
+            .or(self.region.map(|r| r.auth_base_url()))
+            .map(normalize)
+    }
+
+    // The explicitly requested API base URL, if any
+    pub fn api_base_url(&self) -> Option<String> {
+        self.api_base_url
+            .as_deref()
+            .or(self.region.map(|r| r.api_base_url()))
+            .map(normalize)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenIdConfiguration {
+    pub issuer: String,
+    pub token_endpoint: Option<String>,
+    pub jwks_uri: Option<String>,
+}
+
+// Fetch the OpenID configuration document of a realm
+pub async fn fetch_open_id_configuration(
+    client: &Client,
+    open_id_configuration_url: &str,
+) -> Result<OpenIdConfiguration, BiError> {
+    let response = client.get(open_id_configuration_url).send().await?;
+
+    let status = response.status();
+    let response_text = response.text().await?;
+
+    log::debug!(
+        "{} response status: {} and text: {}",
+        open_id_configuration_url,
+        status,
+        response_text
+    );
+
+    if !status.is_success() {
+        return Err(BiError::RequestError(status, response_text));
+    }
+
+    Ok(serde_json::from_str(&response_text)?)
+}
+
+// Discover the auth base URL from a realm's OpenID configuration, preferring
+// the token endpoint since that is what the CLI actually calls.
+pub async fn discover_auth_base_url(
+    client: &Client,
+    open_id_configuration_url: &str,
+) -> Result<String, BiError> {
+    let configuration = fetch_open_id_configuration(client, open_id_configuration_url).await?;
+    let endpoint = configuration.token_endpoint.unwrap_or(configuration.issuer);
+    let url = Url::parse(&endpoint).map_err(BiError::InvalidUrl)?;
+    Ok(url.origin().ascii_serialization())
+}
+
+// Derive the API base URL from the auth base URL. This only works for hosts
+// following the Beyond Identity naming scheme where the first label starts
+// with `auth` (e.g. auth-us.beyondidentity.com -> api-us.beyondidentity.com),
+// and for local development where the API listens on port 8023.
+pub fn derive_api_base_url(auth_base_url: &str) -> Result<String, BiError> {
+    let mut url = Url::parse(auth_base_url).map_err(BiError::InvalidUrl)?;
+    let host = url.host_str().unwrap_or_default().to_string();
+
+    if host == "localhost" && url.port() == Some(8021) {
+        _ = url.set_port(Some(8023));
+    } else if let Some(api_host) = host
+        .strip_prefix("auth-")
+        .map(|rest| format!("api-{}", rest))
+        .or_else(|| {
+            host.strip_prefix("auth.")
+                .map(|rest| format!("api.{}", rest))
+        })
+    {
+        url.set_host(Some(&api_host)).map_err(BiError::InvalidUrl)?;
+    } else {
+        return Err(BiError::StringError(format!(
+            "Unable to determine the API base URL from {}. Use --api-base-url or --region.",
+            auth_base_url
+        )));
+    }
+
+    Ok(normalize(url.as_str()))
+}
+
+fn normalize(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
@@ -0,0 +1,94 @@
+use super::mock_server::{self, MockConfig};
+
+use crate::common::{command::Executable, error::BiError};
+
+use async_trait::async_trait;
+use clap::{Args, Subcommand};
+
+// ====================================
+// Dev Commands
+// ====================================
+
+/// Commands for developing and testing against Beyond Identity locally.
+#[derive(Subcommand)]
+pub enum DevCommands {
+    /// Run an in-memory mock of the Beyond Identity API for offline testing.
+    /// Serves tenants, realms, identities, groups, credentials, credential binding jobs,
+    /// authenticator configs and the token endpoint. All data is lost when the server stops.
+    MockServer(MockServer),
+}
+
+#[async_trait]
+impl Executable for DevCommands {
+    async fn execute(&self) -> Result<(), BiError> {
+        match self {
+            DevCommands::MockServer(command) => command.execute().await,
+        }
+    }
+}
+
+// ====================================
+// Dev MockServer
+// ====================================
+
+#[derive(Args)]
+pub struct MockServer {
+    /// Port to listen on. Port 8023 is where the CLI expects a local API.
+    #[clap(long, default_value_t = 8023)]
+    port: u16,
+
+    /// Address to listen on
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// ID of the mock tenant
+    #[clap(long, default_value = "mock-tenant")]
+    tenant_id: String,
+
+    /// ID of the mock realm
+    #[clap(long, default_value = "mock-realm")]
+    realm_id: String,
+
+    /// ID of the application issuing access tokens
+    #[clap(long, default_value = "mock-application")]
+    application_id: String,
+
+    /// Client ID accepted by the token endpoint
+    #[clap(long, default_value = "mock-client")]
+    client_id: String,
+
+    /// Client secret accepted by the token endpoint
+    #[clap(long, default_value = "mock-secret")]
+    client_secret: String,
+}
+
+#[async_trait]
+impl Executable for MockServer {
+    async fn execute(&self) -> Result<(), BiError> {
+        let config = MockConfig {
+            host: self.host.clone(),
+            port: self.port,
+            tenant_id: self.tenant_id.clone(),
+            realm_id: self.realm_id.clone(),
+            application_id: self.application_id.clone(),
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+        };
+
+        let base_url = config.base_url();
+        println!("Mock Beyond Identity API listening on {}", base_url);
+        println!("Point the CLI at it with:");
+        println!(
+            "  bi config tenants add --tenant-id {} --realm-id {} --application-id {} --client-id {} --client-secret {} --api-base-url {} --auth-base-url {}",
+            config.tenant_id,
+            config.realm_id,
+            config.application_id,
+            config.client_id,
+            config.client_secret,
+            base_url,
+            base_url
+        );
+
+        mock_server::serve(config).await
+    }
+}
@@ -0,0 +1,840 @@
+use crate::common::error::BiError;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{SecondsFormat, Utc};
+use convert_case::{Case, Casing};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+// Page size used when the client does not ask for one.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+// Lifetime of issued access tokens in seconds.
+const TOKEN_EXPIRES_IN: u64 = 3600;
+
+// Collections served under a realm along with the key their request bodies
+// wrap the resource in.
+const REALM_COLLECTIONS: &[(&str, &str)] = &[
+    ("identities", "identity"),
+    ("groups", "group"),
+    ("authenticator-configs", "authenticator_config"),
+    ("sso-configs", "sso_config"),
+];
+
+// Collections served under an identity along with the key their request
+// bodies wrap the resource in.
+const IDENTITY_COLLECTIONS: &[(&str, &str)] = &[
+    ("credentials", "credential"),
+    ("credential-binding-jobs", "job"),
+];
+
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    pub host: String,
+    pub port: u16,
+    pub tenant_id: String,
+    pub realm_id: String,
+    pub application_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl MockConfig {
+    pub fn base_url(&self) -> String {
+        format!("http://{}:{}", self.host, self.port)
+    }
+}
+
+// Serve the mock API until the process is stopped
+pub async fn serve(config: MockConfig) -> Result<(), BiError> {
+    let listener = tokio::net::TcpListener::bind((config.host.as_str(), config.port))
+        .await
+        .map_err(|e| {
+            BiError::StringError(format!("Failed to bind {}: {}", config.base_url(), e))
+        })?;
+
+    let store = Arc::new(Mutex::new(Store::new(config)));
+    let app = Router::new().fallback(handle).with_state(store);
+
+    axum::serve(listener, app)
+        .await
+        .map_err(|e| BiError::StringError(e.to_string()))
+}
+
+async fn handle(
+    State(store): State<Arc<Mutex<Store>>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let result = store
+        .lock()
+        .expect("Mock store lock poisoned")
+        .route(&method, &uri, &headers, &body);
+
+    let (status, body) = match result {
+        Ok((status, body)) => (status, body),
+        Err(ApiError(status, message)) => (
+            status,
+            json!({
+                "code": status.canonical_reason().unwrap_or_default().to_lowercase().replace(' ', "_"),
+                "message": message,
+            }),
+        ),
+    };
+
+    log::info!("{} {} -> {}", method, uri, status.as_u16());
+    (status, Json(body)).into_response()
+}
+
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn not_found(what: &str) -> Self {
+        ApiError(StatusCode::NOT_FOUND, format!("{} not found", what))
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        ApiError(StatusCode::BAD_REQUEST, message.into())
+    }
+}
+
+type ApiResult = Result<(StatusCode, Value), ApiError>;
+
+struct Store {
+    config: MockConfig,
+    tenant: Value,
+    // Resources keyed by the path of their collection, e.g.
+    // `tenants/t/realms/r/identities`, in creation order.
+    collections: HashMap<String, Vec<Value>>,
+    // Identity IDs keyed by the path of the group they belong to
+    members: HashMap<String, Vec<String>>,
+    tokens: HashSet<String>,
+}
+
+impl Store {
+    fn new(config: MockConfig) -> Self {
+        let now = timestamp();
+        let tenant = json!({
+            "id": config.tenant_id,
+            "display_name": "Mock Tenant",
+            "create_time": now,
+            "update_time": now,
+        });
+        let realm = json!({
+            "id": config.realm_id,
+            "tenant_id": config.tenant_id,
+            "display_name": "Mock Realm",
+            "classification": "Secure Workforce",
+            "create_time": now,
+            "update_time": now,
+        });
+
+        let mut collections = HashMap::new();
+        collections.insert(format!("tenants/{}/realms", config.tenant_id), vec![realm]);
+
+        Self {
+            config,
+            tenant,
+            collections,
+            members: HashMap::new(),
+            tokens: HashSet::new(),
+        }
+    }
+
+    fn route(&mut self, method: &Method, uri: &Uri, headers: &HeaderMap, body: &[u8]) -> ApiResult {
+        let query: HashMap<String, String> = uri
+            .query()
+            .map(|q| {
+                url::form_urlencoded::parse(q.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let segments: Vec<&str> = uri.path().trim_matches('/').split('/').collect();
+
+        let segments = match segments.as_slice() {
+            ["v1", "tenants", tenant_id, rest @ ..] if *tenant_id == self.config.tenant_id => rest,
+            ["v1", "tenants", _, ..] => return Err(ApiError::not_found("Tenant")),
+            _ => return Err(ApiError::not_found("Route")),
+        };
+
+        // Endpoints of the auth service are not authenticated with a bearer token
+        if let ["realms", realm_id, "applications", application_id, rest @ ..] = segments {
+            if *realm_id == self.config.realm_id && *application_id == self.config.application_id {
+                match (method, rest) {
+                    (&Method::POST, ["token"]) => return self.token(headers, body),
+                    (&Method::GET, [".well-known", "openid-configuration"]) => {
+                        return Ok((StatusCode::OK, self.openid_configuration()))
+                    }
+                    (&Method::GET, [".well-known", "jwks.json"]) => {
+                        return Ok((StatusCode::OK, json!({ "keys": [] })))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.authorize(headers)?;
+        let body = parse_body(body)?;
+        let tenant_path = format!("tenants/{}", self.config.tenant_id);
+
+        match segments {
+            [] => match *method {
+                Method::GET => Ok((StatusCode::OK, self.tenant.clone())),
+                Method::PATCH => {
+                    merge(&mut self.tenant, body.get("tenant").unwrap_or(&Value::Null));
+                    self.tenant["update_time"] = json!(timestamp());
+                    Ok((StatusCode::OK, self.tenant.clone()))
+                }
+                _ => Err(method_not_allowed()),
+            },
+            ["realms"] => {
+                let path = format!("{}/realms", tenant_path);
+                match *method {
+                    Method::GET => self.list(&path, "realms", &query),
+                    Method::POST => {
+                        let mut realm = wrapped(&body, "realm")?;
+                        if let Some(classification) = body.get("classification") {
+                            realm.insert("classification".to_string(), classification.clone());
+                        }
+                        self.create(
+                            &path,
+                            realm,
+                            &[("tenant_id", &self.config.tenant_id.clone())],
+                        )
+                    }
+                    _ => Err(method_not_allowed()),
+                }
+            }
+            ["realms", realm_id] => self.item(
+                method,
+                &format!("{}/realms", tenant_path),
+                realm_id,
+                "realm",
+                &body,
+            ),
+            ["realms", realm_id, rest @ ..] => {
+                let realm_path = format!("{}/realms/{}", tenant_path, realm_id);
+                self.find(&format!("{}/realms", tenant_path), realm_id)
+                    .ok_or_else(|| ApiError::not_found("Realm"))?;
+                self.realm_route(method, &realm_path, realm_id, rest, &query, &body)
+            }
+            _ => Err(ApiError::not_found("Route")),
+        }
+    }
+
+    fn realm_route(
+        &mut self,
+        method: &Method,
+        realm_path: &str,
+        realm_id: &str,
+        segments: &[&str],
+        query: &HashMap<String, String>,
+        body: &Value,
+    ) -> ApiResult {
+        let parents = [
+            ("tenant_id", self.config.tenant_id.clone()),
+            ("realm_id", realm_id.to_string()),
+        ];
+
+        match segments {
+            [collection] => {
+                let (collection, key) = realm_collection(collection)?;
+                let path = format!("{}/{}", realm_path, collection);
+                match *method {
+                    Method::GET => self.list(&path, &collection.replace('-', "_"), query),
+                    Method::POST => {
+                        let mut resource = wrapped(body, key)?;
+                        if collection == "identities" {
+                            resource
+                                .entry("status".to_string())
+                                .or_insert(json!("active"));
+                        } else if collection == "groups" {
+                            resource
+                                .entry("description".to_string())
+                                .or_insert(json!(""));
+                        } else if collection == "sso-configs" {
+                            tag_payload(&mut resource);
+                            if let Some(Value::Object(payload)) = resource.get_mut("payload") {
+                                for (tag, config) in payload.iter_mut() {
+                                    config["application_tile_id"] = json!(generate_id());
+                                    // Generic OIDC configs are issued client credentials
+                                    if tag == "GenericOidc" {
+                                        config["client_id"] = json!(generate_id());
+                                        config["client_secret"] = json!(generate_id());
+                                    }
+                                }
+                            }
+                        }
+                        self.create(&path, resource, &as_refs(&parents))
+                    }
+                    _ => Err(method_not_allowed()),
+                }
+            }
+            [collection, id] => {
+                let (collection, key) = realm_collection(collection)?;
+                let path = format!("{}/{}", realm_path, collection);
+                match id.split_once(':') {
+                    Some((id, custom_method)) => self.custom_method(
+                        method,
+                        realm_path,
+                        collection,
+                        id,
+                        custom_method,
+                        query,
+                        body,
+                    ),
+                    None => {
+                        let mut body = body.clone();
+                        if let Some(Value::Object(resource)) = body.get_mut(key) {
+                            if collection == "sso-configs" {
+                                tag_payload(resource);
+                            }
+                        }
+                        let response = self.item(method, &path, id, key, &body)?;
+                        if *method == Method::DELETE {
+                            self.remove_references(realm_path, collection, id);
+                        }
+                        Ok(response)
+                    }
+                }
+            }
+            ["identities", identity_id, rest @ ..] => {
+                self.find(&format!("{}/identities", realm_path), identity_id)
+                    .ok_or_else(|| ApiError::not_found("Identity"))?;
+                let identity_path = format!("{}/identities/{}", realm_path, identity_id);
+                let mut parents = parents.to_vec();
+                parents.push(("identity_id", identity_id.to_string()));
+                self.identity_route(
+                    method,
+                    &identity_path,
+                    rest,
+                    query,
+                    body,
+                    &as_refs(&parents),
+                )
+            }
+            _ => Err(ApiError::not_found("Route")),
+        }
+    }
+
+    fn identity_route(
+        &mut self,
+        method: &Method,
+        identity_path: &str,
+        segments: &[&str],
+        query: &HashMap<String, String>,
+        body: &Value,
+        parents: &[(&str, &str)],
+    ) -> ApiResult {
+        let (collection, id) = match segments {
+            [collection] => (*collection, None),
+            [collection, id] => (*collection, Some(*id)),
+            _ => return Err(ApiError::not_found("Route")),
+        };
+        let (collection, key) = IDENTITY_COLLECTIONS
+            .iter()
+            .find(|(name, _)| *name == collection)
+            .copied()
+            .ok_or_else(|| ApiError::not_found("Route"))?;
+        let path = format!("{}/{}", identity_path, collection);
+
+        match (method, collection, id) {
+            (&Method::GET, _, None) => self.list(&path, &collection.replace('-', "_"), query),
+            (&Method::POST, "credential-binding-jobs", None) => {
+                let mut job = wrapped(body, key)?;
+                let delivery_method = job
+                    .get("delivery_method")
+                    .and_then(Value::as_str)
+                    .unwrap_or("RETURN")
+                    .to_string();
+                job.insert(
+                    "state".to_string(),
+                    json!(if delivery_method == "EMAIL" {
+                        "LINK_SENT"
+                    } else {
+                        "REQUEST_DELIVERED"
+                    }),
+                );
+                job.insert(
+                    "expire_time".to_string(),
+                    json!((Utc::now() + chrono::Duration::days(7))
+                        .to_rfc3339_opts(SecondsFormat::Secs, true)),
+                );
+                let (status, job) = self.create(&path, job, parents)?;
+                let link = (delivery_method == "RETURN").then(|| {
+                    format!(
+                        "{}/v1/tenants/{}/realms/{}/bind?job_id={}",
+                        self.config.base_url(),
+                        self.config.tenant_id,
+                        self.config.realm_id,
+                        job["id"].as_str().unwrap_or_default()
+                    )
+                });
+                Ok((
+                    status,
+                    json!({ "credential_binding_job": job, "credential_binding_link": link }),
+                ))
+            }
+            (&Method::GET, _, Some(id)) => self
+                .find(&path, id)
+                .cloned()
+                .map(|r| (StatusCode::OK, r))
+                .ok_or_else(|| ApiError::not_found(key)),
+            (&Method::POST, "credentials", Some(id)) if id.ends_with(":revoke") => {
+                let id = id.trim_end_matches(":revoke");
+                let credential = self
+                    .find_mut(&path, id)
+                    .ok_or_else(|| ApiError::not_found("Credential"))?;
+                credential["state"] = json!("REVOKED");
+                credential["update_time"] = json!(timestamp());
+                Ok((StatusCode::OK, credential.clone()))
+            }
+            _ => Err(method_not_allowed()),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn custom_method(
+        &mut self,
+        method: &Method,
+        realm_path: &str,
+        collection: &str,
+        id: &str,
+        custom_method: &str,
+        query: &HashMap<String, String>,
+        body: &Value,
+    ) -> ApiResult {
+        let path = format!("{}/{}", realm_path, collection);
+        self.find(&path, id)
+            .ok_or_else(|| ApiError::not_found(collection))?;
+        let identities_path = format!("{}/identities", realm_path);
+        let member_key = format!("{}/{}", path, id);
+
+        match (method, collection, custom_method) {
+            (&Method::POST, "groups", "addMembers")
+            | (&Method::POST, "groups", "deleteMembers") => {
+                self.update_members(
+                    &member_key,
+                    &identities_path,
+                    "identity_ids",
+                    body,
+                    custom_method == "addMembers",
+                )?;
+                let group = self.find(&path, id).cloned().unwrap_or_default();
+                Ok((StatusCode::OK, group))
+            }
+            (&Method::POST, "sso-configs", "addIdentities")
+            | (&Method::POST, "sso-configs", "deleteIdentities") => {
+                self.update_members(
+                    &format!("{}/identities", member_key),
+                    &identities_path,
+                    "identity_ids",
+                    body,
+                    custom_method == "addIdentities",
+                )?;
+                Ok((StatusCode::OK, json!({})))
+            }
+            (&Method::GET, "sso-configs", "listIdentities") => {
+                let members = self
+                    .members
+                    .get(&format!("{}/identities", member_key))
+                    .cloned()
+                    .unwrap_or_default();
+                let identities = self.resources(&identities_path, |i| {
+                    members.iter().any(|m| Some(m.as_str()) == i["id"].as_str())
+                });
+                paginate(identities, "identities", query)
+            }
+            (&Method::POST, "sso-configs", "addGroups")
+            | (&Method::POST, "sso-configs", "deleteGroups") => {
+                self.update_members(
+                    &format!("{}/groups", member_key),
+                    &format!("{}/groups", realm_path),
+                    "group_ids",
+                    body,
+                    custom_method == "addGroups",
+                )?;
+                Ok((StatusCode::OK, json!({})))
+            }
+            (&Method::GET, "sso-configs", "listGroups") => {
+                let groups_path = format!("{}/groups", realm_path);
+                let members = self
+                    .members
+                    .get(&format!("{}/groups", member_key))
+                    .cloned()
+                    .unwrap_or_default();
+                let groups = self.resources(&groups_path, |g| {
+                    members.iter().any(|m| Some(m.as_str()) == g["id"].as_str())
+                });
+                paginate(groups, "groups", query)
+            }
+            (&Method::GET, "groups", "listMembers") => {
+                let members = self.members.get(&member_key).cloned().unwrap_or_default();
+                let identities = self.resources(&identities_path, |i| {
+                    members.iter().any(|m| Some(m.as_str()) == i["id"].as_str())
+                });
+                paginate(identities, "identities", query)
+            }
+            (&Method::GET, "identities", "listGroups") => {
+                let groups_path = format!("{}/groups", realm_path);
+                let members = &self.members;
+                let groups = self.resources(&groups_path, |g| {
+                    members
+                        .get(&format!(
+                            "{}/{}",
+                            groups_path,
+                            g["id"].as_str().unwrap_or_default()
+                        ))
+                        .map(|m| m.iter().any(|m| m == id))
+                        .unwrap_or(false)
+                });
+                paginate(groups, "groups", query)
+            }
+            // Roles are not modeled by the mock server
+            (&Method::GET, "groups", "listRoles") | (&Method::GET, "identities", "listRoles") => {
+                paginate(Vec::new(), "roles", query)
+            }
+            _ => Err(ApiError::not_found("Route")),
+        }
+    }
+
+    // Add or remove the resources listed under `field` of the body to the
+    // members stored at `key`. Every listed resource must exist in `path`.
+    fn update_members(
+        &mut self,
+        key: &str,
+        path: &str,
+        field: &str,
+        body: &Value,
+        add: bool,
+    ) -> Result<(), ApiError> {
+        let ids: Vec<String> = body
+            .get(field)
+            .and_then(Value::as_array)
+            .ok_or_else(|| ApiError::bad_request(format!("{} is required", field)))?
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect();
+        if let Some(missing) = ids.iter().find(|i| self.find(path, i).is_none()) {
+            let kind = field.trim_end_matches("_ids").to_case(Case::Pascal);
+            return Err(ApiError::not_found(&format!("{} {}", kind, missing)));
+        }
+
+        let members = self.members.entry(key.to_string()).or_default();
+        if add {
+            for id in ids {
+                if !members.contains(&id) {
+                    members.push(id);
+                }
+            }
+        } else {
+            members.retain(|m| !ids.contains(m));
+        }
+        Ok(())
+    }
+
+    // Get, patch or delete a single resource of a collection
+    fn item(
+        &mut self,
+        method: &Method,
+        path: &str,
+        id: &str,
+        key: &str,
+        body: &Value,
+    ) -> ApiResult {
+        match *method {
+            Method::GET => self
+                .find(path, id)
+                .cloned()
+                .map(|r| (StatusCode::OK, r))
+                .ok_or_else(|| ApiError::not_found(key)),
+            Method::PATCH => {
+                let mut patch = wrapped(body, key)?;
+                // Identifiers cannot be changed
+                patch.remove("id");
+                let resource = self
+                    .find_mut(path, id)
+                    .ok_or_else(|| ApiError::not_found(key))?;
+                merge(resource, &Value::Object(patch));
+                resource["update_time"] = json!(timestamp());
+                Ok((StatusCode::OK, resource.clone()))
+            }
+            Method::DELETE => {
+                let resources = self
+                    .collections
+                    .get_mut(path)
+                    .ok_or_else(|| ApiError::not_found(key))?;
+                let index = resources
+                    .iter()
+                    .position(|r| r["id"].as_str() == Some(id))
+                    .ok_or_else(|| ApiError::not_found(key))?;
+                resources.remove(index);
+                Ok((StatusCode::OK, json!({})))
+            }
+            _ => Err(method_not_allowed()),
+        }
+    }
+
+    fn create(
+        &mut self,
+        path: &str,
+        mut resource: Map<String, Value>,
+        parents: &[(&str, &str)],
+    ) -> ApiResult {
+        let now = timestamp();
+        resource.insert("id".to_string(), json!(generate_id()));
+        for (key, value) in parents {
+            resource.insert(key.to_string(), json!(value));
+        }
+        resource.insert("create_time".to_string(), json!(now));
+        resource.insert("update_time".to_string(), json!(now));
+
+        let resource = Value::Object(resource);
+        self.collections
+            .entry(path.to_string())
+            .or_default()
+            .push(resource.clone());
+        Ok((StatusCode::OK, resource))
+    }
+
+    fn list(&self, path: &str, key: &str, query: &HashMap<String, String>) -> ApiResult {
+        paginate(self.resources(path, |_| true), key, query)
+    }
+
+    fn resources(&self, path: &str, predicate: impl Fn(&Value) -> bool) -> Vec<Value> {
+        self.collections
+            .get(path)
+            .map(|resources| resources.iter().filter(|r| predicate(r)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn find(&self, path: &str, id: &str) -> Option<&Value> {
+        self.collections
+            .get(path)?
+            .iter()
+            .find(|r| r["id"].as_str() == Some(id))
+    }
+
+    fn find_mut(&mut self, path: &str, id: &str) -> Option<&mut Value> {
+        self.collections
+            .get_mut(path)?
+            .iter_mut()
+            .find(|r| r["id"].as_str() == Some(id))
+    }
+
+    // Drop memberships and nested resources of a deleted resource
+    fn remove_references(&mut self, realm_path: &str, collection: &str, id: &str) {
+        let resource_path = format!("{}/{}/{}", realm_path, collection, id);
+        let nested = format!("{}/", resource_path);
+        self.members
+            .retain(|path, _| *path != resource_path && !path.starts_with(&nested));
+        self.collections
+            .retain(|path, _| !path.starts_with(&nested));
+        if collection == "identities" || collection == "groups" {
+            for members in self.members.values_mut() {
+                members.retain(|m| m != id);
+            }
+        }
+    }
+
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), ApiError> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                ApiError(StatusCode::UNAUTHORIZED, "Missing bearer token".to_string())
+            })?;
+
+        // Unknown tokens are rejected with 403 so clients refresh their token
+        if self.tokens.contains(token) {
+            Ok(())
+        } else {
+            Err(ApiError(
+                StatusCode::FORBIDDEN,
+                "Invalid bearer token".to_string(),
+            ))
+        }
+    }
+
+    fn token(&mut self, headers: &HeaderMap, body: &[u8]) -> ApiResult {
+        let grant_type = url::form_urlencoded::parse(body)
+            .find(|(key, _)| key == "grant_type")
+            .map(|(_, value)| value.into_owned());
+        if grant_type.as_deref() != Some("client_credentials") {
+            return Err(ApiError::bad_request("Unsupported grant_type"));
+        }
+
+        let expected = format!(
+            "Basic {}",
+            STANDARD.encode(format!(
+                "{}:{}",
+                self.config.client_id, self.config.client_secret
+            ))
+        );
+        if headers
+            .get(header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            != Some(&expected)
+        {
+            return Err(ApiError(
+                StatusCode::UNAUTHORIZED,
+                "Invalid client credentials".to_string(),
+            ));
+        }
+
+        let access_token = format!("mock-{}", generate_id());
+        self.tokens.insert(access_token.clone());
+        Ok((
+            StatusCode::OK,
+            json!({
+                "access_token": access_token,
+                "token_type": "Bearer",
+                "expires_in": TOKEN_EXPIRES_IN,
+            }),
+        ))
+    }
+
+    fn openid_configuration(&self) -> Value {
+        let issuer = format!(
+            "{}/v1/tenants/{}/realms/{}/applications/{}",
+            self.config.base_url(),
+            self.config.tenant_id,
+            self.config.realm_id,
+            self.config.application_id
+        );
+        json!({
+            "issuer": issuer,
+            "token_endpoint": format!("{}/token", issuer),
+            "jwks_uri": format!("{}/.well-known/jwks.json", issuer),
+        })
+    }
+}
+
+fn realm_collection(name: &str) -> Result<(&'static str, &'static str), ApiError> {
+    REALM_COLLECTIONS
+        .iter()
+        .find(|(collection, _)| *collection == name)
+        .copied()
+        .ok_or_else(|| ApiError::not_found("Route"))
+}
+
+// Apply the SCIM filter and page through the matching resources. Page tokens
+// are simply the offset of the next page.
+fn paginate(resources: Vec<Value>, key: &str, query: &HashMap<String, String>) -> ApiResult {
+    let resources = match query.get("filter").filter(|f| !f.trim().is_empty()) {
+        Some(filter) => scim_filter::scim_filter(filter, resources)
+            .map_err(|e| ApiError::bad_request(format!("Invalid filter: {}", e)))?,
+        None => resources,
+    };
+
+    let page_size = match query.get("page_size") {
+        Some(size) => size
+            .parse::<usize>()
+            .map_err(|_| ApiError::bad_request("Invalid page_size"))?
+            .max(1),
+        None => DEFAULT_PAGE_SIZE,
+    };
+    let offset = match query.get("page_token") {
+        Some(token) => token
+            .parse::<usize>()
+            .map_err(|_| ApiError::bad_request("Invalid page_token"))?,
+        None => 0,
+    };
+
+    let total_size = resources.len();
+    let page: Vec<Value> = resources.into_iter().skip(offset).take(page_size).collect();
+    let next_offset = offset + page.len();
+
+    let mut response = Map::new();
+    response.insert(key.to_string(), Value::Array(page));
+    response.insert("total_size".to_string(), json!(total_size));
+    if next_offset < total_size {
+        response.insert(
+            "next_page_token".to_string(),
+            json!(next_offset.to_string()),
+        );
+    }
+    Ok((StatusCode::OK, Value::Object(response)))
+}
+
+fn parse_body(body: &[u8]) -> Result<Value, ApiError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Value::Object(Map::new()));
+    }
+    serde_json::from_slice(body).map_err(|e| ApiError::bad_request(format!("Invalid JSON: {}", e)))
+}
+
+// The object a request body wraps its resource in, e.g. `{"identity": {..}}`
+fn wrapped(body: &Value, key: &str) -> Result<Map<String, Value>, ApiError> {
+    body.get(key)
+        .and_then(Value::as_object)
+        .cloned()
+        .ok_or_else(|| ApiError::bad_request(format!("Request body must contain a {} object", key)))
+}
+
+// SSO config payloads are sent as `{"type": "bookmark", ...}` but returned
+// tagged by their type, e.g. `{"Bookmark": {...}}`.
+fn tag_payload(resource: &mut Map<String, Value>) {
+    let payload = match resource.get_mut("payload").and_then(Value::as_object_mut) {
+        Some(payload) => payload,
+        None => return,
+    };
+    let tag = match payload.remove("type") {
+        Some(Value::String(tag)) => tag.to_case(Case::Pascal),
+        _ => return,
+    };
+    let config = std::mem::take(payload);
+    payload.insert(tag, Value::Object(config));
+}
+
+// Recursively merge a patch into a resource
+fn merge(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (_, Value::Null) => {}
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+fn as_refs<'a>(pairs: &'a [(&'a str, String)]) -> Vec<(&'a str, &'a str)> {
+    pairs.iter().map(|(k, v)| (*k, v.as_str())).collect()
+}
+
+fn method_not_allowed() -> ApiError {
+    ApiError(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "Method not allowed".to_string(),
+    )
+}
+
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// Random UUID formatted identifier
+fn generate_id() -> String {
+    let hex = format!("{:032x}", rand::random::<u128>());
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
@@ -0,0 +1,63 @@
+//! Beyond Identity SDK and the library behind the `bi` command-line interface.
+//!
+//! The SDK does not require any local configuration. Build an [`ApiClient`]
+//! from in-memory [`Credentials`] and turn it into the service for the
+//! resource you want to work with:
+//!
+//! ```no_run
+//! use bi::{ApiClient, Credentials, IdentitiesApi, IdentitiesService};
+//!
+//! # async fn run() -> Result<(), bi::BiError> {
+//! let api_client = ApiClient::from_credentials(Credentials {
+//!     tenant_id: "tenant".to_string(),
+//!     realm_id: "realm".to_string(),
+//!     application_id: "application".to_string(),
+//!     client_id: "client".to_string(),
+//!     client_secret: "secret".to_string(),
+//!     api_base_url: "https://api-us.beyondidentity.com".to_string(),
+//!     auth_base_url: "https://auth-us.beyondidentity.com".to_string(),
+//! })
+//! .await?;
+//!
+//! let identities = IdentitiesService::from(api_client)
+//!     .list_identities(None, None)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The command-line interface and everything only it needs, such as the local
+//! configuration database and the migration tooling, are behind the default
+//! `cli` feature. Depend on this crate with `default-features = false` to use
+//! the SDK on its own.
+
+#[cfg(feature = "cli")]
+pub mod ai;
+pub mod beyond_identity;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod common;
+#[cfg(feature = "cli")]
+pub mod config;
+#[cfg(feature = "cli")]
+pub mod dev;
+#[cfg(feature = "cli")]
+pub mod okta;
+#[cfg(feature = "cli")]
+pub mod onelogin;
+
+pub use beyond_identity::api::authenticator_configs::api::AuthenticatorConfigsApi;
+pub use beyond_identity::api::common::api_client::{ApiClient, URLBuilder};
+pub use beyond_identity::api::common::credentials::Credentials;
+pub use beyond_identity::api::common::filter::Filter;
+pub use beyond_identity::api::common::service::{
+    AuthenticatorConfigsService, CredentialBindingJobsService, CredentialsService, GroupsService,
+    IdentitiesService, RealmsService, TenantsService,
+};
+pub use beyond_identity::api::credential_binding_jobs::api::CredentialBindingJobsApi;
+pub use beyond_identity::api::credentials::api::CredentialsApi;
+pub use beyond_identity::api::groups::api::GroupsApi;
+pub use beyond_identity::api::identities::api::IdentitiesApi;
+pub use beyond_identity::api::realms::api::RealmsApi;
+pub use beyond_identity::api::tenants::api::TenantsApi;
+pub use common::error::BiError;
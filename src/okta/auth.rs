@@ -0,0 +1,439 @@
+use crate::beyond_identity::api::common::middleware::rate_limit::RespectRateLimitMiddleware;
+use crate::beyond_identity::api::common::middleware::with_transport;
+use crate::common::database::models::{OktaConfig, OktaOAuthConfig, OktaToken};
+use crate::common::database::Database;
+use crate::common::error::BiError;
+use crate::common::global;
+use crate::common::http_client;
+use crate::common::redact;
+use crate::common::secret;
+use crate::common::stats;
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use http::header::AUTHORIZATION;
+use http::{Extensions, HeaderValue, StatusCode};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+    EllipticCurveKeyType, Jwk,
+};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use reqwest::{Request, Response};
+use reqwest_middleware::{
+    ClientBuilder, ClientWithMiddleware as Client, Error, Middleware, Next,
+    Result as MiddlewareResult,
+};
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+// Scopes requested for a service app unless others are configured.
+pub const DEFAULT_SCOPES: &str = "okta.apps.read,okta.users.read,okta.groups.read";
+
+const CLIENT_ASSERTION_TYPE: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+const DPOP_NONCE: &str = "dpop-nonce";
+
+// Build a client for the Okta API that respects rate limits and authenticates
+// every request with the configured credentials.
+pub fn client(db: &Database, okta_config: &OktaConfig) -> Result<Client, BiError> {
+    // Resolve the API key once rather than for every request
+    let mut okta_config = okta_config.clone();
+    okta_config.api_key = okta_config
+        .api_key
+        .as_deref()
+        .map(secret::resolve)
+        .transpose()?;
+
+    let http_client = http_client::new(&global::options().http_options())?;
+    let token_client = with_transport(ClientBuilder::new(http_client.clone())).build();
+    Ok(with_transport(
+        ClientBuilder::new(http_client)
+            .with(RespectRateLimitMiddleware)
+            .with(OktaAuthMiddleware::new(
+                db.clone(),
+                token_client,
+                okta_config,
+            )),
+    )
+    .build())
+}
+
+// ====================================
+// Okta Authorization Middleware
+// ====================================
+
+// Authenticates requests with the SSWS API token, or as an OAuth 2.0 service
+// app. Service app tokens are fetched with a signed JWT assertion, cached, and
+// bound to a DPoP key when the org requires it.
+pub struct OktaAuthMiddleware {
+    db: Database,
+    client: Client,
+    config: OktaConfig,
+}
+
+impl OktaAuthMiddleware {
+    pub fn new(db: Database, client: Client, config: OktaConfig) -> Self {
+        Self { db, client, config }
+    }
+
+    async fn token(&self, oauth: &OktaOAuthConfig) -> Result<OktaToken, BiError> {
+        // Recorded tokens are redacted, so there is nothing to fetch when replaying
+        if global::options().replay.is_some() {
+            return Ok(OktaToken {
+                client_id: oauth.client_id.clone(),
+                access_token: redact::REDACTED.to_string(),
+                token_type: "Bearer".to_string(),
+                expires_at: i64::MAX,
+                dpop_key: None,
+            });
+        }
+
+        if let Some(token) = self.db.get_okta_token().await? {
+            if token.client_id == oauth.client_id && token.expires_at > now() as i64 {
+                log::debug!("Using stored Okta access token");
+                return Ok(token);
+            }
+        }
+
+        log::debug!("No valid Okta access token found. Fetching a new one.");
+        let token = fetch_token(&self.client, &self.config.domain, oauth).await?;
+        stats::record_token_refresh();
+        self.db.set_okta_token(&token).await?;
+
+        Ok(token)
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for OktaAuthMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> MiddlewareResult<Response> {
+        let oauth = match (&self.config.api_key, &self.config.oauth) {
+            (Some(api_key), _) => {
+                let value = header_value(&format!("SSWS {}", api_key)).map_err(middleware_error)?;
+                req.headers_mut().insert(AUTHORIZATION, value);
+                return next.run(req, extensions).await;
+            }
+            (None, Some(oauth)) => oauth,
+            (None, None) => {
+                return Err(middleware_error(BiError::StringError(
+                    "Okta is configured without an API key or service app".to_string(),
+                )))
+            }
+        };
+
+        // Clone the request for potential retry
+        let mut req_for_retry = req.try_clone().ok_or_else(|| {
+            Error::Middleware(anyhow::anyhow!(
+                "Request object is not clonable. Are you passing a streaming body?".to_string()
+            ))
+        })?;
+
+        let token = self.token(oauth).await.map_err(middleware_error)?;
+        authorize(&mut req, &token, None).map_err(middleware_error)?;
+        let response = next.clone().run(req, extensions).await?;
+
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        // A DPoP nonce challenge is answered with the same token, anything
+        // else with a new one
+        let nonce = response
+            .headers()
+            .get(DPOP_NONCE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let token = match &nonce {
+            Some(_) if token.dpop_key.is_some() => token,
+            _ => {
+                log::debug!("Received 401 Unauthorized, refreshing the Okta access token");
+                self.db
+                    .delete_okta_token()
+                    .await
+                    .map_err(middleware_error)?;
+                self.token(oauth).await.map_err(middleware_error)?
+            }
+        };
+        authorize(&mut req_for_retry, &token, nonce.as_deref()).map_err(middleware_error)?;
+
+        stats::record_retry();
+        next.run(req_for_retry, extensions).await
+    }
+}
+
+fn authorize(req: &mut Request, token: &OktaToken, nonce: Option<&str>) -> Result<(), BiError> {
+    let authorization = match &token.dpop_key {
+        Some(dpop_key) => {
+            let proof = DpopKey::decode(dpop_key)?.proof(
+                req.method().as_str(),
+                req.url(),
+                nonce,
+                Some(&token.access_token),
+            )?;
+            req.headers_mut().insert("DPoP", header_value(&proof)?);
+            format!("DPoP {}", token.access_token)
+        }
+        None => format!("Bearer {}", token.access_token),
+    };
+    req.headers_mut()
+        .insert(AUTHORIZATION, header_value(&authorization)?);
+    Ok(())
+}
+
+// ====================================
+// Okta Service App Token
+// ====================================
+
+#[derive(Debug, Deserialize)]
+struct OktaTokenResponse {
+    access_token: String,
+    token_type: String,
+    expires_in: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OktaTokenError {
+    error: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ClientAssertion<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+    jti: String,
+}
+
+// Run the client credentials flow, authenticating with a JWT signed by the
+// service app's private key. A DPoP key is only generated if Okta asks for a
+// proof, and a nonce only sent if Okta asks for one.
+pub async fn fetch_token(
+    client: &Client,
+    domain: &str,
+    oauth: &OktaOAuthConfig,
+) -> Result<OktaToken, BiError> {
+    let url = format!("{}/oauth2/v1/token", domain.trim_end_matches('/'));
+    let token_url = Url::parse(&url).map_err(|e| BiError::StringError(e.to_string()))?;
+    let scope = oauth.scopes.join(" ");
+
+    let mut dpop_key: Option<DpopKey> = None;
+    let mut nonce: Option<String> = None;
+
+    loop {
+        let assertion = client_assertion(oauth, &url)?;
+        let mut request = client.post(&url).form(&[
+            ("grant_type", "client_credentials"),
+            ("scope", scope.as_str()),
+            ("client_assertion_type", CLIENT_ASSERTION_TYPE),
+            ("client_assertion", assertion.as_str()),
+        ]);
+        if let Some(dpop_key) = &dpop_key {
+            let proof = dpop_key.proof("POST", &token_url, nonce.as_deref(), None)?;
+            request = request.header("DPoP", proof);
+        }
+
+        let response = request.send().await?;
+
+        let status = response.status();
+        let next_nonce = response
+            .headers()
+            .get(DPOP_NONCE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let response_text = response.text().await?;
+
+        log::debug!(
+            "{} response status: {} and text: {}",
+            url,
+            status,
+            redact::body(&response_text)
+        );
+
+        if status.is_success() {
+            let token_response: OktaTokenResponse = serde_json::from_str(&response_text)?;
+            return Ok(OktaToken {
+                client_id: oauth.client_id.clone(),
+                access_token: token_response.access_token,
+                token_type: token_response.token_type,
+                expires_at: (now() + token_response.expires_in) as i64,
+                dpop_key: dpop_key.map(|dpop_key| dpop_key.encode()),
+            });
+        }
+
+        let error = serde_json::from_str::<OktaTokenError>(&response_text)
+            .map(|error| error.error)
+            .unwrap_or_default();
+        match error.as_str() {
+            "invalid_dpop_proof" if dpop_key.is_none() => {
+                log::debug!("Okta requires DPoP, retrying with a proof");
+                dpop_key = Some(DpopKey::generate()?);
+            }
+            "use_dpop_nonce" if dpop_key.is_some() && nonce.is_none() && next_nonce.is_some() => {
+                log::debug!("Okta requires a DPoP nonce, retrying with the nonce");
+                nonce = next_nonce;
+            }
+            _ => return Err(BiError::RequestError(status, response_text)),
+        }
+    }
+}
+
+fn client_assertion(oauth: &OktaOAuthConfig, audience: &str) -> Result<String, BiError> {
+    let (algorithm, key) = load_private_key(&oauth.private_key_file)?;
+    let mut header = Header::new(algorithm);
+    header.kid.clone_from(&oauth.key_id);
+
+    let iat = now();
+    let claims = ClientAssertion {
+        iss: &oauth.client_id,
+        sub: &oauth.client_id,
+        aud: audience,
+        iat,
+        exp: iat + 300,
+        jti: random_id(),
+    };
+    encode(&header, &claims, &key)
+        .map_err(|e| BiError::StringError(format!("Failed to sign client assertion: {}", e)))
+}
+
+// Load the RSA or EC private key of a service app from a PEM file
+pub fn load_private_key(path: &Path) -> Result<(Algorithm, EncodingKey), BiError> {
+    let pem = std::fs::read(path)
+        .map_err(|e| BiError::StringError(format!("Failed to read {}: {}", path.display(), e)))?;
+    if let Ok(key) = EncodingKey::from_rsa_pem(&pem) {
+        return Ok((Algorithm::RS256, key));
+    }
+    EncodingKey::from_ec_pem(&pem)
+        .map(|key| (Algorithm::ES256, key))
+        .map_err(|e| {
+            BiError::StringError(format!(
+                "{} is not a PEM encoded RSA or EC private key: {}",
+                path.display(),
+                e
+            ))
+        })
+}
+
+// ====================================
+// DPoP
+// ====================================
+
+// P-256 key that access tokens are bound to. Every request carries a proof
+// signed with it.
+struct DpopKey {
+    pkcs8: Vec<u8>,
+    jwk: Jwk,
+}
+
+#[derive(Debug, Serialize)]
+struct DpopProof {
+    htm: String,
+    htu: String,
+    iat: u64,
+    jti: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ath: Option<String>,
+}
+
+impl DpopKey {
+    fn generate() -> Result<Self, BiError> {
+        let pkcs8 =
+            EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+                .map_err(|e| BiError::StringError(format!("Failed to generate DPoP key: {}", e)))?;
+        Self::from_pkcs8(pkcs8.as_ref().to_vec())
+    }
+
+    fn decode(encoded: &str) -> Result<Self, BiError> {
+        let pkcs8 = STANDARD
+            .decode(encoded)
+            .map_err(|e| BiError::StringError(format!("Invalid DPoP key: {}", e)))?;
+        Self::from_pkcs8(pkcs8)
+    }
+
+    fn encode(&self) -> String {
+        STANDARD.encode(&self.pkcs8)
+    }
+
+    fn from_pkcs8(pkcs8: Vec<u8>) -> Result<Self, BiError> {
+        let key_pair = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &pkcs8,
+            &SystemRandom::new(),
+        )
+        .map_err(|e| BiError::StringError(format!("Invalid DPoP key: {}", e)))?;
+
+        // The public key is an uncompressed point: 0x04 followed by x and y
+        let public_key = key_pair.public_key().as_ref();
+        let jwk = Jwk {
+            common: CommonParameters::default(),
+            algorithm: AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                key_type: EllipticCurveKeyType::EC,
+                curve: EllipticCurve::P256,
+                x: URL_SAFE_NO_PAD.encode(&public_key[1..33]),
+                y: URL_SAFE_NO_PAD.encode(&public_key[33..65]),
+            }),
+        };
+
+        Ok(Self { pkcs8, jwk })
+    }
+
+    // Sign a proof for a request. Proofs sent with an access token carry its hash.
+    fn proof(
+        &self,
+        method: &str,
+        url: &Url,
+        nonce: Option<&str>,
+        access_token: Option<&str>,
+    ) -> Result<String, BiError> {
+        let mut header = Header::new(Algorithm::ES256);
+        header.typ = Some("dpop+jwt".to_string());
+        header.jwk = Some(self.jwk.clone());
+
+        let mut htu = url.clone();
+        htu.set_query(None);
+        htu.set_fragment(None);
+
+        let claims = DpopProof {
+            htm: method.to_string(),
+            htu: htu.to_string(),
+            iat: now(),
+            jti: random_id(),
+            nonce: nonce.map(String::from),
+            ath: access_token
+                .map(|token| URL_SAFE_NO_PAD.encode(digest(&SHA256, token.as_bytes()))),
+        };
+        encode(&header, &claims, &EncodingKey::from_ec_der(&self.pkcs8))
+            .map_err(|e| BiError::StringError(format!("Failed to sign DPoP proof: {}", e)))
+    }
+}
+
+fn header_value(value: &str) -> Result<HeaderValue, BiError> {
+    HeaderValue::from_str(value).map_err(|e| BiError::StringError(e.to_string()))
+}
+
+fn middleware_error(e: BiError) -> Error {
+    Error::Middleware(e.into())
+}
+
+fn random_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
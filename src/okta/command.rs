@@ -1,14 +1,14 @@
-use super::fast_migrate;
+use super::{auth, fast_migrate};
 
 use crate::beyond_identity::api::common::api_client::ApiClient;
-use crate::beyond_identity::api::common::middleware::rate_limit::RespectRateLimitMiddleware;
+use crate::beyond_identity::helper::migration::{self, MatchStrategy, MigrationPlan};
 use crate::common::command::ambassador_impl_Executable;
+use crate::common::database::models::OktaConfig;
 use crate::common::{command::Executable, error::BiError};
 
 use async_trait::async_trait;
 use clap::{Args, Subcommand};
-use reqwest::Client;
-use reqwest_middleware::ClientBuilder;
+use std::path::PathBuf;
 
 // ====================================
 // Okta Commands
@@ -19,45 +19,342 @@ use reqwest_middleware::ClientBuilder;
 #[delegate(Executable)]
 pub enum OktaCommands {
     /// Automatically migrate all Okta applications to Beyond Identity SSO and assign users based on existing Okta assignments.
-    /// Each application tile in Beyond Identity will act as an opaque redirect to Okta.
+    /// Each application tile in Beyond Identity will act as an opaque redirect to Okta, unless `--federate` is passed.
+    /// Rerunning updates the tiles and assignments of previously migrated applications instead of creating duplicates,
+    /// only removing assignments that an earlier run made.
     FastMigrate(FastMigrate),
+
+    /// Export Okta applications, users, groups and assignments to a snapshot file that
+    /// fast-migrate can read with `--from-snapshot`, without access to the Okta API.
+    Export(Export),
+
+    /// Compare the current Okta application assignments with the SSO configs they were migrated
+    /// to, listing identities to add or remove and applications added or deactivated since.
+    Drift(Drift),
 }
 
 // ====================================
 // Okta FastMigrate
 // ====================================
 
+// Source recorded in migration plans and runs.
+const SOURCE: &str = "okta";
+
 #[derive(Args)]
-pub struct FastMigrate;
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct FastMigrate {
+    #[clap(subcommand)]
+    command: Option<FastMigrateCommands>,
+
+    /// Write the proposed SSO configs, their login links and logos, and the matched and unmatched
+    /// user assignments to this file without making any changes.
+    #[clap(long, conflicts_with = "apply")]
+    plan: Option<PathBuf>,
+
+    /// Create exactly the SSO configs and user assignments in a plan previously written with
+    /// `--plan`.
+    #[clap(long)]
+    apply: Option<PathBuf>,
+
+    /// Read applications, users, groups and assignments from a snapshot written by `bi okta
+    /// export` instead of the Okta API. No Okta configuration is needed.
+    #[clap(long, conflicts_with = "apply")]
+    from_snapshot: Option<PathBuf>,
+
+    /// Mirror the Okta groups assigned to each application as Beyond Identity groups and assign
+    /// them to its SSO config, instead of assigning their members directly. Members of a group
+    /// that fails to mirror are still assigned directly.
+    #[clap(long, conflicts_with = "apply")]
+    groups: bool,
+
+    /// Create a Beyond Identity identity from the Okta profile of each assigned user without a
+    /// matching identity, with its external ID set to the Okta user ID, and assign it. Every
+    /// created identity is reported.
+    #[clap(long, conflicts_with = "apply")]
+    create_missing_identities: bool,
+
+    /// Create a generic OIDC SSO config from the sign-on settings of each OpenID Connect
+    /// application, so that Beyond Identity signs users in to it directly instead of redirecting
+    /// to Okta. OIDC tiles open the application's login URL, and the client ID and secret of
+    /// each config are reported so the application can be switched over. Applications that
+    /// cannot be translated, including every SAML 2.0 application for now, are reported and
+    /// migrated as bookmark tiles.
+    #[clap(long, conflicts_with = "apply")]
+    federate: bool,
+
+    /// How to match Okta users to identities. Strategies are tried in order until one matches
+    /// exactly one identity, e.g. `--match email,email-ci,username`.
+    #[clap(
+        long = "match",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "email",
+        conflicts_with = "apply"
+    )]
+    matching: Vec<MatchStrategy>,
+
+    /// Okta profile attribute compared with the external ID of each identity by
+    /// `custom-attribute` matching.
+    #[clap(long, conflicts_with = "apply")]
+    match_attribute: Option<String>,
+
+    /// Write the assigned users without a matching identity to this CSV file for manual
+    /// reconciliation.
+    #[clap(long, conflicts_with = "apply")]
+    unmatched_report: Option<PathBuf>,
+}
 
 #[async_trait]
 impl Executable for FastMigrate {
     async fn execute(&self) -> Result<(), BiError> {
-        let http_client = Client::new();
-        let okta_client = ClientBuilder::new(http_client.clone())
-            .with(RespectRateLimitMiddleware)
-            .build();
-
-        let api_client = ApiClient::new(None, None).await;
-        let okta_config = api_client.db.get_okta_config().await?.expect("Failed to load Okta Configuration. Make sure to setup Okta before running this command.");
-
-        let okta_applications = fast_migrate::fetch_okta_applications(&okta_client, &okta_config)
-            .await
-            .expect("Failed to fetch okta applications");
-
-        let selected_applications = fast_migrate::select_applications(&okta_applications);
-        for app in selected_applications {
-            match fast_migrate::create_sso_config_and_assign_identities(&api_client, &app).await {
-                Ok(sso_config) => println!(
-                    "SSO config created for {}: {}",
-                    app.label,
-                    serde_json::to_string_pretty(&sso_config).unwrap()
-                ),
-                Err(err) => {
-                    println!("Failed to create SSO config for {}: {}", app.label, err)
-                }
+        if let Some(command) = &self.command {
+            return command.execute().await;
+        }
+
+        let api_client = ApiClient::new(None, None).await?;
+
+        if let Some(path) = &self.apply {
+            let plan = MigrationPlan::load(path, SOURCE)?;
+            println!("Applying plan for {}", plan.summary());
+            return plan.apply(&api_client).await;
+        }
+
+        let match_attribute = match_attribute(&self.matching, &self.match_attribute)?;
+
+        let (offered, mut selected_applications, okta_groups) = match &self.from_snapshot {
+            Some(path) => {
+                let snapshot = fast_migrate::OktaSnapshot::load(path)?;
+                println!(
+                    "Loaded {} exported at {}",
+                    snapshot.summary(),
+                    snapshot.exported_at
+                );
+                let selected_applications =
+                    fast_migrate::select_applications(&snapshot.applications);
+                let okta_groups = if self.groups {
+                    fast_migrate::assigned_groups(&selected_applications, snapshot.groups)
+                } else {
+                    Vec::new()
+                };
+                (
+                    fast_migrate::application_ids(&snapshot.applications),
+                    selected_applications,
+                    okta_groups,
+                )
             }
+            None => {
+                let okta_config = okta_config(&api_client).await?;
+                let okta_client = auth::client(api_client.db()?, &okta_config)?;
+
+                let okta_applications =
+                    fast_migrate::fetch_okta_applications(&okta_client, &okta_config).await?;
+
+                let mut selected_applications =
+                    fast_migrate::select_applications(&okta_applications);
+                let okta_groups = if self.groups {
+                    fast_migrate::fetch_okta_groups(
+                        &okta_client,
+                        &okta_config,
+                        &mut selected_applications,
+                    )
+                    .await?
+                } else {
+                    Vec::new()
+                };
+                (
+                    fast_migrate::application_ids(&okta_applications),
+                    selected_applications,
+                    okta_groups,
+                )
+            }
+        };
+        if self.groups {
+            fast_migrate::exclude_group_assignments(&mut selected_applications);
+        }
+        let mut plan = MigrationPlan::build(
+            &api_client,
+            SOURCE,
+            offered,
+            fast_migrate::source_applications(
+                &selected_applications,
+                self.federate,
+                match_attribute,
+            ),
+            fast_migrate::source_groups(&okta_groups, match_attribute),
+            &self.matching,
+        )
+        .await?;
+        if self.create_missing_identities {
+            plan.plan_missing_identities();
+        }
+        if let Some(path) = &self.unmatched_report {
+            plan.write_unmatched_report(path)?;
+        }
+
+        match &self.plan {
+            Some(path) => {
+                plan.save(path)?;
+                println!("Wrote plan for {} to {}", plan.summary(), path.display());
+                plan.report_untranslated();
+                Ok(())
+            }
+            None => plan.apply(&api_client).await,
         }
+    }
+}
+
+// The profile attribute to read for `custom-attribute` matching, which is
+// required when that strategy is used
+fn match_attribute<'a>(
+    matching: &[MatchStrategy],
+    match_attribute: &'a Option<String>,
+) -> Result<Option<&'a str>, BiError> {
+    if matching.contains(&MatchStrategy::CustomAttribute) && match_attribute.is_none() {
+        return Err(BiError::StringError(
+            "--match-attribute is required for custom-attribute matching".to_string(),
+        ));
+    }
+    Ok(match_attribute.as_deref())
+}
+
+// The Okta configuration, which must be set up before talking to Okta
+async fn okta_config(api_client: &ApiClient) -> Result<OktaConfig, BiError> {
+    api_client.db()?.get_okta_config().await?.ok_or_else(|| {
+        BiError::StringError(
+            "Failed to load Okta Configuration. Make sure to setup Okta before running this command."
+                .to_string(),
+        )
+    })
+}
+
+// ====================================
+// Okta Export
+// ====================================
+
+#[derive(Args)]
+pub struct Export {
+    /// File to write the snapshot to.
+    #[clap(long)]
+    out: PathBuf,
+}
+
+#[async_trait]
+impl Executable for Export {
+    async fn execute(&self) -> Result<(), BiError> {
+        let api_client = ApiClient::new(None, None).await?;
+        let okta_config = okta_config(&api_client).await?;
+        let okta_client = auth::client(api_client.db()?, &okta_config)?;
+
+        let snapshot = fast_migrate::fetch_okta_snapshot(&okta_client, &okta_config).await?;
+        snapshot.save(&self.out)?;
+        println!("Exported {} to {}", snapshot.summary(), self.out.display());
         Ok(())
     }
 }
+
+// ====================================
+// Okta Drift
+// ====================================
+
+#[derive(Args)]
+pub struct Drift {
+    /// Reconcile the drift: assign identities, unassign those fast-migrate assigned, migrate
+    /// applications added since the last run as bookmark tiles and unassign what fast-migrate
+    /// assigned to deactivated ones. Undo with `fast-migrate rollback`.
+    #[clap(long)]
+    fix: bool,
+
+    /// Leave users assigned through Okta groups to the mirrored groups, for applications
+    /// migrated with `fast-migrate --groups`, and compare the members of each mirrored group
+    /// with its Okta group.
+    #[clap(long)]
+    groups: bool,
+
+    /// How to match Okta users to identities. Strategies are tried in order until one matches
+    /// exactly one identity, e.g. `--match email,email-ci,username`. Identities created by
+    /// fast-migrate are always matched by external ID first.
+    #[clap(
+        long = "match",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "email"
+    )]
+    matching: Vec<MatchStrategy>,
+
+    /// Okta profile attribute compared with the external ID of each identity by
+    /// `custom-attribute` matching.
+    #[clap(long)]
+    match_attribute: Option<String>,
+}
+
+#[async_trait]
+impl Executable for Drift {
+    async fn execute(&self) -> Result<(), BiError> {
+        let match_attribute = match_attribute(&self.matching, &self.match_attribute)?;
+        let api_client = ApiClient::new(None, None).await?;
+        let okta_config = okta_config(&api_client).await?;
+        let okta_client = auth::client(api_client.db()?, &okta_config)?;
+
+        let mut okta_applications =
+            fast_migrate::fetch_okta_applications(&okta_client, &okta_config).await?;
+        let okta_groups = if self.groups {
+            let okta_groups =
+                fast_migrate::fetch_okta_groups(&okta_client, &okta_config, &mut okta_applications)
+                    .await?;
+            fast_migrate::exclude_group_assignments(&mut okta_applications);
+            okta_groups
+        } else {
+            Vec::new()
+        };
+        migration::drift(
+            &api_client,
+            SOURCE,
+            fast_migrate::source_applications(&okta_applications, false, match_attribute),
+            fast_migrate::source_groups(&okta_groups, match_attribute),
+            &self.matching,
+            self.fix,
+        )
+        .await
+    }
+}
+
+// ====================================
+// Okta FastMigrate Runs
+// ====================================
+
+/// Commands for inspecting and undoing previous fast-migrate runs.
+#[derive(Subcommand, ambassador::Delegate)]
+#[delegate(Executable)]
+pub enum FastMigrateCommands {
+    /// Delete every SSO config, group, identity and assignment created by a fast-migrate run
+    /// and restore what `drift --fix` removed. Pre-existing resources are left untouched.
+    Rollback(Rollback),
+
+    /// List previous fast-migrate runs and what each of them created.
+    Runs(Runs),
+}
+
+#[derive(Args)]
+pub struct Rollback {
+    /// ID of the run to roll back, as printed at the end of the run or listed by `runs`.
+    run_id: String,
+}
+
+#[async_trait]
+impl Executable for Rollback {
+    async fn execute(&self) -> Result<(), BiError> {
+        let api_client = ApiClient::new(None, None).await?;
+        migration::rollback(&api_client, SOURCE, &self.run_id).await
+    }
+}
+
+#[derive(Args)]
+pub struct Runs;
+
+#[async_trait]
+impl Executable for Runs {
+    async fn execute(&self) -> Result<(), BiError> {
+        let api_client = ApiClient::new(None, None).await?;
+        migration::list_runs(&api_client, SOURCE).await
+    }
+}
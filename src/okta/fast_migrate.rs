@@ -1,15 +1,49 @@
-use crate::beyond_identity::api::common::api_client::ApiClient;
-use crate::beyond_identity::api::common::service::IdentitiesService;
-use crate::beyond_identity::api::identities::api::IdentitiesApi;
-use crate::beyond_identity::api::identities::types::Identity;
-use crate::beyond_identity::helper::sso_configs;
+use crate::beyond_identity::helper::migration::{
+    self, Federation, SourceApplication, SourceGroup, SourceUser,
+};
 use crate::common::database::models::OktaConfig;
 use crate::common::error::BiError;
 
+use chrono::{DateTime, Utc};
 use reqwest_middleware::ClientWithMiddleware as Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{self, Write};
+use std::path::Path;
+
+/// Everything fast-migrate reads from Okta, written by `bi okta export` so that
+/// a migration can be planned without access to the Okta API.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OktaSnapshot {
+    pub domain: String,
+    pub exported_at: DateTime<Utc>,
+    users: Vec<OktaUser>,
+    /// Applications along with their assigned users and groups.
+    pub applications: Vec<OktaApplication>,
+    /// Groups assigned to any of the applications along with their members.
+    pub groups: Vec<OktaGroup>,
+}
+
+impl OktaSnapshot {
+    pub fn load(path: &Path) -> Result<Self, BiError> {
+        migration::read_json(path, "Okta snapshot")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BiError> {
+        migration::write_json(path, self)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} users, {} applications and {} groups from {}",
+            self.users.len(),
+            self.applications.len(),
+            self.groups.len(),
+            self.domain
+        )
+    }
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,9 +51,19 @@ pub struct OktaApplication {
     id: String,
     pub label: String,
     status: String,
+    /// How Okta signs users in to the application, e.g. `SAML_2_0`.
+    #[serde(default)]
+    sign_on_mode: String,
+    #[serde(default)]
+    settings: OktaApplicationSettings,
+    #[serde(default)]
+    credentials: OktaApplicationCredentials,
     embedded: Option<OktaEmbeddedUsers>,
     #[serde(rename = "_links")]
     _links: Links,
+    /// IDs of the groups assigned to the application, filled in by `fetch_okta_groups`.
+    #[serde(default)]
+    assigned_groups: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -52,16 +96,34 @@ struct Logo {
     r#type: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct OktaApplicationSettings {
-    app: OktaApplicationSettingsApp,
+    oauth_client: Option<OktaOAuthClientSettings>,
 }
 
+// Okta uses snake case for OAuth client settings
 #[derive(Clone, Debug, Serialize, Deserialize)]
+struct OktaOAuthClientSettings {
+    #[serde(default)]
+    redirect_uris: Vec<String>,
+    #[serde(default)]
+    post_logout_redirect_uris: Vec<String>,
+    #[serde(default)]
+    grant_types: Vec<String>,
+    application_type: Option<String>,
+    initiate_login_uri: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OktaApplicationSettingsApp {
-    login_url: Option<String>,
+struct OktaApplicationCredentials {
+    oauth_client: Option<OktaOAuthClientCredentials>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct OktaOAuthClientCredentials {
+    token_endpoint_auth_method: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -74,13 +136,44 @@ struct OktaEmbeddedUsers {
 #[serde(rename_all = "camelCase")]
 struct OktaUser {
     id: String,
+    /// `USER` for direct application assignments and `GROUP` for assignments
+    /// through a group. Not present on users listed outside an application.
+    scope: Option<String>,
     profile: OktaUserProfile,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct OktaUserProfile {
+    login: Option<String>,
     email: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    /// Every other profile attribute, including custom ones.
+    #[serde(flatten)]
+    attributes: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OktaGroup {
+    id: String,
+    profile: OktaGroupProfile,
+    #[serde(default)]
+    members: Vec<OktaUser>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OktaGroupProfile {
+    name: String,
+    description: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OktaApplicationGroup {
+    id: String,
 }
 
 async fn fetch_all_okta_users(
@@ -90,11 +183,7 @@ async fn fetch_all_okta_users(
     let mut users_map = HashMap::new();
     let mut url = format!("{}/api/v1/users?limit=200", okta_config.domain);
     loop {
-        let response = client
-            .get(&url)
-            .header("Authorization", format!("SSWS {}", okta_config.api_key))
-            .send()
-            .await?;
+        let response = client.get(&url).send().await?;
 
         let status = response.status();
         log::debug!("{} response status: {}", url, status);
@@ -125,6 +214,36 @@ async fn fetch_all_okta_users(
 pub async fn fetch_okta_applications(
     client: &Client,
     okta_config: &OktaConfig,
+) -> Result<Vec<OktaApplication>, BiError> {
+    let users_map = fetch_all_okta_users(client, okta_config).await?;
+    fetch_applications(client, okta_config, &users_map).await
+}
+
+// Fetch every user, application, group and assignment for a snapshot
+pub async fn fetch_okta_snapshot(
+    client: &Client,
+    okta_config: &OktaConfig,
+) -> Result<OktaSnapshot, BiError> {
+    let users_map = fetch_all_okta_users(client, okta_config).await?;
+    let mut applications = fetch_applications(client, okta_config, &users_map).await?;
+    let groups = fetch_okta_groups(client, okta_config, &mut applications).await?;
+
+    let mut users: Vec<OktaUser> = users_map.into_values().collect();
+    users.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(OktaSnapshot {
+        domain: okta_config.domain.clone(),
+        exported_at: Utc::now(),
+        users,
+        applications,
+        groups,
+    })
+}
+
+async fn fetch_applications(
+    client: &Client,
+    okta_config: &OktaConfig,
+    users_map: &HashMap<String, OktaUser>,
 ) -> Result<Vec<OktaApplication>, BiError> {
     let mut apps = Vec::new();
     let mut url = format!(
@@ -132,14 +251,8 @@ pub async fn fetch_okta_applications(
         okta_config.domain
     );
 
-    let users_map = fetch_all_okta_users(client, okta_config).await?;
-
     loop {
-        let response = client
-            .get(&url)
-            .header("Authorization", format!("SSWS {}", okta_config.api_key))
-            .send()
-            .await?;
+        let response = client.get(&url).send().await?;
 
         let status = response.status();
         log::debug!("{} response status: {}", url, status);
@@ -155,7 +268,7 @@ pub async fn fetch_okta_applications(
 
         for app in &mut page_apps {
             log::info!("Fetching assigned users for app: {:?}", app.label);
-            let users = get_users_assigned_to_app(client, okta_config, &app.id, &users_map).await?;
+            let users = get_users_assigned_to_app(client, okta_config, &app.id, users_map).await?;
             app.embedded = Some(OktaEmbeddedUsers { users });
         }
 
@@ -171,6 +284,100 @@ pub async fn fetch_okta_applications(
     Ok(apps)
 }
 
+// Fetch the groups assigned to the applications along with their members
+pub async fn fetch_okta_groups(
+    client: &Client,
+    okta_config: &OktaConfig,
+    applications: &mut [OktaApplication],
+) -> Result<Vec<OktaGroup>, BiError> {
+    for app in applications.iter_mut() {
+        log::info!("Fetching assigned groups for app: {:?}", app.label);
+        let url = format!(
+            "{}/api/v1/apps/{}/groups?limit=200",
+            okta_config.domain, app.id
+        );
+        let app_groups: Vec<OktaApplicationGroup> = fetch_all(client, &url).await?;
+        app.assigned_groups = app_groups.into_iter().map(|group| group.id).collect();
+    }
+
+    let url = format!("{}/api/v1/groups?limit=200", okta_config.domain);
+    let groups: Vec<OktaGroup> = fetch_all(client, &url).await?;
+    let mut groups = assigned_groups(applications, groups);
+
+    for group in &mut groups {
+        let url = format!(
+            "{}/api/v1/groups/{}/users?limit=200",
+            okta_config.domain, group.id
+        );
+        group.members = fetch_all(client, &url).await?;
+        log::info!(
+            "Fetched {} members for group {}",
+            group.members.len(),
+            group.id
+        );
+    }
+
+    Ok(groups)
+}
+
+// Keep only the groups assigned to any of the applications
+pub fn assigned_groups(applications: &[OktaApplication], groups: Vec<OktaGroup>) -> Vec<OktaGroup> {
+    groups
+        .into_iter()
+        .filter(|group| {
+            applications
+                .iter()
+                .any(|app| app.assigned_groups.contains(&group.id))
+        })
+        .collect()
+}
+
+// Drop users assigned to an application through a group from its direct
+// assignments, since the mirrored group covers them.
+pub fn exclude_group_assignments(applications: &mut [OktaApplication]) {
+    for app in applications {
+        if let Some(embedded) = &mut app.embedded {
+            embedded
+                .users
+                .retain(|user| user.scope.as_deref() != Some("GROUP"));
+        }
+    }
+}
+
+// Fetch every page of an Okta list endpoint
+async fn fetch_all<T: DeserializeOwned>(client: &Client, url: &str) -> Result<Vec<T>, BiError> {
+    let mut items = Vec::new();
+    let mut url = url.to_string();
+    loop {
+        let response = client.get(&url).send().await?;
+
+        let status = response.status();
+        let next_link = extract_next_link(&response);
+        let response_text = response.text().await?;
+
+        log::debug!(
+            "{} response status: {} and text: {}",
+            url,
+            status,
+            response_text
+        );
+
+        if !status.is_success() {
+            return Err(BiError::RequestError(status, response_text));
+        }
+
+        let page: Vec<T> = serde_json::from_str(&response_text)?;
+        items.extend(page);
+
+        match next_link {
+            Some(next) => url = next,
+            None => break,
+        }
+    }
+
+    Ok(items)
+}
+
 fn extract_next_link(response: &reqwest::Response) -> Option<String> {
     response.headers().get_all("link").iter().find_map(|link| {
         let link_str = link.to_str().ok()?;
@@ -203,11 +410,7 @@ async fn get_users_assigned_to_app(
     );
 
     loop {
-        let response = client
-            .get(&url)
-            .header("Authorization", format!("SSWS {}", okta_config.api_key))
-            .send()
-            .await?;
+        let response = client.get(&url).send().await?;
 
         let status = response.status();
         let next_link = extract_next_link(&response);
@@ -226,11 +429,28 @@ async fn get_users_assigned_to_app(
 
         let mut users: Vec<OktaUser> = serde_json::from_str(&response_text)?;
 
-        // Backfill missing emails
+        // Backfill profile fields missing from the application user
         for user in &mut users {
-            if user.profile.email.is_none() {
-                if let Some(full_user) = users_map.get(&user.id) {
-                    user.profile.email.clone_from(&full_user.profile.email);
+            if let Some(full_user) = users_map.get(&user.id) {
+                let profile = &mut user.profile;
+                let full_profile = &full_user.profile;
+                if profile.email.is_none() {
+                    profile.email.clone_from(&full_profile.email);
+                }
+                if profile.login.is_none() {
+                    profile.login.clone_from(&full_profile.login);
+                }
+                if profile.first_name.is_none() {
+                    profile.first_name.clone_from(&full_profile.first_name);
+                }
+                if profile.last_name.is_none() {
+                    profile.last_name.clone_from(&full_profile.last_name);
+                }
+                for (name, value) in &full_profile.attributes {
+                    profile
+                        .attributes
+                        .entry(name.clone())
+                        .or_insert_with(|| value.clone());
                 }
             }
         }
@@ -284,31 +504,39 @@ pub fn select_applications(applications: &[OktaApplication]) -> Vec<OktaApplicat
         .collect()
 }
 
-fn filter_identities(
-    okta_users: &[OktaUser],
-    beyond_identity_identities: &[Identity],
-) -> Vec<Identity> {
-    let okta_user_emails: Vec<&str> = okta_users
-        .iter()
-        .filter_map(|user| user.profile.email.as_deref())
-        .collect();
-    beyond_identity_identities
+// Source IDs of the applications offered for selection
+pub fn application_ids(applications: &[OktaApplication]) -> Vec<String> {
+    applications.iter().map(|app| app.id.clone()).collect()
+}
+
+// Describe the selected applications for a migration plan, skipping any
+// without a login link. With `federate`, OIDC applications are described as
+// federated where their settings allow it. `match_attribute` names
+// the profile attribute used for `custom-attribute` matching.
+pub fn source_applications(
+    okta_applications: &[OktaApplication],
+    federate: bool,
+    match_attribute: Option<&str>,
+) -> Vec<SourceApplication> {
+    okta_applications
         .iter()
-        .filter(|identity| {
-            identity
-                .traits
-                .primary_email_address
-                .as_deref()
-                .map_or(false, |email| okta_user_emails.contains(&email))
-        })
-        .cloned()
+        .filter_map(
+            |app| match source_application(app, federate, match_attribute) {
+                Ok(app) => Some(app),
+                Err(err) => {
+                    println!("Skipping {}: {}", app.label, err);
+                    None
+                }
+            },
+        )
         .collect()
 }
 
-pub async fn create_sso_config_and_assign_identities(
-    api_client: &ApiClient,
+fn source_application(
     okta_application: &OktaApplication,
-) -> Result<sso_configs::SsoConfigBookmark, BiError> {
+    federate: bool,
+    match_attribute: Option<&str>,
+) -> Result<SourceApplication, BiError> {
     let login_link = okta_application
         ._links
         .app_links
@@ -324,27 +552,116 @@ pub async fn create_sso_config_and_assign_identities(
             href: "https://static.byndid.com/logos/beyondidentity.png".to_string(),
             r#type: "image/png".to_string(),
         });
-    let sso_config = sso_configs::create_sso_config(
-        &api_client,
-        okta_application.label.clone(),
-        login_link.href.clone(),
-        Some(logo.href),
-    )
-    .await?;
-
-    let beyond_identity_identities = IdentitiesService::new()
-        .build()
-        .await
-        .list_identities(None, None)
-        .await?
-        .identities;
-    let filtered_identities = filter_identities(
-        &okta_application.embedded.as_ref().unwrap().users,
-        &beyond_identity_identities,
-    );
+    let users = okta_application
+        .embedded
+        .as_ref()
+        .map(|embedded| embedded.users.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|user| source_user(user, match_attribute))
+        .collect();
+    let (federation, untranslated) = match federate.then(|| federation(okta_application)) {
+        Some(Ok(federation)) => (federation, None),
+        Some(Err(reason)) => (None, Some((okta_application.sign_on_mode.clone(), reason))),
+        None => (None, None),
+    };
+
+    Ok(SourceApplication {
+        id: okta_application.id.clone(),
+        name: okta_application.label.clone(),
+        login_link: login_link.href.clone(),
+        icon: Some(logo.href),
+        users,
+        groups: okta_application.assigned_groups.clone(),
+        federation,
+        untranslated,
+    })
+}
 
-    sso_configs::assign_identities_to_sso_config(&api_client, &sso_config, &filtered_identities)
-        .await?;
+// Translate the sign-on settings of an application into a federated SSO
+// config. Bookmark applications stay bookmarks and anything else that cannot
+// be translated is returned with the reason.
+fn federation(okta_application: &OktaApplication) -> Result<Option<Federation>, String> {
+    let settings = &okta_application.settings;
+    match okta_application.sign_on_mode.as_str() {
+        "BOOKMARK" => Ok(None),
+        // There is no known Beyond Identity sign in URL to link the tile of a
+        // generic SAML SSO config to, so SAML tiles keep signing in through Okta
+        "SAML_2_0" => Err("SAML federation is not supported yet".to_string()),
+        "OPENID_CONNECT" => {
+            let client = settings
+                .oauth_client
+                .as_ref()
+                .ok_or("no OAuth client settings")?;
+            if client.application_type.as_deref() == Some("service") {
+                return Err("service applications do not sign users in".to_string());
+            }
+            if !client
+                .grant_types
+                .iter()
+                .any(|grant| grant == "authorization_code")
+            {
+                return Err("authorization code grant is not enabled".to_string());
+            }
+            if client.redirect_uris.is_empty() {
+                return Err("no redirect URIs".to_string());
+            }
+            // Without an initiate login URI, sign in starts from the origin
+            // of the relying party
+            let login_uri = match &client.initiate_login_uri {
+                Some(uri) => uri.clone(),
+                None => url::Url::parse(&client.redirect_uris[0])
+                    .map(|uri| uri.origin().ascii_serialization())
+                    .map_err(|_| "invalid redirect URI".to_string())?,
+            };
+            let auth_method = okta_application
+                .credentials
+                .oauth_client
+                .as_ref()
+                .and_then(|credentials| credentials.token_endpoint_auth_method.as_deref());
+            Ok(Some(Federation::Oidc {
+                redirect_uris: client.redirect_uris.clone(),
+                post_logout_redirect_uris: client.post_logout_redirect_uris.clone(),
+                public_client: auth_method == Some("none"),
+                login_uri,
+            }))
+        }
+        mode => Err(format!("sign-on mode {} is not supported", mode)),
+    }
+}
+
+// Describe the groups fetched by `fetch_okta_groups` for a migration plan
+pub fn source_groups(okta_groups: &[OktaGroup], match_attribute: Option<&str>) -> Vec<SourceGroup> {
+    okta_groups
+        .iter()
+        .map(|group| SourceGroup {
+            id: group.id.clone(),
+            name: group.profile.name.clone(),
+            description: group.profile.description.clone().unwrap_or_default(),
+            users: group
+                .members
+                .iter()
+                .map(|user| source_user(user, match_attribute))
+                .collect(),
+        })
+        .collect()
+}
 
-    Ok(sso_config)
+fn source_user(user: &OktaUser, match_attribute: Option<&str>) -> SourceUser {
+    let attribute = match_attribute
+        .and_then(|name| user.profile.attributes.get(name))
+        .and_then(|value| match value {
+            serde_json::Value::String(value) => Some(value.clone()),
+            serde_json::Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        });
+
+    SourceUser {
+        id: user.id.clone(),
+        email: user.profile.email.clone(),
+        username: user.profile.login.clone(),
+        given_name: user.profile.first_name.clone(),
+        family_name: user.profile.last_name.clone(),
+        attribute,
+    }
 }
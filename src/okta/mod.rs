@@ -1,2 +1,3 @@
+pub mod auth;
 pub mod command;
 pub mod fast_migrate;
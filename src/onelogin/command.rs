@@ -1,16 +1,20 @@
 use super::fast_migrate;
 
 use crate::beyond_identity::api::common::api_client::ApiClient;
+use crate::beyond_identity::helper::migration::{self, MatchStrategy, MigrationPlan};
 use crate::common::command::ambassador_impl_Executable;
+use crate::common::database::models::OneloginConfig;
 use crate::{
-    beyond_identity::api::common::middleware::rate_limit::RespectRateLimitMiddleware,
-    common::{command::Executable, error::BiError},
+    beyond_identity::api::common::middleware::{
+        rate_limit::RespectRateLimitMiddleware, with_transport,
+    },
+    common::{command::Executable, error::BiError, global, http_client},
 };
 
 use async_trait::async_trait;
 use clap::{Args, Subcommand};
-use reqwest::Client;
-use reqwest_middleware::ClientBuilder;
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use std::path::PathBuf;
 
 // ====================================
 // Onelogin Commands
@@ -22,45 +26,258 @@ use reqwest_middleware::ClientBuilder;
 pub enum OneloginCommands {
     /// Automatically migrate all OneLogin applications to Beyond Identity SSO and assign users based on existing OneLogin assignments.
     /// Each application tile in Beyond Identity will act as an opaque redirect to Onelogin.
+    /// Rerunning updates the tiles and assignments of previously migrated applications instead of creating duplicates,
+    /// only removing assignments that an earlier run made.
     FastMigrate(FastMigrate),
+
+    /// Export OneLogin applications, users, groups and user assignments to a snapshot file that
+    /// fast-migrate can read with `--from-snapshot`, without access to the OneLogin API.
+    Export(Export),
+
+    /// Compare the current OneLogin application assignments with the SSO configs they were
+    /// migrated to, listing identities to add or remove and applications added or deactivated
+    /// since.
+    Drift(Drift),
 }
 
 // ====================================
 // Onelogin FastMigrate
 // ====================================
 
+// Source recorded in migration plans and runs.
+const SOURCE: &str = "onelogin";
+
 #[derive(Args)]
-pub struct FastMigrate;
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct FastMigrate {
+    #[clap(subcommand)]
+    command: Option<FastMigrateCommands>,
+
+    /// Write the proposed SSO configs, their login links and logos, and the matched and unmatched
+    /// user assignments to this file without making any changes.
+    #[clap(long, conflicts_with = "apply")]
+    plan: Option<PathBuf>,
+
+    /// Create exactly the SSO configs and user assignments in a plan previously written with
+    /// `--plan`.
+    #[clap(long)]
+    apply: Option<PathBuf>,
+
+    /// Read applications and user assignments from a snapshot written by `bi onelogin export`
+    /// instead of the OneLogin API. No OneLogin configuration is needed.
+    #[clap(long, conflicts_with = "apply")]
+    from_snapshot: Option<PathBuf>,
+
+    /// How to match OneLogin users to identities. Strategies are tried in order until one matches
+    /// exactly one identity, e.g. `--match email,email-ci,username`.
+    #[clap(
+        long = "match",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "email",
+        conflicts_with = "apply"
+    )]
+    matching: Vec<MatchStrategy>,
+
+    /// Write the assigned users without a matching identity to this CSV file for manual
+    /// reconciliation.
+    #[clap(long, conflicts_with = "apply")]
+    unmatched_report: Option<PathBuf>,
+}
 
 #[async_trait]
 impl Executable for FastMigrate {
     async fn execute(&self) -> Result<(), BiError> {
-        let http_client = Client::new();
-        let onelogin_client = ClientBuilder::new(http_client.clone())
-            .with(RespectRateLimitMiddleware)
-            .build();
-        let api_client = ApiClient::new(None, None).await;
-        let onelogin_config = api_client.db.get_onelogin_config().await?.expect("Failed to load Onelogin Configuration. Make sure to setup Onelogin before running this command.");
+        if let Some(command) = &self.command {
+            return command.execute().await;
+        }
 
-        let onelogin_applications =
-            fast_migrate::fetch_onelogin_applications(&onelogin_client, &onelogin_config)
-                .await
-                .expect("Failed to fetch onelogin applications");
+        let api_client = ApiClient::new(None, None).await?;
+
+        if let Some(path) = &self.apply {
+            let plan = MigrationPlan::load(path, SOURCE)?;
+            println!("Applying plan for {}", plan.summary());
+            return plan.apply(&api_client).await;
+        }
+
+        check_matching(&self.matching)?;
+
+        let onelogin_applications = match &self.from_snapshot {
+            Some(path) => {
+                let snapshot = fast_migrate::OneLoginSnapshot::load(path)?;
+                println!(
+                    "Loaded {} exported at {}",
+                    snapshot.summary(),
+                    snapshot.exported_at
+                );
+                snapshot.applications
+            }
+            None => {
+                let onelogin_config = onelogin_config(&api_client).await?;
+                fast_migrate::fetch_onelogin_applications(&onelogin_client()?, &onelogin_config)
+                    .await?
+            }
+        };
 
         let selected_applications = fast_migrate::select_applications(&onelogin_applications);
-        for app in selected_applications {
-            match fast_migrate::create_sso_config_and_assign_identities(&api_client, &app).await {
-                Ok(sso_config) => println!(
-                    "SSO config created for {}: {}",
-                    app.name,
-                    serde_json::to_string_pretty(&sso_config).unwrap()
-                ),
-                Err(err) => {
-                    println!("Failed to create SSO config for {}: {}", app.name, err)
-                }
+        let plan = MigrationPlan::build(
+            &api_client,
+            SOURCE,
+            fast_migrate::application_ids(&onelogin_applications),
+            fast_migrate::source_applications(&selected_applications),
+            Vec::new(),
+            &self.matching,
+        )
+        .await?;
+        if let Some(path) = &self.unmatched_report {
+            plan.write_unmatched_report(path)?;
+        }
+
+        match &self.plan {
+            Some(path) => {
+                plan.save(path)?;
+                println!("Wrote plan for {} to {}", plan.summary(), path.display());
+                Ok(())
             }
+            None => plan.apply(&api_client).await,
         }
+    }
+}
+
+// ====================================
+// Onelogin Export
+// ====================================
+
+#[derive(Args)]
+pub struct Export {
+    /// File to write the snapshot to.
+    #[clap(long)]
+    out: PathBuf,
+}
+
+#[async_trait]
+impl Executable for Export {
+    async fn execute(&self) -> Result<(), BiError> {
+        let api_client = ApiClient::new(None, None).await?;
+        let onelogin_config = onelogin_config(&api_client).await?;
 
+        let snapshot =
+            fast_migrate::fetch_onelogin_snapshot(&onelogin_client()?, &onelogin_config).await?;
+        snapshot.save(&self.out)?;
+        println!("Exported {} to {}", snapshot.summary(), self.out.display());
         Ok(())
     }
 }
+
+// OneLogin users carry no custom attributes to match on
+fn check_matching(matching: &[MatchStrategy]) -> Result<(), BiError> {
+    if matching.contains(&MatchStrategy::CustomAttribute) {
+        return Err(BiError::StringError(
+            "custom-attribute matching is not supported for OneLogin".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// The OneLogin configuration, which must be set up before talking to OneLogin
+async fn onelogin_config(api_client: &ApiClient) -> Result<OneloginConfig, BiError> {
+    api_client.db()?.get_onelogin_config().await?.ok_or_else(|| {
+        BiError::StringError(
+            "Failed to load Onelogin Configuration. Make sure to setup Onelogin before running this command."
+                .to_string(),
+        )
+    })
+}
+
+// Client for the OneLogin API
+fn onelogin_client() -> Result<ClientWithMiddleware, BiError> {
+    let http_client = http_client::new(&global::options().http_options())?;
+    Ok(with_transport(ClientBuilder::new(http_client).with(RespectRateLimitMiddleware)).build())
+}
+
+// ====================================
+// Onelogin Drift
+// ====================================
+
+#[derive(Args)]
+pub struct Drift {
+    /// Reconcile the drift: assign identities, unassign those fast-migrate assigned, migrate
+    /// applications added since the last run as bookmark tiles and unassign what fast-migrate
+    /// assigned to deactivated ones. Undo with `fast-migrate rollback`.
+    #[clap(long)]
+    fix: bool,
+
+    /// How to match OneLogin users to identities. Strategies are tried in order until one matches
+    /// exactly one identity, e.g. `--match email,email-ci,username`. Identities created by
+    /// fast-migrate are always matched by external ID first.
+    #[clap(
+        long = "match",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "email"
+    )]
+    matching: Vec<MatchStrategy>,
+}
+
+#[async_trait]
+impl Executable for Drift {
+    async fn execute(&self) -> Result<(), BiError> {
+        check_matching(&self.matching)?;
+        let api_client = ApiClient::new(None, None).await?;
+        let onelogin_config = onelogin_config(&api_client).await?;
+
+        let onelogin_applications =
+            fast_migrate::fetch_onelogin_applications(&onelogin_client()?, &onelogin_config)
+                .await?;
+        migration::drift(
+            &api_client,
+            SOURCE,
+            fast_migrate::source_applications(&onelogin_applications),
+            Vec::new(),
+            &self.matching,
+            self.fix,
+        )
+        .await
+    }
+}
+
+// ====================================
+// Onelogin FastMigrate Runs
+// ====================================
+
+/// Commands for inspecting and undoing previous fast-migrate runs.
+#[derive(Subcommand, ambassador::Delegate)]
+#[delegate(Executable)]
+pub enum FastMigrateCommands {
+    /// Delete every SSO config and assignment created by a fast-migrate run and restore
+    /// the assignments `drift --fix` removed. Pre-existing resources are left untouched.
+    Rollback(Rollback),
+
+    /// List previous fast-migrate runs and what each of them created.
+    Runs(Runs),
+}
+
+#[derive(Args)]
+pub struct Rollback {
+    /// ID of the run to roll back, as printed at the end of the run or listed by `runs`.
+    run_id: String,
+}
+
+#[async_trait]
+impl Executable for Rollback {
+    async fn execute(&self) -> Result<(), BiError> {
+        let api_client = ApiClient::new(None, None).await?;
+        migration::rollback(&api_client, SOURCE, &self.run_id).await
+    }
+}
+
+#[derive(Args)]
+pub struct Runs;
+
+#[async_trait]
+impl Executable for Runs {
+    async fn execute(&self) -> Result<(), BiError> {
+        let api_client = ApiClient::new(None, None).await?;
+        migration::list_runs(&api_client, SOURCE).await
+    }
+}
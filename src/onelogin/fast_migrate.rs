@@ -1,14 +1,58 @@
-use crate::beyond_identity::api::common::{api_client::ApiClient, service::IdentitiesService};
-use crate::beyond_identity::api::identities::api::IdentitiesApi;
-use crate::beyond_identity::api::identities::types::Identity;
-use crate::beyond_identity::helper::sso_configs;
+use crate::beyond_identity::helper::migration::{self, SourceApplication, SourceUser};
 use crate::common::database::models::OneloginConfig;
 use crate::common::error::BiError;
+use crate::common::secret;
 
+use chrono::{DateTime, Utc};
 use reqwest_middleware::ClientWithMiddleware as Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::io::{self, Write};
+use std::path::Path;
+
+/// Everything fast-migrate reads from OneLogin, written by `bi onelogin export`
+/// so that a migration can be planned without access to the OneLogin API.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OneLoginSnapshot {
+    pub domain: String,
+    pub exported_at: DateTime<Utc>,
+    /// Applications along with their assigned users.
+    pub applications: Vec<OneLoginApplication>,
+    /// Every user of the account, including those without applications.
+    #[serde(default)]
+    pub users: Vec<OneLoginAccountUser>,
+    /// Every group of the account. Users name the one group they belong to.
+    #[serde(default)]
+    pub groups: Vec<OneLoginGroup>,
+}
+
+impl OneLoginSnapshot {
+    pub fn load(path: &Path) -> Result<Self, BiError> {
+        migration::read_json(path, "OneLogin snapshot")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BiError> {
+        migration::write_json(path, self)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut users: Vec<u64> = self
+            .applications
+            .iter()
+            .flat_map(|app| app.assigned_users.iter().map(|user| user.id))
+            .collect();
+        users.sort_unstable();
+        users.dedup();
+        format!(
+            "{} applications with {} assigned users, {} users and {} groups from {}",
+            self.applications.len(),
+            users.len(),
+            self.users.len(),
+            self.groups.len(),
+            self.domain
+        )
+    }
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -33,6 +77,36 @@ pub struct OneLoginUser {
     username: Option<String>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OneLoginAccountUser {
+    id: u64,
+    email: Option<String>,
+    username: Option<String>,
+    firstname: Option<String>,
+    lastname: Option<String>,
+    group_id: Option<u64>,
+    #[serde(default)]
+    role_ids: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OneLoginGroup {
+    id: u64,
+    name: String,
+}
+
+// Groups are only listed by version 1 of the API, which wraps results
+#[derive(Debug, Deserialize)]
+struct OneLoginV1Page<T> {
+    data: Vec<T>,
+    pagination: Option<OneLoginV1Pagination>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OneLoginV1Pagination {
+    after_cursor: Option<String>,
+}
+
 async fn get_onelogin_access_token(
     client: &Client,
     onelogin_config: &OneloginConfig,
@@ -78,6 +152,14 @@ async fn get_onelogin_access_token(
 pub async fn fetch_onelogin_applications(
     client: &Client,
     onelogin_config: &OneloginConfig,
+) -> Result<Vec<OneLoginApplication>, BiError> {
+    fetch_applications(client, &resolve_credentials(onelogin_config)?).await
+}
+
+// Fetch every application and its assigned users with resolved credentials
+async fn fetch_applications(
+    client: &Client,
+    onelogin_config: &OneloginConfig,
 ) -> Result<Vec<OneLoginApplication>, BiError> {
     let url = format!("{}/api/2/apps", onelogin_config.domain);
 
@@ -120,6 +202,144 @@ pub async fn fetch_onelogin_applications(
     Ok(applications)
 }
 
+// Resolve the credentials once rather than for every token request
+fn resolve_credentials(onelogin_config: &OneloginConfig) -> Result<OneloginConfig, BiError> {
+    Ok(OneloginConfig {
+        domain: onelogin_config.domain.clone(),
+        client_id: secret::resolve(&onelogin_config.client_id)?,
+        client_secret: secret::resolve(&onelogin_config.client_secret)?,
+    })
+}
+
+// Fetch every application and its assigned users, along with every user and
+// group of the account, for a snapshot
+pub async fn fetch_onelogin_snapshot(
+    client: &Client,
+    onelogin_config: &OneloginConfig,
+) -> Result<OneLoginSnapshot, BiError> {
+    let onelogin_config = &resolve_credentials(onelogin_config)?;
+    let applications = fetch_applications(client, onelogin_config).await?;
+
+    let access_token = get_onelogin_access_token(client, onelogin_config).await?;
+    let users = fetch_onelogin_users(client, onelogin_config, &access_token).await?;
+    let groups = fetch_onelogin_groups(client, onelogin_config, &access_token).await?;
+
+    Ok(OneLoginSnapshot {
+        domain: onelogin_config.domain.clone(),
+        exported_at: Utc::now(),
+        applications,
+        users,
+        groups,
+    })
+}
+
+// Fetch every user of the account, following the cursor version 2 of the API
+// returns in the `After-Cursor` header
+async fn fetch_onelogin_users(
+    client: &Client,
+    onelogin_config: &OneloginConfig,
+    access_token: &str,
+) -> Result<Vec<OneLoginAccountUser>, BiError> {
+    let base_url = format!("{}/api/2/users", onelogin_config.domain);
+    let mut users = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let url = match &cursor {
+            Some(cursor) => format!("{}?cursor={}", base_url, urlencoding::encode(cursor)),
+            None => base_url.clone(),
+        };
+        let response = client
+            .get(&url)
+            .header("Authorization", format!("Bearer: {}", access_token))
+            .send()
+            .await?;
+
+        let status = response.status();
+        let after_cursor = response
+            .headers()
+            .get("After-Cursor")
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty())
+            .map(String::from);
+        let response_text = response.text().await?;
+
+        log::debug!(
+            "{} response status: {} and text: {}",
+            url,
+            status,
+            response_text
+        );
+
+        if !status.is_success() {
+            return Err(BiError::RequestError(status, response_text));
+        }
+
+        let page: Vec<OneLoginAccountUser> = serde_json::from_str(&response_text)?;
+        users.extend(page);
+
+        match after_cursor {
+            Some(after_cursor) => cursor = Some(after_cursor),
+            None => break,
+        }
+    }
+
+    println!("Fetched {} users", users.len());
+    Ok(users)
+}
+
+// Fetch every group of the account
+async fn fetch_onelogin_groups(
+    client: &Client,
+    onelogin_config: &OneloginConfig,
+    access_token: &str,
+) -> Result<Vec<OneLoginGroup>, BiError> {
+    let base_url = format!("{}/api/1/groups", onelogin_config.domain);
+    let mut groups = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let url = match &cursor {
+            Some(cursor) => format!("{}?after_cursor={}", base_url, urlencoding::encode(cursor)),
+            None => base_url.clone(),
+        };
+        let response = client
+            .get(&url)
+            .header("Authorization", format!("Bearer: {}", access_token))
+            .send()
+            .await?;
+
+        let status = response.status();
+        let response_text = response.text().await?;
+
+        log::debug!(
+            "{} response status: {} and text: {}",
+            url,
+            status,
+            response_text
+        );
+
+        if !status.is_success() {
+            return Err(BiError::RequestError(status, response_text));
+        }
+
+        let page: OneLoginV1Page<OneLoginGroup> = serde_json::from_str(&response_text)?;
+        groups.extend(page.data);
+
+        match page
+            .pagination
+            .and_then(|pagination| pagination.after_cursor)
+            .filter(|cursor| !cursor.is_empty())
+        {
+            Some(after_cursor) => cursor = Some(after_cursor),
+            None => break,
+        }
+    }
+
+    println!("Fetched {} groups", groups.len());
+    Ok(groups)
+}
+
 async fn fetch_onelogin_application(
     client: &Client,
     onelogin_config: &OneloginConfig,
@@ -225,48 +445,37 @@ pub fn select_applications(applications: &[OneLoginApplication]) -> Vec<OneLogin
         .collect()
 }
 
-fn filter_identities(
-    onelogin_users: &[OneLoginUser],
-    beyond_identity_identities: &[Identity],
-) -> Vec<Identity> {
-    let onelogin_user_emails: Vec<&str> = onelogin_users
-        .iter()
-        .filter_map(|user| user.email.as_deref())
-        .collect();
-    beyond_identity_identities
+// Source IDs of the applications offered for selection
+pub fn application_ids(applications: &[OneLoginApplication]) -> Vec<String> {
+    applications.iter().map(|app| app.id.to_string()).collect()
+}
+
+// Describe the selected applications for a migration plan
+pub fn source_applications(
+    onelogin_applications: &[OneLoginApplication],
+) -> Vec<SourceApplication> {
+    onelogin_applications
         .iter()
-        .filter(|identity| {
-            identity
-                .traits
-                .primary_email_address
-                .as_deref()
-                .map_or(false, |email| onelogin_user_emails.contains(&email))
+        .map(|app| SourceApplication {
+            id: app.id.to_string(),
+            name: app.name.clone(),
+            login_link: app.login_link.clone(),
+            icon: app.icon.clone(),
+            users: app
+                .assigned_users
+                .iter()
+                .map(|user| SourceUser {
+                    id: user.id.to_string(),
+                    email: user.email.clone(),
+                    username: user.username.clone(),
+                    given_name: None,
+                    family_name: None,
+                    attribute: None,
+                })
+                .collect(),
+            groups: Vec::new(),
+            federation: None,
+            untranslated: None,
         })
-        .cloned()
         .collect()
 }
-
-pub async fn create_sso_config_and_assign_identities(
-    api_client: &ApiClient,
-    onelogin_application: &OneLoginApplication,
-) -> Result<sso_configs::SsoConfigBookmark, BiError> {
-    let name = onelogin_application.name.clone();
-    let login_link = onelogin_application.login_link.clone();
-    let icon_url = onelogin_application.icon.clone();
-    let sso_config =
-        sso_configs::create_sso_config(&api_client, name, login_link, icon_url).await?;
-
-    let beyond_identity_identities = IdentitiesService::new()
-        .build()
-        .await
-        .list_identities(None, None)
-        .await?
-        .identities;
-    let assigned_users = onelogin_application.assigned_users.as_ref();
-    let filtered_identities = filter_identities(assigned_users, &beyond_identity_identities);
-
-    sso_configs::assign_identities_to_sso_config(&api_client, &sso_config, &filtered_identities)
-        .await?;
-
-    Ok(sso_config)
-}